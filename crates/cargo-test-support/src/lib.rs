@@ -118,7 +118,7 @@ use std::process::{Command, Output};
 use std::str;
 use std::time::{self, Duration};
 
-use cargo::util::{is_ci, CargoResult, ProcessBuilder, ProcessError, Rustc};
+use cargo::util::{is_ci, process_error, CargoResult, ProcessBuilder, ProcessError, Rustc};
 use serde_json::{self, Value};
 use url::Url;
 
@@ -1419,14 +1419,59 @@ fn normalize_matcher(matcher: &str, cwd: Option<&Path>) -> String {
     let root = paths::root();
     let matcher = replace_path(&matcher, &root, "[ROOT]");
 
-    // Let's not deal with \r\n vs \n on windows...
-    let matcher = matcher.replace("\r", "");
+    // Let's not deal with \r\n vs \n, or drive letters, on windows...
+    let matcher = normalize_for_test(&matcher).replace("\r", "");
 
     // It's easier to read tabs in outputs if they don't show up as literal
     // hidden characters
     matcher.replace("\t", "<tab>")
 }
 
+/// Normalizes text, such as a rendered error, so that it compares the same on
+/// all platforms.
+///
+/// Path separators `\` become `/`, drive letters like `C:` are removed from
+/// the start of absolute paths, and `\r\n` line endings become `\n`.
+pub fn normalize_for_test(s: &str) -> String {
+    let s = s
+        .replace("\r\n", "\n")
+        .replace("\\\\", "/")
+        .replace("\\", "/");
+    let mut out = String::with_capacity(s.len());
+    let mut prev = None;
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        // A drive letter starts a word and is followed by `:/`.
+        let starts_word = prev.map_or(true, |p: char| !p.is_alphanumeric());
+        if c.is_ascii_alphabetic() && starts_word && chars.peek() == Some(&':') {
+            let mut rest = chars.clone();
+            rest.next();
+            if rest.peek() == Some(&'/') {
+                chars.next();
+                prev = Some(':');
+                continue;
+            }
+        }
+        out.push(c);
+        prev = Some(c);
+    }
+    out
+}
+
+#[test]
+fn normalize_for_test_works() {
+    assert_eq!(
+        normalize_for_test("failed to read `C:\\foo\\bar`\r\nnext"),
+        "failed to read `/foo/bar`\nnext"
+    );
+    assert_eq!(normalize_for_test("d:/x and c:/y"), "/x and /y");
+    assert_eq!(
+        normalize_for_test("see https://example.com, key: value"),
+        "see https://example.com, key: value"
+    );
+    assert_eq!(normalize_for_test("/a/b\nc"), "/a/b\nc");
+}
+
 #[test]
 fn lines_match_works() {
     assert!(lines_match("a b", "a b"));
@@ -1639,6 +1684,7 @@ fn substitute_macros(input: &str) -> String {
         ("[NOTE]", "note:"),
         ("[DOCUMENTING]", " Documenting"),
         ("[FRESH]", "       Fresh"),
+        ("[DIRTY]", "       Dirty"),
         ("[UPDATING]", "    Updating"),
         ("[ADDING]", "      Adding"),
         ("[REMOVING]", "    Removing"),
@@ -1649,6 +1695,7 @@ fn substitute_macros(input: &str) -> String {
         ("[UPLOADING]", "   Uploading"),
         ("[VERIFYING]", "   Verifying"),
         ("[ARCHIVING]", "   Archiving"),
+        ("[CHECKSUM]", "    Checksum"),
         ("[INSTALLING]", "  Installing"),
         ("[REPLACING]", "   Replacing"),
         ("[UNPACKING]", "   Unpacking"),
@@ -1798,6 +1845,43 @@ pub fn slow_cpu_multiplier(main: u64) -> Duration {
     Duration::from_secs(*SLOW_CPU_MULTIPLIER * main)
 }
 
+/// Runs `cmd` and checks that it exits with `expected_status` and that its
+/// stdout contains each of the strings in `stdout_contains`.
+///
+/// Panics with a [`ProcessError`] showing the command, its exit status and
+/// its captured output if any of those don't hold. Returns the output
+/// otherwise, for further checks.
+pub fn assert_process(cmd: &mut Command, expected_status: i32, stdout_contains: &[&str]) -> Output {
+    let output = match cmd.output() {
+        Ok(output) => output,
+        Err(e) => panic!("failed to run {:?}: {}", cmd, e),
+    };
+    if output.status.code() != Some(expected_status) {
+        panic!(
+            "{}",
+            process_error(
+                &format!("expected {:?} to exit with {}", cmd, expected_status),
+                Some(output.status),
+                Some(&output),
+            )
+        );
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    for expected in stdout_contains {
+        if !stdout.contains(expected) {
+            panic!(
+                "{}",
+                process_error(
+                    &format!("expected stdout of {:?} to contain {:?}", cmd, expected),
+                    Some(output.status),
+                    Some(&output),
+                )
+            );
+        }
+    }
+    output
+}
+
 pub fn command_is_available(cmd: &str) -> bool {
     if let Err(e) = process(cmd).arg("-V").exec_with_output() {
         eprintln!("{} not available, skipping tests", cmd);
@@ -145,14 +145,20 @@ impl Registry {
     pub fn add_owners(&mut self, krate: &str, owners: &[&str]) -> Result<String> {
         let body = serde_json::to_string(&OwnersReq { users: owners })?;
         let body = self.put(&format!("/crates/{}/owners", krate), body.as_bytes())?;
-        assert!(serde_json::from_str::<OwnerResponse>(&body)?.ok);
-        Ok(serde_json::from_str::<OwnerResponse>(&body)?.msg)
+        let response = serde_json::from_str::<OwnerResponse>(&body)?;
+        if !response.ok {
+            bail!("the registry refused to add owners: {}", response.msg);
+        }
+        Ok(response.msg)
     }
 
     pub fn remove_owners(&mut self, krate: &str, owners: &[&str]) -> Result<()> {
         let body = serde_json::to_string(&OwnersReq { users: owners })?;
         let body = self.delete(&format!("/crates/{}/owners", krate), Some(body.as_bytes()))?;
-        assert!(serde_json::from_str::<OwnerResponse>(&body)?.ok);
+        let response = serde_json::from_str::<OwnerResponse>(&body)?;
+        if !response.ok {
+            bail!("the registry refused to remove owners: {}", response.msg);
+        }
         Ok(())
     }
 
@@ -262,13 +268,17 @@ impl Registry {
 
     pub fn yank(&mut self, krate: &str, version: &str) -> Result<()> {
         let body = self.delete(&format!("/crates/{}/{}/yank", krate, version), None)?;
-        assert!(serde_json::from_str::<R>(&body)?.ok);
+        if !serde_json::from_str::<R>(&body)?.ok {
+            bail!("the registry refused to yank {} {}", krate, version);
+        }
         Ok(())
     }
 
     pub fn unyank(&mut self, krate: &str, version: &str) -> Result<()> {
         let body = self.put(&format!("/crates/{}/{}/unyank", krate, version), &[])?;
-        assert!(serde_json::from_str::<R>(&body)?.ok);
+        if !serde_json::from_str::<R>(&body)?.ok {
+            bail!("the registry refused to unyank {} {}", krate, version);
+        }
         Ok(())
     }
 
@@ -1,4 +1,6 @@
 use cargo::core::features;
+use cargo::core::shell::ErrorFormat;
+use cargo::util::errors::NoSubcommandError;
 use cargo::{self, drop_print, drop_println, CliResult, Config};
 use clap::{AppSettings, Arg, ArgMatches};
 
@@ -18,6 +20,11 @@ pub fn main(config: &mut Config) -> CliResult {
                 let cmd = &e.info.as_ref().unwrap()[0].to_owned();
                 return super::execute_external_subcommand(config, cmd, &[cmd, "--help"])
                     .map_err(|_| e.into());
+            } else if e.kind == clap::ErrorKind::UnknownArgument
+                && !has_subcommand_without(unknown_arg(&e))
+            {
+                let err = NoSubcommandError::new(unknown_arg(&e));
+                return Err(CliError::new(err.into(), 1));
             } else {
                 return Err(e.into());
             }
@@ -110,6 +117,27 @@ Run with 'cargo -Z [FLAG] [SUBCOMMAND]'"
     execute_subcommand(config, cmd, subcommand_args)
 }
 
+/// The argument clap reported as unexpected.
+fn unknown_arg(e: &clap::Error) -> &str {
+    e.info
+        .as_ref()
+        .and_then(|info| info.first())
+        .map_or("", |arg| arg.as_str())
+}
+
+/// Whether the command line names a subcommand once the unexpected `arg` is
+/// removed, used to tell a bad global flag apart from a bad subcommand flag.
+///
+/// If the command line still fails to parse, this returns `true` so that
+/// clap's own error is reported.
+fn has_subcommand_without(arg: &str) -> bool {
+    let args = std::env::args_os().filter(|a| a.to_str() != Some(arg));
+    match cli().get_matches_from_safe(args) {
+        Ok(args) => args.subcommand_name().is_some(),
+        Err(_) => true,
+    }
+}
+
 pub fn get_version_string(is_verbose: bool) -> String {
     let version = cargo::version();
     let mut version_string = version.to_string();
@@ -183,6 +211,10 @@ fn config_configure(
         args.is_present("quiet") || subcommand_args.is_present("quiet") || global_args.quiet;
     let global_color = global_args.color; // Extract so it can take reference.
     let color = args.value_of("color").or_else(|| global_color.as_deref());
+    let global_error_format = global_args.error_format;
+    let error_format = args
+        .value_of("error-format")
+        .or_else(|| global_error_format.as_deref());
     let frozen = args.is_present("frozen") || global_args.frozen;
     let locked = args.is_present("locked") || global_args.locked;
     let offline = args.is_present("offline") || global_args.offline;
@@ -205,6 +237,43 @@ fn config_configure(
         &unstable_flags,
         &config_args,
     )?;
+    // The command line takes precedence over `CARGO_TERM_ERROR_FORMAT`, which
+    // takes precedence over `term.error-format` in the config files.
+    let error_format = match error_format {
+        Some(error_format) => {
+            config
+                .cli_unstable()
+                .fail_if_stable_opt("--error-format", 8680)?;
+            Some((error_format.to_string(), "`--error-format` flag"))
+        }
+        // A config value is shared by every invocation, so on its own it must
+        // not break stable ones.
+        None => match config.get::<Option<String>>("term.error-format")? {
+            Some(_) if !config.cli_unstable().unstable_options => {
+                config.shell().warn(
+                    "config `term.error-format` ignored, \
+                     the -Zunstable-options command-line flag is required",
+                )?;
+                None
+            }
+            error_format => error_format.map(|f| (f, "`term.error-format` config value")),
+        },
+    };
+    if let Some((error_format, what)) = error_format {
+        let error_format = match error_format.as_str() {
+            "full" => ErrorFormat::Full,
+            "short" => ErrorFormat::Short,
+            other => {
+                return Err(anyhow::format_err!(
+                    "the {} must be `full` or `short`, but found `{}`",
+                    what,
+                    other
+                )
+                .into())
+            }
+        };
+        config.shell().set_error_format(error_format);
+    }
     Ok(())
 }
 
@@ -227,6 +296,7 @@ struct GlobalArgs {
     verbose: u32,
     quiet: bool,
     color: Option<String>,
+    error_format: Option<String>,
     frozen: bool,
     locked: bool,
     offline: bool,
@@ -240,6 +310,7 @@ impl GlobalArgs {
             verbose: args.occurrences_of("verbose") as u32,
             quiet: args.is_present("quiet"),
             color: args.value_of("color").map(|s| s.to_string()),
+            error_format: args.value_of("error-format").map(|s| s.to_string()),
             frozen: args.is_present("frozen"),
             locked: args.is_present("locked"),
             offline: args.is_present("offline"),
@@ -309,6 +380,13 @@ See 'cargo help <command>' for more information on a specific command.\n",
                 .value_name("WHEN")
                 .global(true),
         )
+        .arg(
+            opt("error-format", "Error format: full, short (unstable)")
+                .value_name("FMT")
+                .possible_values(&["full", "short"])
+                .global(true)
+                .hidden(true),
+        )
         .arg(opt("frozen", "Require Cargo.lock and cache are up to date").global(true))
         .arg(opt("locked", "Require Cargo.lock is up to date").global(true))
         .arg(opt("offline", "Run without accessing the network").global(true))
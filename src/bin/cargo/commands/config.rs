@@ -0,0 +1,42 @@
+use crate::command_prelude::*;
+use cargo::ops;
+
+pub fn cli() -> App {
+    subcommand("config")
+        .about("Inspect configuration values")
+        .after_help("This command is unstable, and requires `-Z unstable-options`.")
+        .settings(&[AppSettings::SubcommandRequiredElseHelp])
+        .subcommand(
+            subcommand("get")
+                .about("Print the effective value of a config key, or of all config")
+                .arg(Arg::with_name("key").help("The dotted config key to print"))
+                .arg(opt(
+                    "show-secrets",
+                    "Show secret values such as registry tokens",
+                )),
+        )
+}
+
+pub fn exec(config: &mut Config, args: &ArgMatches<'_>) -> CliResult {
+    config
+        .cli_unstable()
+        .fail_if_stable_command("config", 9301)?;
+    match args.subcommand() {
+        ("get", Some(args)) => {
+            config.load_credentials()?;
+            let opts = ops::ConfigGetOptions {
+                key: args.value_of("key"),
+                show_secrets: args.is_present("show-secrets"),
+            };
+            if !ops::config_get(config, &opts)? {
+                let key = opts.key.unwrap();
+                return Err(CliError::new(
+                    anyhow::format_err!("config value `{}` is not set", key),
+                    1,
+                ));
+            }
+        }
+        (cmd, _) => panic!("unexpected command `{}`", cmd),
+    }
+    Ok(())
+}
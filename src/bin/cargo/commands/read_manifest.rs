@@ -1,5 +1,8 @@
 use crate::command_prelude::*;
 
+use cargo::core::Package;
+use cargo::util::CargoResult;
+
 pub fn cli() -> App {
     subcommand("read-manifest")
         .about(
@@ -15,6 +18,29 @@ Deprecated, use `cargo metadata --no-deps` instead.\
 
 pub fn exec(config: &mut Config, args: &ArgMatches<'_>) -> CliResult {
     let ws = args.workspace(config)?;
-    config.shell().print_json(&ws.current()?);
+    let output = manifest_json(ws.current()?)?;
+    config.shell().print_json(&output);
     Ok(())
 }
+
+/// Extends the serialized package with the fields `cargo metadata` doesn't
+/// report: the remaining package metadata, and the paths of path
+/// dependencies.
+fn manifest_json(pkg: &Package) -> CargoResult<serde_json::Value> {
+    let mut output = serde_json::to_value(pkg)?;
+    let manmeta = pkg.manifest().metadata();
+    output["homepage"] = manmeta.homepage.clone().into();
+    output["documentation"] = manmeta.documentation.clone().into();
+    for (dep, json) in pkg
+        .dependencies()
+        .iter()
+        .zip(output["dependencies"].as_array_mut().unwrap())
+    {
+        let source_id = dep.source_id();
+        if source_id.is_path() {
+            let path = source_id.url().to_file_path().unwrap();
+            json["path"] = path.to_string_lossy().into_owned().into();
+        }
+    }
+    Ok(output)
+}
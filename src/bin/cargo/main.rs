@@ -41,8 +41,15 @@ fn main() {
         Err(e) => Err(CliError::from(e)),
     };
 
+    let code = cargo::exit_code_with_warnings(
+        &result,
+        config.shell().warning_count(),
+        config.cli_unstable().warnings_exit_code,
+    );
+    cargo::util::profile::print_summary();
     match result {
         Err(e) => cargo::exit_with_error(e, &mut *config.shell()),
+        Ok(()) if code != 0 => std::process::exit(code),
         Ok(()) => {}
     }
 }
@@ -1,5 +1,6 @@
 use crate::core::compiler::CompileKind;
 use crate::core::interning::InternedString;
+use crate::util::config::Value;
 use crate::util::ProcessBuilder;
 use crate::util::{CargoResult, Config, RustfixDiagnosticServer};
 use anyhow::bail;
@@ -48,6 +49,10 @@ impl BuildConfig {
     /// * `target.$target.ar`
     /// * `target.$target.linker`
     /// * `target.$target.libfoo.metadata`
+    ///
+    /// `build.jobs` and `build.target` are overridden by `CARGO_BUILD_*`
+    /// environment variables, which are in turn overridden by `jobs` and
+    /// `requested_targets` if given.
     pub fn new(
         config: &Config,
         jobs: Option<u32>,
@@ -66,6 +71,10 @@ impl BuildConfig {
                  its environment, ignoring the `-j` parameter",
             )?;
         }
+        if jobs.is_none() && cfg.jobs == Some(0) {
+            let def = config.get::<Value<u32>>("build.jobs")?.definition;
+            bail!("`build.jobs` must be at least 1, but found 0 in {}", def)
+        }
         let jobs = jobs.or(cfg.jobs).unwrap_or(::num_cpus::get() as u32);
 
         Ok(BuildConfig {
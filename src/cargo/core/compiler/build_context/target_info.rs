@@ -1,6 +1,7 @@
 use crate::core::compiler::{BuildOutput, CompileKind, CompileMode, CompileTarget, CrateType};
 use crate::core::{Dependency, Target, TargetKind, Workspace};
 use crate::util::config::{Config, StringList, TargetConfig};
+use crate::util::errors::TargetNotInstalledError;
 use crate::util::{CargoResult, CargoResultExt, ProcessBuilder, Rustc};
 use cargo_platform::{Cfg, CfgExpr};
 use std::cell::RefCell;
@@ -170,9 +171,21 @@ impl TargetInfo {
         process.arg("--print=sysroot");
         process.arg("--print=cfg");
 
-        let (output, error) = rustc
-            .cached_output(&process)
-            .chain_err(|| "failed to run `rustc` to learn about target-specific information")?;
+        let (output, error) = match rustc.cached_output(&process) {
+            Ok(output) => output,
+            Err(e) => {
+                if let CompileKind::Target(target) = kind {
+                    if !is_known_target(rustc, target) {
+                        let not_installed =
+                            TargetNotInstalledError::new(target.rustc_target(), false);
+                        return Err(e.context(not_installed));
+                    }
+                }
+                return Err(
+                    e.context("failed to run `rustc` to learn about target-specific information")
+                );
+            }
+        };
 
         let mut lines = output.lines();
         let mut map = HashMap::new();
@@ -528,6 +541,22 @@ fn parse_crate_type(
     Ok(Some((prefix.to_string(), suffix.to_string())))
 }
 
+/// Whether `rustc --print target-list` includes `target`.
+///
+/// Custom targets are assumed to be known, as is everything if the list
+/// can't be printed.
+fn is_known_target(rustc: &Rustc, target: CompileTarget) -> bool {
+    if target.rustc_target().ends_with(".json") {
+        return true;
+    }
+    let mut process = rustc.process();
+    process.arg("--print=target-list").env_remove("RUSTC_LOG");
+    match rustc.cached_output(&process) {
+        Ok((output, _error)) => output.lines().any(|l| l.trim() == target.rustc_target()),
+        Err(_) => true,
+    }
+}
+
 /// Helper for creating an error message when parsing rustc output fails.
 fn output_err_info(cmd: &ProcessBuilder, stdout: &str, stderr: &str) -> String {
     let mut result = format!("command was: {}\n", cmd);
@@ -641,7 +670,12 @@ fn env_args(
         return Ok(rustflags);
     }
 
-    // Then the `build.rustflags` value.
+    // Then the `build.rustflags` value, which `CARGO_BUILD_RUSTFLAGS`
+    // overrides rather than being joined with like other arrays.
+    let env_key = format!("CARGO_BUILD_{}", name.to_uppercase());
+    if let Some((_, flags)) = config.env_config_vars().find(|(k, _)| *k == env_key) {
+        return Ok(flags.split_whitespace().map(str::to_string).collect());
+    }
     let build = config.build_config()?;
     let list = if name == "rustflags" {
         &build.rustflags
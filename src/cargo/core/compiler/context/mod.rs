@@ -7,7 +7,7 @@ use jobserver::Client;
 
 use crate::core::compiler::{self, compilation, Unit};
 use crate::core::PackageId;
-use crate::util::errors::{CargoResult, CargoResultExt};
+use crate::util::errors::{CargoResult, CargoResultExt, OutputCollisionError};
 use crate::util::profile;
 
 use super::build_plan::BuildPlan;
@@ -286,6 +286,8 @@ impl<'a, 'cfg> Context<'a, 'cfg> {
                 targets.insert(target, layout);
             }
         }
+        // Everything cargo needs to know about rustc has been queried by now.
+        self.bcx.rustc().write_cache();
         self.primary_packages
             .extend(self.bcx.roots.iter().map(|u| u.pkg.package_id()));
 
@@ -425,7 +427,24 @@ impl<'a, 'cfg> Context<'a, 'cfg> {
                                 path: &PathBuf,
                                 suggestion: &str|
          -> CargoResult<()> {
-            if unit.target.name() == other_unit.target.name() {
+            if unit.target.name() == other_unit.target.name() && !unit.mode.is_doc() {
+                // Both units would write the same file, and whichever
+                // finishes last wins, so refuse to start the build.
+                let describe = |unit: &Unit| {
+                    format!(
+                        "{} target `{}` in package `{}`",
+                        unit.target.kind().description(),
+                        unit.target.name(),
+                        unit.pkg.package_id()
+                    )
+                };
+                Err(OutputCollisionError {
+                    unit: describe(unit),
+                    other_unit: describe(other_unit),
+                    path: path.clone(),
+                }
+                .into())
+            } else if unit.target.name() == other_unit.target.name() {
                 self.bcx.config.shell().warn(format!(
                     "output filename collision.\n\
                      {}\
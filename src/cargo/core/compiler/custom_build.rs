@@ -3,7 +3,7 @@ use super::{fingerprint, Context, Unit};
 use crate::core::compiler::context::Metadata;
 use crate::core::compiler::job_queue::JobState;
 use crate::core::{profiles::ProfileRoot, PackageId};
-use crate::util::errors::{CargoResult, CargoResultExt};
+use crate::util::errors::{BuildScriptError, BuildScriptOutputError, CargoResult, CargoResultExt};
 use crate::util::machine_message::{self, Message};
 use crate::util::{self, internal, paths, profile};
 use cargo_platform::Cfg;
@@ -208,6 +208,10 @@ fn build_work(cx: &mut Context<'_, '_>, unit: &Unit) -> CargoResult<Job> {
         cmd.env("CARGO_MANIFEST_LINKS", links);
     }
 
+    if let Some(limit) = bcx.config.build_config()?.script_output_limit {
+        cmd.output_limit(limit as usize);
+    }
+
     // Be sure to pass along all enabled features for this package, this is the
     // last piece of statically known information that we have.
     for feat in &unit.features {
@@ -365,7 +369,7 @@ fn build_work(cx: &mut Context<'_, '_>, unit: &Unit) -> CargoResult<Job> {
                 },
                 true,
             )
-            .chain_err(|| format!("failed to run custom build command for `{}`", pkg_name));
+            .map_err(|e| anyhow::Error::from(BuildScriptError::new(e, pkg_name.clone())));
 
         if let Err(error) = output {
             insert_warnings_in_build_outputs(
@@ -478,6 +482,10 @@ impl BuildOutput {
 
     // Parses the output of a script.
     // The `pkg_name` is used for error messages.
+    //
+    // Lines with an unknown key are metadata for dependent packages, so only
+    // the lines of known instructions are checked, and an invalid one is a
+    // `BuildScriptOutputError`.
     pub fn parse(
         input: &[u8],
         pkg_name: &str,
@@ -493,7 +501,6 @@ impl BuildOutput {
         let mut rerun_if_changed = Vec::new();
         let mut rerun_if_env_changed = Vec::new();
         let mut warnings = Vec::new();
-        let whence = format!("build script of `{}`", pkg_name);
 
         for line in input.split(|b| *b == b'\n') {
             let line = match str::from_utf8(line) {
@@ -514,30 +521,53 @@ impl BuildOutput {
             let mut iter = data.splitn(2, '=');
             let key = iter.next();
             let value = iter.next();
+            let invalid = |reason: &str| BuildScriptOutputError::new(pkg_name, line, reason);
             let (key, value) = match (key, value) {
                 (Some(a), Some(b)) => (a, b.trim_end()),
                 // Line started with `cargo:` but didn't match `key=value`.
-                _ => anyhow::bail!("Wrong output in {}: `{}`", whence, line),
+                _ => return Err(invalid("expected `cargo:KEY=VALUE`").into()),
             };
 
             // This will rewrite paths if the target directory has been moved.
-            let value = value.replace(
-                script_out_dir_when_generated.to_str().unwrap(),
-                script_out_dir.to_str().unwrap(),
-            );
+            // Paths which aren't valid UTF-8 can't appear in the output as
+            // written, so there's nothing to rewrite for them.
+            let value = match (
+                script_out_dir_when_generated.to_str(),
+                script_out_dir.to_str(),
+            ) {
+                (Some(from), Some(to)) => value.replace(from, to),
+                _ => value.to_string(),
+            };
 
             // Keep in sync with TargetConfig::new.
             match key {
                 "rustc-flags" => {
-                    let (paths, links) = BuildOutput::parse_rustc_flags(&value, &whence)?;
+                    let (paths, links) = rustc_flags(&value).map_err(invalid)?;
                     library_links.extend(links.into_iter());
                     library_paths.extend(paths.into_iter());
                 }
-                "rustc-link-lib" => library_links.push(value.to_string()),
-                "rustc-link-search" => library_paths.push(PathBuf::from(value)),
+                "rustc-link-lib" => {
+                    // Either `NAME` or `KIND=NAME`.
+                    if value.rsplit('=').next().unwrap().is_empty() {
+                        return Err(invalid("the library name is empty").into());
+                    }
+                    library_links.push(value.to_string())
+                }
+                "rustc-link-search" => {
+                    // Either `PATH` or `KIND=PATH`.
+                    if value.rsplit('=').next().unwrap().is_empty() {
+                        return Err(invalid("the search path is empty").into());
+                    }
+                    library_paths.push(PathBuf::from(value))
+                }
                 "rustc-cdylib-link-arg" => linker_args.push(value.to_string()),
-                "rustc-cfg" => cfgs.push(value.to_string()),
-                "rustc-env" => env.push(BuildOutput::parse_rustc_env(&value, &whence)?),
+                "rustc-cfg" => {
+                    if value.is_empty() {
+                        return Err(invalid("the cfg is empty").into());
+                    }
+                    cfgs.push(value.to_string())
+                }
+                "rustc-env" => env.push(rustc_env(&value).map_err(invalid)?),
                 "warning" => warnings.push(value.to_string()),
                 "rerun-if-changed" => rerun_if_changed.push(PathBuf::from(value)),
                 "rerun-if-env-changed" => rerun_if_env_changed.push(value.to_string()),
@@ -562,55 +592,55 @@ impl BuildOutput {
         value: &str,
         whence: &str,
     ) -> CargoResult<(Vec<PathBuf>, Vec<String>)> {
-        let value = value.trim();
-        let mut flags_iter = value
-            .split(|c: char| c.is_whitespace())
-            .filter(|w| w.chars().any(|c| !c.is_whitespace()));
-        let (mut library_paths, mut library_links) = (Vec::new(), Vec::new());
-
-        while let Some(flag) = flags_iter.next() {
-            if flag.starts_with("-l") || flag.starts_with("-L") {
-                // Check if this flag has no space before the value as is
-                // common with tools like pkg-config
-                // e.g. -L/some/dir/local/lib or -licui18n
-                let (flag, mut value) = flag.split_at(2);
-                if value.is_empty() {
-                    value = match flags_iter.next() {
-                        Some(v) => v,
-                        None => anyhow::bail! {
-                            "Flag in rustc-flags has no value in {}: {}",
-                            whence,
-                            value
-                        },
-                    }
+        rustc_flags(value)
+            .map_err(|reason| anyhow::format_err!("{} in {}: `{}`", reason, whence, value.trim()))
+    }
+}
+
+/// Parses the value of `rustc-flags`, returning why it's invalid on error.
+fn rustc_flags(value: &str) -> Result<(Vec<PathBuf>, Vec<String>), &'static str> {
+    let value = value.trim();
+    let mut flags_iter = value
+        .split(|c: char| c.is_whitespace())
+        .filter(|w| w.chars().any(|c| !c.is_whitespace()));
+    let (mut library_paths, mut library_links) = (Vec::new(), Vec::new());
+
+    while let Some(flag) = flags_iter.next() {
+        if flag.starts_with("-l") || flag.starts_with("-L") {
+            // Check if this flag has no space before the value as is
+            // common with tools like pkg-config
+            // e.g. -L/some/dir/local/lib or -licui18n
+            let (flag, mut value) = flag.split_at(2);
+            if value.is_empty() {
+                value = match flags_iter.next() {
+                    Some(v) => v,
+                    None => return Err("a flag has no value"),
                 }
+            }
 
-                match flag {
-                    "-l" => library_links.push(value.to_string()),
-                    "-L" => library_paths.push(PathBuf::from(value)),
+            match flag {
+                "-l" => library_links.push(value.to_string()),
+                "-L" => library_paths.push(PathBuf::from(value)),
 
-                    // This was already checked above
-                    _ => unreachable!(),
-                };
-            } else {
-                anyhow::bail!(
-                    "Only `-l` and `-L` flags are allowed in {}: `{}`",
-                    whence,
-                    value
-                )
-            }
+                // This was already checked above
+                _ => unreachable!(),
+            };
+        } else {
+            return Err("only `-l` and `-L` flags are allowed");
         }
-        Ok((library_paths, library_links))
     }
+    Ok((library_paths, library_links))
+}
 
-    pub fn parse_rustc_env(value: &str, whence: &str) -> CargoResult<(String, String)> {
-        let mut iter = value.splitn(2, '=');
-        let name = iter.next();
-        let val = iter.next();
-        match (name, val) {
-            (Some(n), Some(v)) => Ok((n.to_owned(), v.to_owned())),
-            _ => anyhow::bail!("Variable rustc-env has no value in {}: {}", whence, value),
-        }
+/// Parses the `NAME=VALUE` value of `rustc-env`, returning why it's invalid on
+/// error.
+fn rustc_env(value: &str) -> Result<(String, String), &'static str> {
+    let mut iter = value.splitn(2, '=');
+    let name = iter.next();
+    let val = iter.next();
+    match (name, val) {
+        (Some(n), Some(v)) => Ok((n.to_owned(), v.to_owned())),
+        _ => Err("the variable has no value"),
     }
 }
 
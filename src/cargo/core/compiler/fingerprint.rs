@@ -100,8 +100,8 @@
 //! `target/{debug,release}/.fingerprint/` directory. Each Unit is stored in a
 //! separate directory. Each Unit directory contains:
 //!
-//! - A file with a 16 hex-digit hash. This is the Fingerprint hash, used for
-//!   quick loading and comparison.
+//! - A file with a 16 hex-digit hash. This is the `ShortDigest` of the `.json`
+//!   file below, used for quick loading and comparison.
 //! - A `.json` file that contains details about the Fingerprint. This is only
 //!   used to log details about *why* a fingerprint is considered dirty.
 //!   `CARGO_LOG=cargo::core::compiler::fingerprint=trace cargo build` can be
@@ -310,6 +310,8 @@
 
 use std::collections::hash_map::{Entry, HashMap};
 use std::env;
+use std::fmt;
+use std::fs;
 use std::hash::{self, Hasher};
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
@@ -323,9 +325,11 @@ use serde::ser;
 use serde::{Deserialize, Serialize};
 
 use crate::core::compiler::unit_graph::UnitDep;
+use crate::core::shell::Verbosity;
 use crate::core::{InternedString, Package};
 use crate::util;
-use crate::util::errors::{CargoResult, CargoResultExt};
+use crate::util::errors::{CargoResult, CargoResultExt, FingerprintError};
+use crate::util::hasher::ShortDigest;
 use crate::util::paths;
 use crate::util::{internal, profile};
 
@@ -366,8 +370,23 @@ pub fn prepare_target(cx: &mut Context<'_, '_>, unit: &Unit, force: bool) -> Car
     // information about failed comparisons to aid in debugging.
     let fingerprint = calculate(cx, unit)?;
     let mtime_on_use = cx.bcx.config.cli_unstable().mtime_on_use;
-    let compare = compare_old_fingerprint(&loc, &*fingerprint, mtime_on_use);
+    let compare = compare_old_fingerprint(unit, &loc, &*fingerprint, mtime_on_use);
     log_compare(unit, &compare);
+    if let Err(e) = &compare {
+        if let Some(e) = e.downcast_ref::<FingerprintError>() {
+            bcx.config.shell().warn(e)?;
+        }
+        if bcx.config.shell().verbosity() == Verbosity::Verbose && loc.exists() {
+            let reason = dirty_reason(&loc, &fingerprint, e, unit.pkg.root(), &target_root(cx));
+            let msg = format!(
+                "{} {}: {}",
+                unit.pkg,
+                unit.target.description_named(),
+                reason
+            );
+            bcx.config.shell().status("Dirty", msg)?;
+        }
+    }
 
     // If our comparison failed (e.g., we're going to trigger a rebuild of this
     // crate), then we also ensure the source of the crate passes all
@@ -419,6 +438,17 @@ pub fn prepare_target(cx: &mut Context<'_, '_>, unit: &Unit, force: bool) -> Car
     // fingerprint at step 3 ensures that Cargo never mistakes a partially
     // written output as up-to-date.
     if loc.exists() {
+        // An empty fingerprint means that the last build of this unit never
+        // completed, so its outputs may be partially written. Remove them so
+        // nothing can pick them up if this build doesn't complete either.
+        if fs::metadata(&loc).map(|m| m.len() == 0).unwrap_or(false) {
+            for output in fingerprint.outputs.iter() {
+                if output.exists() {
+                    debug!("removing incomplete output {:?}", output);
+                    paths::remove_file(output)?;
+                }
+            }
+        }
         // Truncate instead of delete so that compare_old_fingerprint will
         // still log the reason for the fingerprint failure instead of just
         // reporting "failed to read fingerprint" during the next build if
@@ -459,12 +489,31 @@ pub fn prepare_target(cx: &mut Context<'_, '_>, unit: &Unit, force: bool) -> Car
             write_fingerprint(&loc, &fingerprint)
         })
     } else {
-        Work::new(move |_| write_fingerprint(&loc, &fingerprint))
+        Work::new(move |_| {
+            // Only claim the unit is fresh once all of its outputs are in
+            // place, otherwise leave the fingerprint empty so the next build
+            // starts over.
+            for output in fingerprint.outputs.iter() {
+                // Outputs may legitimately be empty, such as the metadata of
+                // a checked test, so only their presence is verified.
+                if !output.exists() {
+                    debug!("output {:?} is missing, not fingerprinting", output);
+                    return Ok(());
+                }
+            }
+            write_fingerprint(&loc, &fingerprint)
+        })
     };
 
     Ok(Job::new(write_fingerprint, Dirty))
 }
 
+/// Returns the key identifying `unit` in the shared artifact cache, or `None`
+/// if its fingerprint has not been calculated yet.
+pub fn shared_cache_key(cx: &Context<'_, '_>, unit: &Unit) -> Option<String> {
+    cx.fingerprints.get(unit).map(|f| util::to_hex(f.hash()))
+}
+
 /// Dependency edge information for fingerprints. This is generated for each
 /// dependency and is stored in a `Fingerprint` below.
 #[derive(Clone)]
@@ -676,6 +725,9 @@ enum LocalFingerprint {
 
 enum StaleFile {
     Missing(PathBuf),
+    /// The dep-info file could not be parsed, so the files the unit depends
+    /// on are unknown.
+    Malformed(PathBuf),
     Changed {
         reference: PathBuf,
         reference_mtime: FileTime,
@@ -703,10 +755,12 @@ impl LocalFingerprint {
             // unit has never been compiled!
             LocalFingerprint::CheckDepInfo { dep_info } => {
                 let dep_info = target_root.join(dep_info);
-                if let Some(paths) = parse_dep_info(pkg_root, target_root, &dep_info)? {
-                    Ok(find_stale_file(mtime_cache, &dep_info, paths.iter()))
-                } else {
-                    Ok(Some(StaleFile::Missing(dep_info)))
+                match parse_dep_info(pkg_root, target_root, &dep_info) {
+                    Ok(Some(paths)) => Ok(find_stale_file(mtime_cache, &dep_info, paths.iter())),
+                    Ok(None) => Ok(Some(StaleFile::Missing(dep_info))),
+                    // Without the list of files we can't tell whether any of
+                    // them changed, so fall back to rebuilding.
+                    Err(_) => Ok(Some(StaleFile::Malformed(dep_info))),
                 }
             }
 
@@ -1149,6 +1203,9 @@ impl StaleFile {
             StaleFile::Missing(path) => {
                 info!("stale: missing {:?}", path);
             }
+            StaleFile::Malformed(path) => {
+                info!("stale: malformed dep-info {:?}", path);
+            }
             StaleFile::Changed {
                 reference,
                 reference_mtime,
@@ -1277,7 +1334,7 @@ fn calculate_normal(cx: &mut Context<'_, '_>, unit: &Unit) -> CargoResult<Finger
         profile: profile_hash,
         // Note that .0 is hashed here, not .1 which is the cwd. That doesn't
         // actually affect the output artifact so there's no need to hash it.
-        path: util::hash_u64(super::path_args(cx.bcx, unit).0),
+        path: util::hash_u64(paths::comparable_path(&super::path_args(cx.bcx, unit).0)),
         features: format!("{:?}", unit.features),
         deps,
         local: Mutex::new(local),
@@ -1515,10 +1572,11 @@ fn write_fingerprint(loc: &Path, fingerprint: &Fingerprint) -> CargoResult<()> {
     // This is mostly so outside tools can reliably find out what rust version this file is for,
     // as we can use the full hash.
     let hash = fingerprint.hash();
-    debug!("write fingerprint ({:x}) : {}", hash, loc.display());
-    paths::write(loc, util::to_hex(hash).as_bytes())?;
-
     let json = serde_json::to_string(fingerprint).unwrap();
+    let digest = ShortDigest::of(json.as_bytes());
+    debug!("write fingerprint ({}) : {}", digest, loc.display());
+    paths::write(loc, digest.to_string().as_bytes())?;
+
     if cfg!(debug_assertions) {
         let f: Fingerprint = serde_json::from_str(&json).unwrap();
         assert_eq!(f.hash(), hash);
@@ -1551,12 +1609,28 @@ fn target_root(cx: &Context<'_, '_>) -> PathBuf {
     cx.bcx.ws.target_dir().into_path_unlocked()
 }
 
+/// Compares `new_fingerprint` with the fingerprint at `loc` from the previous
+/// build, returning an error describing why the unit is dirty.
+///
+/// A missing fingerprint, or an empty one left by an interrupted build, means
+/// the unit was never built successfully. A fingerprint which exists but
+/// can't be read, or isn't valid JSON, is a `FingerprintError`, since the
+/// rebuild it causes would otherwise go unexplained.
 fn compare_old_fingerprint(
+    unit: &Unit,
     loc: &Path,
     new_fingerprint: &Fingerprint,
     mtime_on_use: bool,
 ) -> CargoResult<()> {
-    let old_fingerprint_short = paths::read(loc)?;
+    let corrupt = |path: &Path, e: &dyn fmt::Display| {
+        let unit = format!("{} {}", unit.pkg, unit.target.description_named());
+        anyhow::Error::from(FingerprintError::new(unit, path, e.to_string()))
+    };
+    let old_fingerprint_short = match paths::read(loc) {
+        Ok(short) => short,
+        Err(e) if loc.exists() => return Err(corrupt(loc, &e.root_cause())),
+        Err(e) => return Err(e),
+    };
 
     if mtime_on_use {
         // update the mtime so other cleaners know we used it
@@ -1565,27 +1639,166 @@ fn compare_old_fingerprint(
         paths::set_file_time_no_err(loc, t);
     }
 
-    let new_hash = new_fingerprint.hash();
+    let new_json = serde_json::to_string(new_fingerprint)?;
+    let new_digest = ShortDigest::of(new_json.as_bytes());
 
-    if util::to_hex(new_hash) == old_fingerprint_short && new_fingerprint.fs_status.up_to_date() {
+    if new_digest.to_string() == old_fingerprint_short && new_fingerprint.fs_status.up_to_date() {
         return Ok(());
     }
 
-    let old_fingerprint_json = paths::read(&loc.with_extension("json"))?;
-    let old_fingerprint: Fingerprint = serde_json::from_str(&old_fingerprint_json)
-        .chain_err(|| internal("failed to deserialize json"))?;
-    // Fingerprint can be empty after a failed rebuild (see comment in prepare_target).
-    if !old_fingerprint_short.is_empty() {
-        debug_assert_eq!(util::to_hex(old_fingerprint.hash()), old_fingerprint_short);
-    }
+    let json_loc = loc.with_extension("json");
+    let old_fingerprint_json = match paths::read(&json_loc) {
+        Ok(json) => json,
+        Err(e) if old_fingerprint_short.is_empty() => return Err(e),
+        Err(e) => return Err(corrupt(&json_loc, &e.root_cause())),
+    };
+    // A fingerprint which parses as JSON but doesn't match `Fingerprint`, such
+    // as one written by another version of cargo, is just rebuilt.
+    let old_fingerprint: Fingerprint =
+        serde_json::from_str(&old_fingerprint_json).map_err(|e| {
+            if e.is_syntax() || e.is_eof() {
+                corrupt(&json_loc, &e)
+            } else {
+                e.into()
+            }
+        })?;
     let result = new_fingerprint.compare(&old_fingerprint);
     assert!(result.is_err());
     result
 }
 
+/// A user-facing explanation of why a unit with a previous fingerprint on
+/// disk is being rebuilt, printed under `-v`.
+enum DirtyReason {
+    /// The previous fingerprint is missing or unreadable, for example because
+    /// the previous build of this unit did not finish.
+    NoPreviousFingerprint,
+    FeaturesChanged {
+        old: String,
+        new: String,
+    },
+    /// The profile, target configuration, `RUSTFLAGS`, or other config
+    /// settings that change how the unit is compiled.
+    ProfileChanged,
+    DependencyRebuilt(InternedString),
+    /// An environment variable tracked with `cargo:rerun-if-env-changed`.
+    EnvVarChanged(String),
+    /// A file the unit depends on, relative to the package root if possible.
+    SourceFileChanged(PathBuf),
+    /// The dep-info file, relative to the target root, could not be parsed.
+    MalformedDepInfo(PathBuf),
+    /// Anything else, described by the message from `Fingerprint::compare`.
+    Other(String),
+}
+
+impl fmt::Display for DirtyReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DirtyReason::NoPreviousFingerprint => write!(f, "no previous fingerprint"),
+            DirtyReason::FeaturesChanged { old, new } => {
+                write!(f, "features changed from {} to {}", old, new)
+            }
+            DirtyReason::ProfileChanged => write!(f, "profile flags changed"),
+            DirtyReason::DependencyRebuilt(name) => {
+                write!(f, "dependency `{}` was rebuilt", name)
+            }
+            DirtyReason::EnvVarChanged(var) => {
+                write!(f, "environment variable `{}` changed", var)
+            }
+            DirtyReason::SourceFileChanged(path) => {
+                write!(f, "source file `{}` changed", path.display())
+            }
+            DirtyReason::MalformedDepInfo(path) => {
+                write!(f, "dep-info file `{}` is malformed", path.display())
+            }
+            DirtyReason::Other(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+/// Compares the components of `new` with the fingerprint stored at `loc` to
+/// find the most useful explanation of why the unit is dirty.
+fn dirty_reason(
+    loc: &Path,
+    new: &Fingerprint,
+    compare_err: &anyhow::Error,
+    pkg_root: &Path,
+    target_root: &Path,
+) -> DirtyReason {
+    let old_short = paths::read(loc).unwrap_or_default();
+    let old = paths::read(&loc.with_extension("json"))
+        .ok()
+        .and_then(|json| serde_json::from_str::<Fingerprint>(&json).ok());
+    let old = match old {
+        Some(old) if !old_short.is_empty() => old,
+        _ => return DirtyReason::NoPreviousFingerprint,
+    };
+    if new.features != old.features {
+        return DirtyReason::FeaturesChanged {
+            old: old.features,
+            new: new.features.clone(),
+        };
+    }
+    if new.profile != old.profile
+        || new.target != old.target
+        || new.rustflags != old.rustflags
+        || new.config != old.config
+    {
+        return DirtyReason::ProfileChanged;
+    }
+    for (a, b) in new.deps.iter().zip(old.deps.iter()) {
+        if a.fingerprint.hash() != b.fingerprint.hash() {
+            return DirtyReason::DependencyRebuilt(a.name);
+        }
+    }
+    let new_local = new.local.lock().unwrap();
+    let old_local = old.local.lock().unwrap();
+    for (a, b) in new_local.iter().zip(old_local.iter()) {
+        if let (
+            LocalFingerprint::RerunIfEnvChanged { var, val: a_val },
+            LocalFingerprint::RerunIfEnvChanged { val: b_val, .. },
+        ) = (a, b)
+        {
+            if a_val != b_val {
+                return DirtyReason::EnvVarChanged(var.clone());
+            }
+        }
+    }
+    // The hashes match, so the filesystem must be stale. Mirror the order of
+    // `check_filesystem`: stale dependencies first, then our own files.
+    if let Some(dep) = new
+        .deps
+        .iter()
+        .find(|d| !d.fingerprint.fs_status.up_to_date())
+    {
+        return DirtyReason::DependencyRebuilt(dep.name);
+    }
+    let mut mtime_cache = HashMap::new();
+    for local in new_local.iter() {
+        match local.find_stale_file(&mut mtime_cache, pkg_root, target_root) {
+            Ok(Some(StaleFile::Changed { stale, .. })) => {
+                let path = stale.strip_prefix(pkg_root).unwrap_or(&stale);
+                return DirtyReason::SourceFileChanged(path.to_path_buf());
+            }
+            Ok(Some(StaleFile::Malformed(path))) => {
+                let path = path.strip_prefix(target_root).unwrap_or(&path);
+                return DirtyReason::MalformedDepInfo(path.to_path_buf());
+            }
+            _ => {}
+        }
+    }
+    DirtyReason::Other(compare_err.to_string())
+}
+
 fn log_compare(unit: &Unit, compare: &CargoResult<()>) {
     let ce = match compare {
-        Ok(..) => return,
+        Ok(..) => {
+            debug!(
+                "fingerprint fresh for {}/{:?}/{:?}",
+                unit.pkg, unit.mode, unit.target,
+            );
+            return;
+        }
         Err(e) => e,
     };
     info!(
@@ -206,6 +206,8 @@ enum Message {
     BuildPlanMsg(String, ProcessBuilder, Arc<Vec<OutputFile>>),
     Stdout(String),
     Stderr(String),
+    Warning(String),
+    Error(String),
     FixDiagnostic(diagnostic_server::Message),
     Token(io::Result<Acquired>),
     Finish(JobId, Artifact, CargoResult<()>),
@@ -240,6 +242,17 @@ impl<'a> JobState<'a> {
         self.messages.push_bounded(Message::Stderr(stderr));
     }
 
+    /// Records that the compiler emitted a warning, see
+    /// `Shell::warning_count`.
+    pub fn warning_emitted(&self, message: String) {
+        self.messages.push(Message::Warning(message));
+    }
+
+    /// Records that the compiler emitted an error, see `Shell::error_count`.
+    pub fn error_emitted(&self, message: String) {
+        self.messages.push(Message::Error(message));
+    }
+
     /// A method used to signal to the coordinator thread that the rmeta file
     /// for an rlib has been produced. This is only called for some rmeta
     /// builds when required, and can be called at any time before a job ends.
@@ -506,6 +519,12 @@ impl<'cfg> DrainState<'cfg> {
                 shell.print_ansi(err.as_bytes())?;
                 shell.err().write_all(b"\n")?;
             }
+            Message::Warning(message) => {
+                cx.bcx.config.shell().record_warning(&message);
+            }
+            Message::Error(message) => {
+                cx.bcx.config.shell().record_error(&message);
+            }
             Message::FixDiagnostic(msg) => {
                 self.print.print(&msg)?;
             }
@@ -645,13 +664,16 @@ impl<'cfg> DrainState<'cfg> {
         // successful and otherwise wait for pending work to finish if it failed
         // and then immediately return.
         let mut error = None;
+        // Every error of the build, once `error` is set, for the summary shown
+        // with `-Z error-summary`.
+        let mut failed = Vec::new();
         // CAUTION! Do not use `?` or break out of the loop early. Every error
         // must be handled in such a way that the loop is still allowed to
         // drain event messages.
         loop {
             if error.is_none() {
                 if let Err(e) = self.spawn_work_if_possible(cx, jobserver_helper, scope) {
-                    self.handle_error(&mut cx.bcx.config.shell(), &mut error, e);
+                    self.handle_error(&mut cx.bcx.config.shell(), &mut error, &mut failed, e);
                 }
             }
 
@@ -662,7 +684,7 @@ impl<'cfg> DrainState<'cfg> {
             }
 
             if let Err(e) = self.grant_rustc_token_requests() {
-                self.handle_error(&mut cx.bcx.config.shell(), &mut error, e);
+                self.handle_error(&mut cx.bcx.config.shell(), &mut error, &mut failed, e);
             }
 
             // And finally, before we block waiting for the next event, drop any
@@ -672,7 +694,12 @@ impl<'cfg> DrainState<'cfg> {
             // to the jobserver itself.
             for event in self.wait_for_events() {
                 if let Err(event_err) = self.handle_event(cx, jobserver_helper, plan, event) {
-                    self.handle_error(&mut cx.bcx.config.shell(), &mut error, event_err);
+                    self.handle_error(
+                        &mut cx.bcx.config.shell(),
+                        &mut error,
+                        &mut failed,
+                        event_err,
+                    );
                 }
             }
         }
@@ -696,7 +723,7 @@ impl<'cfg> DrainState<'cfg> {
             opt_type += " + debuginfo";
         }
 
-        let time_elapsed = util::elapsed(cx.bcx.config.creation_time().elapsed());
+        let elapsed = cx.bcx.config.creation_time().elapsed();
         if let Err(e) = self.timings.finished(cx.bcx, &error) {
             if error.is_some() {
                 crate::display_error(&e, &mut cx.bcx.config.shell());
@@ -707,6 +734,9 @@ impl<'cfg> DrainState<'cfg> {
         if cx.bcx.build_config.emit_json() {
             let msg = machine_message::BuildFinished {
                 success: error.is_none(),
+                profile: profile_name.to_string(),
+                profile_description: opt_type.clone(),
+                elapsed: elapsed.as_secs_f64(),
             }
             .to_json_string();
             if let Err(e) = writeln!(cx.bcx.config.shell().out(), "{}", msg) {
@@ -719,12 +749,20 @@ impl<'cfg> DrainState<'cfg> {
         }
 
         if let Some(e) = error {
+            crate::display_error_summary(&failed, &mut cx.bcx.config.shell());
             Some(e)
         } else if self.queue.is_empty() && self.pending_queue.is_empty() {
-            let message = format!(
+            let mut message = format!(
                 "{} [{}] target(s) in {}",
-                profile_name, opt_type, time_elapsed
+                profile_name,
+                opt_type,
+                util::elapsed(elapsed)
             );
+            let shell = cx.bcx.config.shell();
+            if shell.warning_count() > 0 || shell.error_count() > 0 {
+                message.push_str(&format!(" ({})", shell.diagnostic_summary()));
+            }
+            drop(shell);
             if !cx.bcx.build_config.build_plan {
                 // It doesn't really matter if this fails.
                 drop(cx.bcx.config.shell().status("Finished", message));
@@ -740,15 +778,18 @@ impl<'cfg> DrainState<'cfg> {
         &self,
         shell: &mut Shell,
         err_state: &mut Option<anyhow::Error>,
+        failed: &mut Vec<anyhow::Error>,
         new_err: anyhow::Error,
     ) {
         if err_state.is_some() {
             // Already encountered one error.
             log::warn!("{:?}", new_err);
+            failed.push(new_err);
         } else {
             if !self.active.is_empty() {
                 crate::display_error(&new_err, shell);
                 drop(shell.warn("build failed, waiting for other jobs to finish..."));
+                failed.push(new_err);
                 *err_state = Some(anyhow::format_err!("build failed"));
             } else {
                 *err_state = Some(new_err);
@@ -11,6 +11,10 @@
 //!     # Cache of `rustc -Vv` output for performance.
 //!     .rustc-info.json
 //!
+//!     # File locked by builds to keep `cargo clean` from removing the
+//!     # directory while they are using it.
+//!     .cargo-lock
+//!
 //!     # All final artifacts are linked into this directory from `deps`.
 //!     # Note that named profiles will soon be included as separate directories
 //!     # here. They have a restricted format, similar to Rust identifiers, so
@@ -101,7 +105,7 @@
 use crate::core::compiler::CompileTarget;
 use crate::core::Workspace;
 use crate::util::paths;
-use crate::util::{CargoResult, FileLock};
+use crate::util::{CargoResult, FileLock, Filesystem};
 use std::path::{Path, PathBuf};
 
 /// Contains the paths of all target output locations.
@@ -128,6 +132,10 @@ pub struct Layout {
     /// The lockfile for a build (`.cargo-lock`). Will be unlocked when this
     /// struct is `drop`ped.
     _lock: FileLock,
+    /// A shared lock on the lockfile of the target directory
+    /// (`$target/.cargo-lock`), which `cargo clean` locks exclusively before
+    /// removing it.
+    _target_dir_lock: FileLock,
 }
 
 impl Layout {
@@ -142,7 +150,10 @@ impl Layout {
         target: Option<CompileTarget>,
         dest: &str,
     ) -> CargoResult<Layout> {
-        let mut root = ws.target_dir();
+        let mut root = Filesystem::new(paths::strip_verbatim_prefix(
+            ws.target_dir().as_path_unlocked(),
+        ));
+        let target_dir_lock = root.open_ro_create(".cargo-lock", ws.config(), "build directory")?;
         if let Some(target) = target {
             root.push(target.short_name());
         }
@@ -172,6 +183,7 @@ impl Layout {
             root,
             dest,
             _lock: lock,
+            _target_dir_lock: target_dir_lock,
         })
     }
 
@@ -1,8 +1,7 @@
 use super::unit_graph::UnitGraph;
 use crate::core::{PackageId, Resolve};
-use crate::util::errors::CargoResult;
+use crate::util::errors::{CargoResult, LinksConflictError, MultipleVersionsError};
 use std::collections::{HashMap, HashSet};
-use std::fmt::Write;
 
 /// Validate `links` field does not conflict between packages.
 pub fn validate_links(resolve: &Resolve, unit_graph: &UnitGraph) -> CargoResult<()> {
@@ -28,30 +27,14 @@ pub fn validate_links(resolve: &Resolve, unit_graph: &UnitGraph) -> CargoResult<
             None => continue,
         };
         if let Some(&prev) = links.get(lib) {
-            let pkg = unit.pkg.package_id();
-
-            let describe_path = |pkgid: PackageId| -> String {
-                let dep_path = resolve.path_to_top(&pkgid);
-                let mut dep_path_desc = format!("package `{}`", dep_path[0]);
-                for dep in dep_path.iter().skip(1) {
-                    write!(dep_path_desc, "\n    ... which is depended on by `{}`", dep).unwrap();
-                }
-                dep_path_desc
+            let path = |pkgid: PackageId| -> Vec<PackageId> {
+                resolve.path_to_top(&pkgid).into_iter().cloned().collect()
             };
-
-            anyhow::bail!(
-                "multiple packages link to native library `{}`, \
-                 but a native library can be linked only once\n\
-                 \n\
-                 {}\nlinks to native library `{}`\n\
-                 \n\
-                 {}\nalso links to native library `{}`",
-                lib,
-                describe_path(prev),
-                lib,
-                describe_path(pkg),
-                lib
-            )
+            let id = unit.pkg.package_id();
+            if prev.name() == id.name() {
+                return Err(MultipleVersionsError::new(lib, vec![path(prev), path(id)]).into());
+            }
+            return Err(LinksConflictError::new(lib, path(prev), path(id)).into());
         }
         links.insert(lib.to_string(), unit.pkg.package_id());
     }
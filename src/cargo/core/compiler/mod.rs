@@ -14,6 +14,7 @@ mod links;
 mod lto;
 mod output_depinfo;
 pub mod rustdoc;
+mod shared_cache;
 pub mod standard_lib;
 mod timings;
 mod unit;
@@ -24,7 +25,7 @@ use std::env;
 use std::ffi::{OsStr, OsString};
 use std::fs::{self, File};
 use std::io::{BufRead, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use anyhow::Error;
@@ -49,8 +50,11 @@ use self::unit_graph::UnitDep;
 pub use crate::core::compiler::unit::{Unit, UnitInterner};
 use crate::core::manifest::TargetSourcePath;
 use crate::core::profiles::{PanicStrategy, Profile, Strip};
+use crate::core::shell::Verbosity;
 use crate::core::{Edition, Feature, InternedString, PackageId, Target};
-use crate::util::errors::{self, CargoResult, CargoResultExt, ProcessError, VerboseError};
+use crate::util::errors::{
+    self, Annotated, CargoResult, CargoResultExt, ProcessError, VerboseError,
+};
 use crate::util::machine_message::Message;
 use crate::util::{self, machine_message, ProcessBuilder};
 use crate::util::{internal, join_paths, paths, profile};
@@ -137,6 +141,9 @@ fn compile<'cfg>(
         job.before(if job.freshness() == Freshness::Dirty {
             let work = if unit.mode.is_doc() {
                 rustdoc(cx, unit)?
+            } else if shared_cache::is_eligible(cx, unit) {
+                let compile = rustc(cx, unit, exec)?;
+                shared_cache::restore_or_compile(cx, unit, compile)?
             } else {
                 rustc(cx, unit, exec)?
             };
@@ -174,12 +181,39 @@ fn compile<'cfg>(
     Ok(())
 }
 
+/// The message used when rustc fails to compile `unit`.
+///
+/// Every file in `tests/` is its own crate, so for integration tests the
+/// message names the file that failed to compile.
+fn compile_err_msg(unit: &Unit) -> String {
+    let name = unit.pkg.name();
+    if !unit.target.is_test() {
+        return format!("could not compile `{}`.", name);
+    }
+    match unit.target.src_path().path() {
+        Some(path) => {
+            let path = path.strip_prefix(unit.pkg.root()).unwrap_or(path);
+            format!(
+                "could not compile `{}` (integration test `{}` at `{}`).",
+                name,
+                unit.target.name(),
+                path.display()
+            )
+        }
+        None => format!(
+            "could not compile `{}` (integration test `{}`).",
+            name,
+            unit.target.name()
+        ),
+    }
+}
+
 fn rustc(cx: &mut Context<'_, '_>, unit: &Unit, exec: &Arc<dyn Executor>) -> CargoResult<Work> {
     let mut rustc = prepare_rustc(cx, &unit.target.rustc_crate_types(), unit)?;
     let build_plan = cx.bcx.build_config.build_plan;
 
-    let name = unit.pkg.name().to_string();
     let buildkey = unit.buildkey();
+    let compile_err_msg = compile_err_msg(unit);
 
     add_cap_lints(cx.bcx, unit, &mut rustc);
 
@@ -225,6 +259,7 @@ fn rustc(cx: &mut Context<'_, '_>, unit: &Unit, exec: &Arc<dyn Executor>) -> Car
     let fingerprint_dir = cx.files().fingerprint_dir(unit);
     let script_metadata = cx.find_build_script_metadata(unit.clone());
     let is_local = unit.is_local();
+    let show_rerun = cx.bcx.config.shell().verbosity() == Verbosity::Verbose;
 
     return Ok(Work::new(move |state| {
         // Only at runtime have we discovered what the extra -L and -l
@@ -280,6 +315,11 @@ fn rustc(cx: &mut Context<'_, '_>, unit: &Unit, exec: &Arc<dyn Executor>) -> Car
         if build_plan {
             state.build_plan(buildkey, rustc.clone(), outputs.clone());
         } else {
+            let rerun = if show_rerun {
+                Some(rustc.rerun_command())
+            } else {
+                None
+            };
             exec.exec(
                 rustc,
                 package_id,
@@ -289,7 +329,18 @@ fn rustc(cx: &mut Context<'_, '_>, unit: &Unit, exec: &Arc<dyn Executor>) -> Car
                 &mut |line| on_stderr_line(state, line, package_id, &target, &mut output_options),
             )
             .map_err(verbose_if_simple_exit_code)
-            .chain_err(|| format!("could not compile `{}`.", name))?;
+            .map_err(|err| match rerun {
+                Some(cmd) => err.context(format!(
+                    "the failed command can be rerun with:\n    {}",
+                    cmd.replace('\n', "\n    ")
+                )),
+                None => err,
+            })
+            .map_err(|err| {
+                Annotated::new(err, compile_err_msg)
+                    .field("package", package_id)
+                    .field("target", target.name())
+            })?;
         }
 
         if rustc_dep_info_loc.exists() {
@@ -660,12 +711,26 @@ fn path_args(bcx: &BuildContext<'_, '_>, unit: &Unit) -> (PathBuf, PathBuf) {
         TargetSourcePath::Metabuild => unit.pkg.manifest().metabuild_path(bcx.ws.target_dir()),
     };
     assert!(src.is_absolute());
+    // rustc can't handle verbatim paths in all cases, see
+    // `strip_verbatim_prefix`.
+    let src = paths::strip_verbatim_prefix(&src);
+    let ws_root = paths::strip_verbatim_prefix(ws_root);
     if unit.pkg.package_id().source_id().is_path() {
-        if let Ok(path) = src.strip_prefix(ws_root) {
-            return (path.to_path_buf(), ws_root.to_path_buf());
+        // Fall back to comparing normalized paths, in case `src` and `ws_root`
+        // are written differently, such as with a different drive letter case.
+        let relative = src
+            .strip_prefix(&ws_root)
+            .map(Path::to_path_buf)
+            .or_else(|_| {
+                paths::comparable_path(&src)
+                    .strip_prefix(paths::comparable_path(&ws_root))
+                    .map(Path::to_path_buf)
+            });
+        if let Ok(path) = relative {
+            return (path, ws_root);
         }
     }
-    (src, unit.pkg.root().to_path_buf())
+    (src, paths::strip_verbatim_prefix(unit.pkg.root()))
 }
 
 fn add_path_args(bcx: &BuildContext<'_, '_>, unit: &Unit, cmd: &mut ProcessBuilder) {
@@ -1195,6 +1260,22 @@ fn on_stderr_line_inner(
         }
     };
 
+    // Count warnings for `-Z warnings-exit-code` and the summary at the end
+    // of the build. rustc's own "N warnings emitted" and "aborting due to N
+    // previous errors" summaries are not diagnostics of their own.
+    #[derive(serde::Deserialize)]
+    struct Diagnostic {
+        level: String,
+        message: String,
+    }
+    if let Ok(diag) = serde_json::from_str::<Diagnostic>(compiler_message.get()) {
+        if diag.level == "warning" && !diag.message.ends_with(" emitted") {
+            state.warning_emitted(diag.message);
+        } else if diag.level == "error" && !diag.message.starts_with("aborting due to") {
+            state.error_emitted(diag.message);
+        }
+    }
+
     // Depending on what we're emitting from Cargo itself, we figure out what to
     // do with this JSON message.
     match options.format {
@@ -18,12 +18,16 @@
 //! to be used with external build systems so that they can detect if Cargo
 //! needs to be re-executed. It includes all the entries from the `rustc`
 //! dep-info file, and extends it with any `rerun-if-changed` entries from
-//! build scripts. It also includes sources from any path dependencies. Registry
-//! dependencies are not included under the assumption that changes to them can
-//! be detected via changes to `Cargo.lock`.
+//! build scripts. It also includes sources and manifests from any path
+//! dependencies, along with `Cargo.lock` and the config files Cargo read.
+//! Registry dependencies are not included under the assumption that changes
+//! to them can be detected via changes to `Cargo.lock`.
+//!
+//! The file is only written when the build succeeded, and is written
+//! atomically so that a build system never reads a truncated file.
 
 use std::collections::{BTreeSet, HashSet};
-use std::io::{BufWriter, Write};
+use std::fmt::Write;
 use std::path::{Path, PathBuf};
 
 use log::debug;
@@ -78,6 +82,11 @@ fn add_deps_for_unit(
         }
     }
 
+    // Changes to the manifest of a local package can change how it's built
+    if unit.is_local() {
+        deps.insert(unit.pkg.manifest_path().to_path_buf());
+    }
+
     // Add rerun-if-changed dependencies
     if let Some(metadata) = cx.find_build_script_metadata(unit.clone()) {
         if let Some(output) = cx
@@ -110,6 +119,11 @@ pub fn output_depinfo(cx: &mut Context<'_, '_>, unit: &Unit) -> CargoResult<()>
     let mut deps = BTreeSet::new();
     let mut visited = HashSet::new();
     let success = add_deps_for_unit(&mut deps, cx, unit, &mut visited).is_ok();
+    let lock_file = bcx.ws.root().join("Cargo.lock");
+    if lock_file.exists() {
+        deps.insert(lock_file);
+    }
+    deps.extend(bcx.config.loaded_files()?);
     let basedir_string;
     let basedir = match bcx.config.build_config()?.dep_info_basedir.clone() {
         Some(value) => {
@@ -147,12 +161,12 @@ pub fn output_depinfo(cx: &mut Context<'_, '_>, unit: &Unit) -> CargoResult<()>
                 }
 
                 // Otherwise write it all out
-                let mut outfile = BufWriter::new(paths::create(output_path)?);
-                write!(outfile, "{}:", target_fn)?;
+                let mut contents = format!("{}:", target_fn);
                 for dep in &deps {
-                    write!(outfile, " {}", dep)?;
+                    write!(contents, " {}", dep)?;
                 }
-                writeln!(outfile)?;
+                contents.push('\n');
+                paths::write_atomic(output_path, contents)?;
 
             // dep-info generation failed, so delete output file. This will
             // usually cause the build system to always rerun the build
@@ -0,0 +1,203 @@
+//! A cache of compiled dependencies shared by every project on the machine,
+//! enabled with `-Z shared-cache`.
+//!
+//! Only units of packages from registries and git repositories are cached.
+//! Their fingerprint already covers everything that goes into compiling them
+//! (package identity, features, profile, rustc version, target and the
+//! fingerprints of their dependencies), so the fingerprint hash is used as
+//! the cache key. Each entry is a directory under
+//! `$CARGO_HOME/artifact-cache/<fingerprint>/` holding copies of the files
+//! rustc produced, plus an `entry.json` listing them with their sizes.
+//!
+//! Restoring an entry happens as part of the unit's job, so it runs in
+//! parallel with other units and is reported like a compilation. Entries are
+//! populated by copying the outputs into a scratch directory and renaming it
+//! into place once complete, so a concurrent cargo never sees a partial
+//! entry. Both restoring and populating hold the cache's file lock, which is
+//! released while the unit compiles. An entry whose files do not match `entry.json` is considered
+//! corrupt, and is renamed out of place before being removed. Any failure to
+//! restore an entry falls back to compiling the unit.
+//!
+//! There is no size limit or garbage collection of old entries yet.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use log::debug;
+use serde::{Deserialize, Serialize};
+
+use super::fingerprint;
+use super::job::Work;
+use super::{CompileMode, Context, FileFlavor, Unit};
+use crate::util::{paths, CargoResult};
+
+/// The directory under the cargo home holding the cache.
+const CACHE_DIR: &str = "artifact-cache";
+
+/// The file locked while reading or writing entries.
+const LOCK_FILE: &str = ".cache-lock";
+
+/// The file listing the contents of an entry.
+const ENTRY_FILE: &str = "entry.json";
+
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    files: Vec<EntryFile>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct EntryFile {
+    name: String,
+    size: u64,
+}
+
+/// Returns whether the outputs of `unit` may be shared through the cache.
+pub fn is_eligible(cx: &Context<'_, '_>, unit: &Unit) -> bool {
+    cx.bcx.config.cli_unstable().shared_cache
+        && !unit.is_local()
+        && !unit.is_std
+        && matches!(
+            unit.mode,
+            CompileMode::Build | CompileMode::Check { test: false }
+        )
+}
+
+/// Returns the work for a dirty `unit`, which copies its outputs out of the
+/// cache if there is a usable entry for it, and otherwise runs `compile` and
+/// adds the outputs to the cache.
+///
+/// This must be called after the fingerprint of `unit` has been calculated.
+pub fn restore_or_compile(cx: &Context<'_, '_>, unit: &Unit, compile: Work) -> CargoResult<Work> {
+    let key = match fingerprint::shared_cache_key(cx, unit) {
+        Some(key) => key,
+        None => return Ok(compile),
+    };
+    let cache = cx.bcx.config.home().join(CACHE_DIR);
+    let root = cache.clone().into_path_unlocked();
+    let entry_dir = root.join(&key);
+    let scratch = root.join(format!("{}.tmp{}", key, std::process::id()));
+    let outputs = output_paths(cx, unit)?;
+    let id = unit.pkg.package_id();
+    Ok(Work::new(move |state| {
+        let restored = cache
+            .open_rw_unreported(LOCK_FILE)
+            .and_then(|_lock| restore(&entry_dir, &scratch, &outputs));
+        match restored {
+            Ok(true) => {
+                debug!("restored {} from shared cache entry {}", id, key);
+                return Ok(());
+            }
+            Ok(false) => {}
+            // A failed restore (such as the entry being evicted by another
+            // cargo while copying) is the same as a cache miss.
+            Err(e) => debug!("failed to restore shared cache entry {}: {:?}", key, e),
+        }
+        compile.call(state)?;
+        let populated = cache.open_rw_unreported(LOCK_FILE).and_then(|_lock| {
+            if entry_dir.exists() {
+                return Ok(());
+            }
+            populate(&scratch, &entry_dir, &outputs)
+        });
+        // Failing to populate the cache must not fail the build.
+        if let Err(e) = populated {
+            debug!("failed to populate shared cache entry {}: {:?}", key, e);
+            if scratch.exists() {
+                drop(paths::remove_dir_all(&scratch));
+            }
+        }
+        Ok(())
+    }))
+}
+
+/// Copies the files of the entry at `entry_dir` to `outputs`, returning
+/// `false` if there is no usable entry.
+fn restore(entry_dir: &Path, scratch: &Path, outputs: &[PathBuf]) -> CargoResult<bool> {
+    if !entry_dir.exists() {
+        return Ok(false);
+    }
+    let entry = match read_entry(entry_dir) {
+        Some(entry) => entry,
+        None => {
+            evict(entry_dir, scratch)?;
+            return Ok(false);
+        }
+    };
+
+    let mut sources = Vec::new();
+    for output in outputs {
+        let name = file_name(output);
+        match entry.files.iter().find(|f| f.name == name) {
+            Some(file) => sources.push((entry_dir.join(&file.name), output)),
+            // The entry was produced with a different output layout, so it
+            // cannot stand in for this unit.
+            None => return Ok(false),
+        }
+    }
+    for (src, dst) in sources {
+        paths::create_dir_all(dst.parent().unwrap())?;
+        paths::copy(src, dst)?;
+    }
+    Ok(true)
+}
+
+fn populate(scratch: &Path, entry_dir: &Path, outputs: &[PathBuf]) -> CargoResult<()> {
+    paths::create_dir_all(scratch)?;
+    let mut files = Vec::new();
+    for output in outputs {
+        let name = file_name(output);
+        let size = paths::copy(output, scratch.join(&name))?;
+        files.push(EntryFile { name, size });
+    }
+    let entry = serde_json::to_string(&Entry { files })?;
+    paths::write(scratch.join(ENTRY_FILE), entry)?;
+    // Renaming fails if another cargo populated the entry first, in which
+    // case theirs is kept.
+    if fs::rename(scratch, entry_dir).is_err() {
+        paths::remove_dir_all(scratch)?;
+    }
+    Ok(())
+}
+
+/// Reads the list of files in an entry, returning `None` if it is missing,
+/// malformed, or does not match the files on disk.
+fn read_entry(entry_dir: &Path) -> Option<Entry> {
+    let contents = paths::read(&entry_dir.join(ENTRY_FILE)).ok()?;
+    let entry: Entry = serde_json::from_str(&contents).ok()?;
+    for file in entry.files.iter() {
+        let size = fs::metadata(entry_dir.join(&file.name)).ok()?.len();
+        if size != file.size {
+            return None;
+        }
+    }
+    Some(entry)
+}
+
+/// Removes a corrupt entry, first renaming it to `scratch` so a concurrent
+/// cargo never starts restoring from a partially removed entry.
+fn evict(entry_dir: &Path, scratch: &Path) -> CargoResult<()> {
+    debug!(
+        "evicting corrupt shared cache entry {}",
+        entry_dir.display()
+    );
+    if fs::rename(entry_dir, scratch).is_err() {
+        // Another cargo evicted it first.
+        return Ok(());
+    }
+    paths::remove_dir_all(scratch)
+}
+
+/// The files rustc produces for `unit`, excluding debug info which may be a
+/// directory on some platforms.
+fn output_paths(cx: &Context<'_, '_>, unit: &Unit) -> CargoResult<Vec<PathBuf>> {
+    Ok(cx
+        .outputs(unit)?
+        .iter()
+        .filter(|o| o.flavor != FileFlavor::DebugInfo)
+        .map(|o| o.path.clone())
+        .collect())
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name().unwrap().to_string_lossy().into_owned()
+}
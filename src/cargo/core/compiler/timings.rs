@@ -23,6 +23,8 @@ pub struct Timings<'cfg> {
     report_info: bool,
     /// If true, emits JSON information with timing information.
     report_json: bool,
+    /// If true, prints a table of unit durations to stderr at the end.
+    report_summary: bool,
     /// When Cargo started.
     start: Instant,
     /// A rendered string of when compilation started.
@@ -102,7 +104,8 @@ impl<'cfg> Timings<'cfg> {
         let report_html = has_report("html");
         let report_info = has_report("info");
         let report_json = has_report("json");
-        let enabled = report_html | report_info | report_json;
+        let report_summary = has_report("summary");
+        let enabled = report_html | report_info | report_json | report_summary;
 
         let mut root_map: HashMap<PackageId, Vec<String>> = HashMap::new();
         for unit in root_units {
@@ -139,6 +142,7 @@ impl<'cfg> Timings<'cfg> {
             report_html,
             report_info,
             report_json,
+            report_summary,
             start: bcx.config.creation_time(),
             start_str,
             root_targets,
@@ -325,6 +329,52 @@ impl<'cfg> Timings<'cfg> {
             self.report_html(bcx, error)
                 .chain_err(|| "failed to save timing report")?;
         }
+        if self.report_summary {
+            self.report_summary(bcx)?;
+        }
+        Ok(())
+    }
+
+    /// Print the duration of every compiled unit to stderr, slowest first,
+    /// along with the longest chain of dependent units.
+    fn report_summary(&self, bcx: &BuildContext<'_, '_>) -> CargoResult<()> {
+        let durations: HashMap<&Unit, f64> = self
+            .unit_times
+            .iter()
+            .map(|ut| (&ut.unit, ut.duration))
+            .collect();
+        let mut critical_paths = HashMap::new();
+        let critical_path = bcx
+            .unit_graph
+            .keys()
+            .map(|unit| critical_path(bcx, &durations, &mut critical_paths, unit))
+            .fold(0.0, f64::max);
+        let total: f64 = self.unit_times.iter().map(|ut| ut.duration).sum();
+
+        let mut unit_times: Vec<&UnitTime> = self.unit_times.iter().collect();
+        unit_times.sort_by(|a, b| b.duration.partial_cmp(&a.duration).unwrap());
+        let mut shell = self.config.shell();
+        shell.status_with_color(
+            "Timing",
+            format!(
+                "{} unit{} compiled in {:.1}s total, {:.1}s on the critical path",
+                unit_times.len(),
+                if unit_times.len() == 1 { "" } else { "s" },
+                total,
+                critical_path
+            ),
+            termcolor::Color::Cyan,
+        )?;
+        for ut in unit_times {
+            let duration = format!("{:.1}s", ut.duration);
+            writeln!(
+                shell.err(),
+                "{:>12} {}{}",
+                duration,
+                ut.name_ver(),
+                ut.target
+            )?;
+        }
         Ok(())
     }
 
@@ -616,6 +666,26 @@ impl UnitTime {
     }
 }
 
+/// The time taken to compile `unit` and the slowest chain of its
+/// dependencies. Fresh units take no time.
+fn critical_path<'a>(
+    bcx: &'a BuildContext<'_, '_>,
+    durations: &HashMap<&Unit, f64>,
+    memo: &mut HashMap<&'a Unit, f64>,
+    unit: &'a Unit,
+) -> f64 {
+    if let Some(&t) = memo.get(unit) {
+        return t;
+    }
+    let deps = bcx.unit_graph[unit]
+        .iter()
+        .map(|dep| critical_path(bcx, durations, memo, &dep.unit))
+        .fold(0.0, f64::max);
+    let t = durations.get(unit).copied().unwrap_or(0.0) + deps;
+    memo.insert(unit, t);
+    t
+}
+
 // Replace with as_secs_f64 when 1.38 hits stable.
 fn d_as_f64(d: Duration) -> f64 {
     (d.as_secs() as f64) + f64::from(d.subsec_nanos()) / 1_000_000_000.0
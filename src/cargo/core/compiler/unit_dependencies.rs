@@ -24,6 +24,7 @@ use crate::core::resolver::features::{FeaturesFor, ResolvedFeatures};
 use crate::core::resolver::Resolve;
 use crate::core::{InternedString, Package, PackageId, PackageSet, Target, Workspace};
 use crate::ops::resolve_all_features;
+use crate::util::errors::TargetKindError;
 use crate::util::Config;
 use crate::CargoResult;
 use log::trace;
@@ -85,6 +86,7 @@ pub fn build_unit_dependencies<'a, 'cfg>(
 
     deps_of_roots(roots, &mut state)?;
     super::links::validate_links(state.resolve(), &state.unit_dependencies)?;
+    validate_host_units(&state)?;
     // Hopefully there aren't any links conflicts with the standard library?
 
     if let Some(std_unit_deps) = std_unit_deps {
@@ -621,6 +623,36 @@ fn new_unit_dep_with_profile(
     })
 }
 
+/// Checks that everything which runs during the build, that is build scripts,
+/// their dependencies and procedural macros, is built for the host.
+fn validate_host_units(state: &State<'_, '_>) -> CargoResult<()> {
+    let check = |unit: &Unit, role: String| -> CargoResult<()> {
+        if unit.kind.is_host() {
+            return Ok(());
+        }
+        Err(TargetKindError::new(unit.pkg.package_id(), role, CompileKind::Host, unit.kind).into())
+    };
+    let dual_proc_macros = state.config.cli_unstable().dual_proc_macros;
+    for (unit, deps) in state.unit_dependencies.iter() {
+        let compiles_build_script =
+            unit.target.is_custom_build() && !unit.mode.is_run_custom_build();
+        if compiles_build_script {
+            check(unit, "a build script".to_string())?;
+        }
+        for dep in deps {
+            if compiles_build_script {
+                check(
+                    &dep.unit,
+                    format!("a build dependency of `{}`", unit.pkg.package_id()),
+                )?;
+            } else if dep.unit.target.proc_macro() && !dual_proc_macros {
+                check(&dep.unit, "a procedural macro".to_string())?;
+            }
+        }
+    }
+    Ok(())
+}
+
 /// Fill in missing dependencies for units of the `RunCustomBuild`
 ///
 /// As mentioned above in `compute_deps_custom_build` each build script
@@ -48,6 +48,7 @@
 use std::cell::Cell;
 use std::env;
 use std::fmt;
+use std::path::PathBuf;
 use std::str::FromStr;
 
 use anyhow::{bail, Error};
@@ -217,6 +218,9 @@ features! {
 
         // Allow to specify whether binaries should be stripped.
         [unstable] strip: bool,
+
+        // The minimum rustc version a package supports.
+        [unstable] rust_version: bool,
     }
 }
 
@@ -357,6 +361,10 @@ pub struct CliUnstable {
     pub separate_nightlies: bool,
     pub multitarget: bool,
     pub rustdoc_map: bool,
+    pub shared_cache: bool,
+    pub warnings_exit_code: Option<i32>,
+    pub diagnostics_stream: Option<PathBuf>,
+    pub error_summary: bool,
 }
 
 impl CliUnstable {
@@ -403,6 +411,25 @@ impl CliUnstable {
             }
         }
 
+        fn parse_exit_code(key: &str, value: Option<&str>) -> CargoResult<i32> {
+            match value.map(|v| v.parse::<i32>()) {
+                Some(Ok(code)) if code != 0 => Ok(code),
+                Some(_) => bail!(
+                    "flag -Z{} expected a non-zero exit code, found: `{}`",
+                    key,
+                    value.unwrap()
+                ),
+                None => bail!("flag -Z{} requires an exit code", key),
+            }
+        }
+
+        fn parse_path(key: &str, value: Option<&str>) -> CargoResult<PathBuf> {
+            match value {
+                Some(v) if !v.is_empty() => Ok(PathBuf::from(v)),
+                _ => bail!("flag -Z{} requires a path", key),
+            }
+        }
+
         // Asserts that there is no argument to the flag.
         fn parse_empty(key: &str, value: Option<&str>) -> CargoResult<bool> {
             if let Some(v) = value {
@@ -437,6 +464,10 @@ impl CliUnstable {
             "separate-nightlies" => self.separate_nightlies = parse_empty(k, v)?,
             "multitarget" => self.multitarget = parse_empty(k, v)?,
             "rustdoc-map" => self.rustdoc_map = parse_empty(k, v)?,
+            "shared-cache" => self.shared_cache = parse_empty(k, v)?,
+            "warnings-exit-code" => self.warnings_exit_code = Some(parse_exit_code(k, v)?),
+            "diagnostics-stream" => self.diagnostics_stream = Some(parse_path(k, v)?),
+            "error-summary" => self.error_summary = parse_empty(k, v)?,
             _ => bail!("unknown `-Z` flag specified: {}", k),
         }
 
@@ -447,26 +478,36 @@ impl CliUnstable {
     /// Intended to be used when a user passes a command-line flag that
     /// requires `-Z unstable-options`.
     pub fn fail_if_stable_opt(&self, flag: &str, issue: u32) -> CargoResult<()> {
+        self.fail_if_stable(&format!("`{}` flag", flag), issue)
+    }
+
+    /// Generates an error if `-Z unstable-options` was not used.
+    /// Intended to be used when a user runs an unstable command.
+    pub fn fail_if_stable_command(&self, command: &str, issue: u32) -> CargoResult<()> {
+        self.fail_if_stable(&format!("`cargo {}` command", command), issue)
+    }
+
+    fn fail_if_stable(&self, what: &str, issue: u32) -> CargoResult<()> {
         if !self.unstable_options {
             let see = format!(
                 "See https://github.com/rust-lang/cargo/issues/{} for more \
-                 information about the `{}` flag.",
-                issue, flag
+                 information about the {}.",
+                issue, what
             );
             if nightly_features_allowed() {
                 bail!(
-                    "the `{}` flag is unstable, pass `-Z unstable-options` to enable it\n\
+                    "the {} is unstable, pass `-Z unstable-options` to enable it\n\
                      {}",
-                    flag,
+                    what,
                     see
                 );
             } else {
                 bail!(
-                    "the `{}` flag is unstable, and only available on the nightly channel \
+                    "the {} is unstable, and only available on the nightly channel \
                      of Cargo, but this is the `{}` channel\n\
                      {}\n\
                      {}",
-                    flag,
+                    what,
                     channel(),
                     SEE_CHANNELS,
                     see
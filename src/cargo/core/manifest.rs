@@ -46,6 +46,7 @@ pub struct Manifest {
     edition: Edition,
     im_a_teapot: Option<bool>,
     default_run: Option<String>,
+    rust_version: Option<String>,
     metabuild: Option<Vec<String>>,
     resolve_behavior: Option<ResolveBehavior>,
 }
@@ -375,6 +376,7 @@ impl Manifest {
         edition: Edition,
         im_a_teapot: Option<bool>,
         default_run: Option<String>,
+        rust_version: Option<String>,
         original: Rc<TomlManifest>,
         metabuild: Option<Vec<String>>,
         resolve_behavior: Option<ResolveBehavior>,
@@ -398,6 +400,7 @@ impl Manifest {
             original,
             im_a_teapot,
             default_run,
+            rust_version,
             publish_lockfile,
             metabuild,
             resolve_behavior,
@@ -517,6 +520,12 @@ impl Manifest {
         self.default_run.as_deref()
     }
 
+    /// The oldest rustc version this package supports, like `1.40`, from the
+    /// `rust-version` key.
+    pub fn rust_version(&self) -> Option<&str> {
+        self.rust_version.as_deref()
+    }
+
     pub fn metabuild(&self) -> Option<&Vec<String>> {
         self.metabuild.as_ref()
     }
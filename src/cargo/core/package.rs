@@ -628,6 +628,9 @@ impl<'a, 'cfg> Downloads<'a, 'cfg> {
         assert!(self.pending_ids.insert(id));
 
         let (mut handle, _timeout) = ops::http_handle_and_timeout(self.set.config)?;
+        if self.downloads_finished == 0 && self.pending.is_empty() {
+            ops::note_proxy_for_url(self.set.config, &url)?;
+        }
         handle.get(true)?;
         handle.url(&url)?;
         handle.follow_location(true)?; // follow redirects
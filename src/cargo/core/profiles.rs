@@ -2,7 +2,7 @@ use crate::core::compiler::CompileMode;
 use crate::core::interning::InternedString;
 use crate::core::resolver::features::FeaturesFor;
 use crate::core::{Feature, Features, PackageId, PackageIdSpec, Resolve, Shell};
-use crate::util::errors::CargoResultExt;
+use crate::util::errors::{CargoResultExt, ProfileInheritanceError};
 use crate::util::toml::{ProfilePackageSpec, StringOrBool, TomlProfile, TomlProfiles, U32OrBool};
 use crate::util::{closest_msg, config, CargoResult, Config};
 use anyhow::bail;
@@ -256,10 +256,12 @@ impl Profiles {
 
                 match profiles.get(&inherits_name) {
                     None => {
-                        bail!(
-                            "profile `{}` inherits from `{}`, but that profile is not defined",
-                            name,
-                            inherits_name
+                        let defined = ["dev", "release"]
+                            .iter()
+                            .copied()
+                            .chain(profiles.keys().map(|name| name.as_str()));
+                        return Err(
+                            ProfileInheritanceError::new(&name, &inherits_name, defined).into()
                         );
                     }
                     Some(parent) => self.process_chain(inherits_name, parent, set, profiles)?,
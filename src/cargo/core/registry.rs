@@ -8,7 +8,8 @@ use url::Url;
 use crate::core::{Dependency, PackageId, Source, SourceId, SourceMap, Summary};
 use crate::core::{InternedString, PackageSet};
 use crate::sources::config::SourceConfigMap;
-use crate::util::errors::{CargoResult, CargoResultExt};
+use crate::sources::GitSource;
+use crate::util::errors::{CargoResult, CargoResultExt, SourceUnavailableError};
 use crate::util::{profile, CanonicalUrl, Config};
 
 /// Source of information about a group of packages.
@@ -392,6 +393,21 @@ impl<'cfg> PackageRegistry<'cfg> {
         Ok(())
     }
 
+    /// Checks that the source of `dep` can be used without network access
+    /// when running with `--offline`.
+    fn check_available(&self, dep: &Dependency) -> CargoResult<()> {
+        let id = dep.source_id();
+        if !self.config.offline()
+            || !id.is_git()
+            || self.source_ids.contains_key(&id)
+            || self.source_config.is_replaced(id)
+            || GitSource::is_cached(id, self.config)
+        {
+            return Ok(());
+        }
+        Err(SourceUnavailableError::new(&dep.package_name(), "git", id.url().as_str()).into())
+    }
+
     fn query_overrides(&mut self, dep: &Dependency) -> CargoResult<Option<Summary>> {
         for &s in self.overrides.iter() {
             let src = self.sources.get_mut(s).unwrap();
@@ -537,6 +553,10 @@ impl<'cfg> Registry for PackageRegistry<'cfg> {
                     );
                 }
 
+                // Fail early, naming the dependency, if the source can only
+                // be reached over the network.
+                self.check_available(dep)?;
+
                 // Ensure the requested source_id is loaded
                 self.ensure_loaded(dep.source_id(), Kind::Normal)
                     .chain_err(|| {
@@ -1,6 +1,7 @@
 use std::fmt;
 
 use crate::core::{Dependency, PackageId, Registry, Summary};
+use crate::util::errors::MultipleVersionsError;
 use crate::util::lev_distance::lev_distance;
 use crate::util::Config;
 use anyhow::Error;
@@ -113,6 +114,33 @@ pub(super) fn activation_error(
             .rev()
             .partition(|&(_, r)| r.is_links());
 
+        // The only conflict is with another version of this package, which
+        // links to the same native library.
+        if let Some(&(_, ConflictReason::Links(link))) = links_errors.first() {
+            if other_errors.is_empty()
+                && links_errors
+                    .iter()
+                    .all(|&(p, _)| p.name() == dep.package_name())
+            {
+                let mut paths: Vec<Vec<PackageId>> = links_errors
+                    .iter()
+                    .map(|&(p, _)| cx.parents.path_to_bottom(p).into_iter().cloned().collect())
+                    .collect();
+                let candidate = candidates.iter().map(|s| s.package_id()).max().unwrap();
+                paths.push(
+                    std::iter::once(candidate)
+                        .chain(
+                            cx.parents
+                                .path_to_bottom(&parent.package_id())
+                                .into_iter()
+                                .cloned(),
+                        )
+                        .collect(),
+                );
+                return to_resolve_err(MultipleVersionsError::new(link, paths).into());
+            }
+        }
+
         for &(p, r) in links_errors.iter() {
             if let ConflictReason::Links(ref link) = *r {
                 msg.push_str("\n\nthe package `");
@@ -166,6 +166,7 @@ pub fn resolve(
 
     check_cycles(&resolve)?;
     check_duplicate_pkgs_in_lockfile(&resolve)?;
+    debug!("resolved {} packages", resolve.iter().count());
     trace!("resolved: {:?}", resolve);
 
     Ok(resolve)
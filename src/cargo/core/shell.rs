@@ -1,10 +1,11 @@
 use std::fmt;
-use std::io::prelude::*;
+use std::io::{self, prelude::*};
 
 use termcolor::Color::{Cyan, Green, Red, Yellow};
 use termcolor::{self, Color, ColorSpec, StandardStream, WriteColor};
 
 use crate::util::errors::CargoResult;
+use crate::util::machine_message::{DiagnosticEvent, Message};
 
 /// The requested verbosity of output.
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -14,6 +15,15 @@ pub enum Verbosity {
     Quiet,
 }
 
+/// How errors are rendered when cargo exits.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ErrorFormat {
+    /// The error followed by all of its causes, with hints.
+    Full,
+    /// Only the primary human-readable message, for scripts.
+    Short,
+}
+
 /// An abstraction around console output that remembers preferences for output
 /// verbosity and color.
 pub struct Shell {
@@ -25,6 +35,18 @@ pub struct Shell {
     /// Flag that indicates the current line needs to be cleared before
     /// printing. Used when a progress bar is currently displayed.
     needs_clear: bool,
+    /// The number of warnings emitted so far, by Cargo or by the compiler.
+    warnings: usize,
+    /// The number of errors emitted so far, by Cargo or by the compiler.
+    errors: usize,
+    /// How to render the error cargo exits with.
+    error_format: ErrorFormat,
+    /// Whether several errors are followed by a summary grouping them by
+    /// kind, with `-Z error-summary`.
+    error_summary: bool,
+    /// Where each warning and error is also written as a JSON line as soon
+    /// as it is emitted, with `-Z diagnostics-stream`.
+    diagnostics: Option<Box<dyn Write>>,
 }
 
 impl fmt::Debug for Shell {
@@ -81,6 +103,11 @@ impl Shell {
             },
             verbosity: Verbosity::Verbose,
             needs_clear: false,
+            warnings: 0,
+            errors: 0,
+            error_format: ErrorFormat::Full,
+            error_summary: false,
+            diagnostics: None,
         }
     }
 
@@ -90,6 +117,11 @@ impl Shell {
             output: ShellOut::Write(out),
             verbosity: Verbosity::Verbose,
             needs_clear: false,
+            warnings: 0,
+            errors: 0,
+            error_format: ErrorFormat::Full,
+            error_summary: false,
+            diagnostics: None,
         }
     }
 
@@ -223,6 +255,8 @@ impl Shell {
 
     /// Prints a red 'error' message.
     pub fn error<T: fmt::Display>(&mut self, message: T) -> CargoResult<()> {
+        self.errors += 1;
+        self.stream_diagnostic("error", &message);
         if self.needs_clear {
             self.err_erase_line();
         }
@@ -232,12 +266,67 @@ impl Shell {
 
     /// Prints an amber 'warning' message.
     pub fn warn<T: fmt::Display>(&mut self, message: T) -> CargoResult<()> {
+        self.warnings += 1;
+        self.stream_diagnostic("warning", &message);
         match self.verbosity {
             Verbosity::Quiet => Ok(()),
             _ => self.print(&"warning", Some(&message), Yellow, false),
         }
     }
 
+    /// Records a warning that was printed by something other than `warn`,
+    /// such as a compiler diagnostic.
+    pub fn record_warning(&mut self, message: &str) {
+        self.warnings += 1;
+        self.stream_diagnostic("warning", &message);
+    }
+
+    /// Gets the number of warnings emitted so far.
+    pub fn warning_count(&self) -> usize {
+        self.warnings
+    }
+
+    /// Records an error that was printed by something other than `error`,
+    /// such as a compiler diagnostic.
+    pub fn record_error(&mut self, message: &str) {
+        self.errors += 1;
+        self.stream_diagnostic("error", &message);
+    }
+
+    /// Sets where each warning and error is written as a `cargo-diagnostic`
+    /// JSON line, as soon as it is emitted.
+    pub fn set_diagnostics_stream(&mut self, out: Box<dyn Write>) {
+        self.diagnostics = Some(out);
+    }
+
+    /// Writes a diagnostic to the diagnostics stream, if there is one.
+    ///
+    /// Failing to write it is not an error of the command, so it is ignored.
+    fn stream_diagnostic(&mut self, level: &str, message: &dyn fmt::Display) {
+        if let Some(out) = &mut self.diagnostics {
+            let event = DiagnosticEvent::new(level, message.to_string());
+            drop(writeln!(out, "{}", event.to_json_string()));
+            drop(out.flush());
+        }
+    }
+
+    /// Gets the number of errors emitted so far.
+    pub fn error_count(&self) -> usize {
+        self.errors
+    }
+
+    /// Summarizes the diagnostics emitted so far, like `0 errors, 3 warnings`.
+    pub fn diagnostic_summary(&self) -> String {
+        fn plural(n: usize, what: &str) -> String {
+            format!("{} {}{}", n, what, if n == 1 { "" } else { "s" })
+        }
+        format!(
+            "{}, {}",
+            plural(self.errors, "error"),
+            plural(self.warnings, "warning")
+        )
+    }
+
     /// Prints a cyan 'note' message.
     pub fn note<T: fmt::Display>(&mut self, message: T) -> CargoResult<()> {
         self.print(&"note", Some(&message), Cyan, false)
@@ -253,6 +342,26 @@ impl Shell {
         self.verbosity
     }
 
+    /// Updates how the error cargo exits with is rendered.
+    pub fn set_error_format(&mut self, error_format: ErrorFormat) {
+        self.error_format = error_format;
+    }
+
+    /// Gets how the error cargo exits with is rendered.
+    pub fn error_format(&self) -> ErrorFormat {
+        self.error_format
+    }
+
+    /// Updates whether several errors are followed by a summary.
+    pub fn set_error_summary(&mut self, error_summary: bool) {
+        self.error_summary = error_summary;
+    }
+
+    /// Gets whether several errors are followed by a summary.
+    pub fn error_summary(&self) -> bool {
+        self.error_summary
+    }
+
     /// Updates the color choice (always, never, or auto) from a string..
     pub fn set_color_choice(&mut self, color: Option<&str>) -> CargoResult<()> {
         if let ShellOut::Stream {
@@ -301,6 +410,14 @@ impl Shell {
         }
     }
 
+    /// Whether stderr is a terminal known to support OSC 8 hyperlinks, and
+    /// colors are enabled for it.
+    pub fn err_supports_hyperlinks(&self) -> bool {
+        self.is_err_tty()
+            && self.err_supports_color()
+            && terminal_supports_hyperlinks(|var| std::env::var(var).ok())
+    }
+
     /// Prints a message and translates ANSI escape code into console colors.
     pub fn print_ansi(&mut self, message: &[u8]) -> CargoResult<()> {
         if self.needs_clear {
@@ -342,6 +459,11 @@ impl ShellOut {
     ) -> CargoResult<()> {
         match *self {
             ShellOut::Stream { ref mut stderr, .. } => {
+                // Hold the lock for the whole message so that output from
+                // other threads, such as `CARGO_LOG` messages, can't end up
+                // in the middle of the line.
+                let stderr_lock = io::stderr();
+                let _lock = stderr_lock.lock();
                 stderr.reset()?;
                 stderr.set_color(ColorSpec::new().set_bold(true).set_fg(Some(color)))?;
                 if justified {
@@ -406,6 +528,31 @@ impl ColorChoice {
     }
 }
 
+/// Detects terminals supporting OSC 8 hyperlinks from the environment
+/// variables they set, looked up with `var`.
+///
+/// There is no way to query the terminal itself, so unknown terminals are
+/// assumed not to support them, as they may print the escape codes verbatim.
+fn terminal_supports_hyperlinks(var: impl Fn(&str) -> Option<String>) -> bool {
+    if let Some(program) = var("TERM_PROGRAM") {
+        if ["iTerm.app", "WezTerm", "vscode"].contains(&program.as_str()) {
+            return true;
+        }
+    }
+    if let Some(version) = var("VTE_VERSION") {
+        // GNOME Terminal and other VTE based terminals, since 0.50.
+        if version.parse::<u32>().map_or(false, |v| v >= 5000) {
+            return true;
+        }
+    }
+    if let Some(term) = var("TERM") {
+        if term == "xterm-kitty" || term == "alacritty" {
+            return true;
+        }
+    }
+    var("WT_SESSION").is_some() || var("DOMTERM").is_some()
+}
+
 #[cfg(unix)]
 mod imp {
     use super::Shell;
@@ -498,3 +645,36 @@ fn default_err_erase_line(shell: &mut Shell) {
         drop(write!(shell.output.stderr(), "{}\r", blank));
     }
 }
+
+#[test]
+fn test_terminal_supports_hyperlinks() {
+    let env = |vars: &'static [(&'static str, &'static str)]| {
+        move |var: &str| {
+            vars.iter()
+                .find(|(k, _)| *k == var)
+                .map(|(_, v)| v.to_string())
+        }
+    };
+    assert!(!terminal_supports_hyperlinks(env(&[])));
+    assert!(!terminal_supports_hyperlinks(env(&[(
+        "TERM",
+        "xterm-256color"
+    )])));
+    assert!(terminal_supports_hyperlinks(env(&[(
+        "TERM_PROGRAM",
+        "iTerm.app"
+    )])));
+    assert!(terminal_supports_hyperlinks(env(&[(
+        "TERM",
+        "xterm-kitty"
+    )])));
+    assert!(terminal_supports_hyperlinks(env(&[(
+        "VTE_VERSION",
+        "6003"
+    )])));
+    assert!(!terminal_supports_hyperlinks(env(&[(
+        "VTE_VERSION",
+        "4803"
+    )])));
+    assert!(terminal_supports_hyperlinks(env(&[("WT_SESSION", "1")])));
+}
@@ -14,7 +14,7 @@ use url::Url;
 use crate::core::PackageId;
 use crate::sources::DirectorySource;
 use crate::sources::{GitSource, PathSource, RegistrySource, CRATES_IO_INDEX};
-use crate::util::{CanonicalUrl, CargoResult, Config, IntoUrl};
+use crate::util::{paths, CanonicalUrl, CargoResult, Config, IntoUrl};
 
 lazy_static::lazy_static! {
     static ref SOURCE_ID_CACHE: Mutex<HashSet<&'static SourceIdInner>> = Mutex::new(HashSet::new());
@@ -154,7 +154,9 @@ impl SourceId {
     ///
     /// `path`: an absolute path.
     pub fn for_path(path: &Path) -> CargoResult<SourceId> {
-        let url = path.into_url()?;
+        // Normalize so the same directory written differently (such as with
+        // `/` or `\` on Windows) is the same source.
+        let url = paths::comparable_path(path).into_url()?;
         SourceId::new(SourceKind::Path, url)
     }
 
@@ -351,7 +353,12 @@ impl SourceId {
                 .strip_prefix(workspace)
             {
                 self.inner.kind.hash(into);
-                p.to_str().unwrap().hash(into);
+                // Hash UTF-8 paths as strings so existing hashes stay the
+                // same, and fall back to the raw path otherwise.
+                match p.to_str() {
+                    Some(s) => s.hash(into),
+                    None => p.hash(into),
+                }
                 return;
             }
         }
@@ -11,6 +11,7 @@ use crate::core::interning::InternedString;
 use crate::core::{Dependency, PackageId, SourceId};
 use semver::Version;
 
+use crate::util::errors::DuplicateFeatureError;
 use crate::util::CargoResult;
 
 /// Subset of a `Manifest`. Contains only the most important information about
@@ -33,16 +34,24 @@ struct Inner {
 }
 
 impl Summary {
-    pub fn new<K>(
+    /// Creates a summary from the `features` table of a package, given as
+    /// pairs of a feature name and the values it enables.
+    ///
+    /// Returns a `DuplicateFeatureError` if a feature is listed more than
+    /// once, rather than keeping whichever definition came last.
+    pub fn new<K, V>(
         pkg_id: PackageId,
         dependencies: Vec<Dependency>,
-        features: &BTreeMap<K, Vec<impl AsRef<str>>>,
+        features: impl IntoIterator<Item = (K, V)>,
         links: Option<impl Into<InternedString>>,
         namespaced_features: bool,
     ) -> CargoResult<Summary>
     where
-        K: Borrow<str> + Ord + Display,
+        K: AsRef<str>,
+        V: IntoIterator,
+        V::Item: AsRef<str>,
     {
+        let features = build_feature_table(features)?;
         for dep in dependencies.iter() {
             let feature = dep.name_in_toml();
             if !namespaced_features && features.get(&*feature).is_some() {
@@ -59,7 +68,7 @@ impl Summary {
                 )
             }
         }
-        let feature_map = build_feature_map(features, &dependencies, namespaced_features)?;
+        let feature_map = build_feature_map(&features, &dependencies, namespaced_features)?;
         Ok(Summary {
             inner: Rc::new(Inner {
                 package_id: pkg_id,
@@ -147,6 +156,30 @@ impl Hash for Summary {
 
 // Checks features for errors, bailing out a CargoResult:Err if invalid,
 // and creates FeatureValues for each feature.
+/// Collects the `[features]` table of a package, checking that no feature
+/// is defined more than once.
+fn build_feature_table<K, V>(
+    features: impl IntoIterator<Item = (K, V)>,
+) -> CargoResult<BTreeMap<InternedString, Vec<InternedString>>>
+where
+    K: AsRef<str>,
+    V: IntoIterator,
+    V::Item: AsRef<str>,
+{
+    let mut table = BTreeMap::new();
+    for (feature, values) in features {
+        let feature = InternedString::new(feature.as_ref());
+        let values = values
+            .into_iter()
+            .map(|v| InternedString::new(v.as_ref()))
+            .collect();
+        if table.insert(feature, values).is_some() {
+            return Err(DuplicateFeatureError::new(feature.as_str()).into());
+        }
+    }
+    Ok(table)
+}
+
 fn build_feature_map<K>(
     features: &BTreeMap<K, Vec<impl AsRef<str>>>,
     dependencies: &[Dependency],
@@ -17,7 +17,10 @@ use crate::core::{Dependency, InternedString, PackageId, PackageIdSpec};
 use crate::core::{EitherManifest, Package, SourceId, VirtualManifest};
 use crate::ops;
 use crate::sources::PathSource;
-use crate::util::errors::{CargoResult, CargoResultExt, ManifestError};
+use crate::util::errors::{
+    validate_all, CargoResult, CargoResultExt, CrossPackageFeatureError, ExcludedPackageError,
+    ManifestError, PathEscapeError,
+};
 use crate::util::paths;
 use crate::util::toml::{read_manifest, TomlProfiles};
 use crate::util::{Config, Filesystem};
@@ -616,18 +619,66 @@ impl<'cfg> Workspace<'cfg> {
     /// 2. All workspace members agree on this one root as the root.
     /// 3. The current crate is a member of this workspace.
     fn validate(&mut self) -> CargoResult<()> {
+        self.validate_path_dependencies()?;
+
         // The rest of the checks require a VirtualManifest or multiple members.
         if self.root_manifest.is_none() {
             return Ok(());
         }
 
-        self.validate_unique_names()?;
-        self.validate_workspace_roots()?;
+        // These are independent of each other, so report all of their
+        // failures together.
+        validate_all(vec![
+            self.validate_unique_names(),
+            self.validate_workspace_roots(),
+        ])?;
         self.validate_members()?;
         self.error_if_manifest_not_in_members()?;
         self.validate_manifest()
     }
 
+    /// Checks path dependencies of members which point outside of the
+    /// workspace root, as configured by `build.path-dependency-escape`.
+    fn validate_path_dependencies(&self) -> CargoResult<()> {
+        let deny = match self
+            .config
+            .build_config()?
+            .path_dependency_escape
+            .as_deref()
+        {
+            Some("allow") => return Ok(()),
+            None | Some("warn") => false,
+            Some("deny") => true,
+            Some(other) => anyhow::bail!(
+                "`build.path-dependency-escape` must be `allow`, `warn` or `deny`, \
+                 found `{}`",
+                other
+            ),
+        };
+        let root = self.root();
+        for pkg in self.members() {
+            for dep in pkg.dependencies() {
+                let source_id = dep.source_id();
+                if !source_id.is_path() {
+                    continue;
+                }
+                let path = match source_id.url().to_file_path() {
+                    Ok(path) => path,
+                    Err(()) => continue,
+                };
+                if path.starts_with(root) {
+                    continue;
+                }
+                let err = PathEscapeError::new(&dep.name_in_toml(), &pkg.name(), &path, root);
+                if deny {
+                    return Err(err.into());
+                }
+                self.config.shell().warn(err)?;
+            }
+        }
+        Ok(())
+    }
+
     fn validate_unique_names(&self) -> CargoResult<()> {
         let mut names = BTreeMap::new();
         for member in self.members.iter() {
@@ -724,6 +775,14 @@ impl<'cfg> Workspace<'cfg> {
         let current_dir = self.current_manifest.parent().unwrap();
         let root_pkg = self.packages.get(root);
 
+        // Only a `package.workspace` pointer leads an excluded package to
+        // its workspace, so the usual suggestion to exclude it doesn't help.
+        if let WorkspaceConfig::Root(ref root_config) = *root_pkg.workspace_config() {
+            if let Some(entry) = root_config.excluded_by(&self.current_manifest) {
+                return Err(ExcludedPackageError::new(&self.current_manifest, root, entry).into());
+            }
+        }
+
         // FIXME: Make this more generic by using a relative path resolver between member and root.
         let members_msg = match current_dir.strip_prefix(root_dir) {
             Ok(rel) => format!(
@@ -1028,6 +1087,31 @@ impl<'cfg> Workspace<'cfg> {
         specs: &[PackageIdSpec],
         requested_features: &RequestedFeatures,
     ) -> CargoResult<Vec<(&Package, RequestedFeatures)>> {
+        // `member/feature` for another member would otherwise be reported as
+        // a missing feature of the current package.
+        if let Some(current) = self.current_opt() {
+            let others: Vec<String> = requested_features
+                .features
+                .iter()
+                .filter(|feature| {
+                    let pkg = match feature.find('/') {
+                        Some(i) => &feature[..i],
+                        None => return false,
+                    };
+                    pkg != current.name().as_str()
+                        && !current
+                            .dependencies()
+                            .iter()
+                            .any(|dep| dep.name_in_toml() == pkg)
+                        && self.members().any(|member| member.name() == pkg)
+                })
+                .map(|feature| feature.to_string())
+                .collect();
+            if !others.is_empty() {
+                return Err(CrossPackageFeatureError::new(&current.name(), others).into());
+            }
+        }
+
         let ms = self.members().filter_map(|member| {
             let member_id = member.package_id();
             match self.current_opt() {
@@ -1142,19 +1226,25 @@ impl WorkspaceRootConfig {
     ///
     /// This method does **not** consider the `members` list.
     fn is_excluded(&self, manifest_path: &Path) -> bool {
-        let excluded = self
-            .exclude
-            .iter()
-            .any(|ex| manifest_path.starts_with(self.root_dir.join(ex)));
+        self.excluded_by(manifest_path).is_some()
+    }
 
+    /// Returns the entry of the `excluded` list which excludes the path, if
+    /// any. Like `is_excluded`, this does not consider the `members` list.
+    fn excluded_by(&self, manifest_path: &Path) -> Option<&str> {
         let explicit_member = match self.members {
             Some(ref members) => members
                 .iter()
                 .any(|mem| manifest_path.starts_with(self.root_dir.join(mem))),
             None => false,
         };
-
-        !explicit_member && excluded
+        if explicit_member {
+            return None;
+        }
+        self.exclude
+            .iter()
+            .find(|ex| manifest_path.starts_with(self.root_dir.join(ex)))
+            .map(|ex| ex.as_str())
     }
 
     fn has_members_list(&self) -> bool {
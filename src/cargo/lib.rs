@@ -30,13 +30,17 @@
 // https://github.com/rust-lang/cargo/pull/7251#pullrequestreview-274914270
 #![allow(clippy::useless_conversion)]
 
+use crate::core::shell::ErrorFormat;
 use crate::core::shell::Verbosity::Verbose;
 use crate::core::Shell;
+use crate::util::errors::{ConfigFileError, ErrorKind, ManifestError, MultiError};
 use anyhow::Error;
 use log::debug;
+use std::collections::BTreeMap;
 use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
 
-pub use crate::util::errors::{InternalError, VerboseError};
+pub use crate::util::errors::{InternalError, PhaseError, VerboseError};
 pub use crate::util::{CargoResult, CliError, CliResult, Config};
 
 pub const CARGO_ENV: &str = "CARGO";
@@ -108,9 +112,55 @@ pub fn exit_with_error(err: CliError, shell: &mut Shell) -> ! {
     std::process::exit(exit_code)
 }
 
+/// Returns the code the process should exit with for `result`, without
+/// printing anything.
+///
+/// This is the code `exit_with_error` exits with, for callers that display
+/// errors themselves.
+pub fn exit_code(result: &CliResult) -> i32 {
+    match result {
+        Ok(()) => 0,
+        Err(e) => e.exit_code,
+    }
+}
+
+/// Returns the code the process should exit with for `result` after
+/// `warnings` warnings were emitted.
+///
+/// A successful command which emitted warnings exits with
+/// `warnings_exit_code` if it is set (with `-Z warnings-exit-code`). Otherwise
+/// this is the same as `exit_code`.
+pub fn exit_code_with_warnings(
+    result: &CliResult,
+    warnings: usize,
+    warnings_exit_code: Option<i32>,
+) -> i32 {
+    match (result, warnings_exit_code) {
+        (Ok(()), Some(code)) if warnings > 0 => code,
+        _ => exit_code(result),
+    }
+}
+
 /// Displays an error, and all its causes, to stderr.
+///
+/// With `--error-format=short` only the primary message is displayed, see
+/// `display_error_short`.
 pub fn display_error(err: &Error, shell: &mut Shell) {
-    debug!("display_error; err={:?}", err);
+    // Displaying the error formats every link of its chain. Check that none
+    // of them panics first, so that a bug in one of them still leaves the
+    // user with something to go on, instead of a crash while reporting.
+    let formatted = panic::catch_unwind(AssertUnwindSafe(|| {
+        debug!("display_error; err={:?}", err);
+        err.chain().for_each(|e| drop(e.to_string()));
+    }));
+    if formatted.is_err() {
+        display_error_fallback(err, shell);
+        return;
+    }
+    if shell.error_format() == ErrorFormat::Short {
+        display_error_short(err, shell);
+        return;
+    }
     let has_verbose = _display_error(err, shell, true);
     if has_verbose {
         drop(writeln!(
@@ -118,6 +168,9 @@ pub fn display_error(err: &Error, shell: &mut Shell) {
             "\nTo learn more, run the command again with --verbose."
         ));
     }
+    if let Some(multi) = err.downcast_ref::<MultiError>() {
+        display_error_summary(multi.errors(), shell);
+    }
     if err
         .chain()
         .any(|e| e.downcast_ref::<InternalError>().is_some())
@@ -134,6 +187,78 @@ pub fn display_error(err: &Error, shell: &mut Shell) {
     }
 }
 
+/// Displays the errors of several commands run in one process, separated by
+/// blank lines, and returns the code the process should exit with.
+///
+/// Each error is displayed as `display_error` would, according to the
+/// shell's verbosity and error format. Errors without a message only
+/// contribute their exit code. The returned code is the highest exit code of
+/// all `errors`, or 0 if there are none.
+pub fn display_errors(errors: &[CliError], shell: &mut Shell) -> i32 {
+    let mut first = true;
+    for err in errors {
+        if let Some(ref error) = err.error {
+            if !first {
+                drop(writeln!(shell.err()));
+            }
+            first = false;
+            display_error(error, shell);
+        }
+    }
+    display_error_summary(errors.iter().filter_map(|e| e.error.as_ref()), shell);
+    errors.iter().map(|e| e.exit_code).max().unwrap_or(0)
+}
+
+/// Displays how many of `errors` there are of each kind (see
+/// `util::errors::kind`), along with the first error of each kind.
+///
+/// This is only shown for several errors, when enabled with
+/// `-Z error-summary`, after the errors themselves.
+fn display_error_summary<'a>(errors: impl IntoIterator<Item = &'a Error>, shell: &mut Shell) {
+    if !shell.error_summary() {
+        return;
+    }
+    let mut total = 0;
+    let mut kinds: BTreeMap<ErrorKind, (usize, &Error)> = BTreeMap::new();
+    for err in errors {
+        total += 1;
+        kinds.entry(util::errors::kind(err)).or_insert((0, err)).0 += 1;
+    }
+    if total < 2 {
+        return;
+    }
+    drop(writeln!(shell.err()));
+    drop(shell.note(format!("{} errors occurred, by kind:", total)));
+    for (kind, (count, first)) in kinds {
+        drop(writeln!(
+            shell.err(),
+            "  {}: {} (first: {})",
+            kind,
+            count,
+            first
+        ));
+    }
+}
+
+/// Displays an error which panicked while being formatted: only the outermost
+/// message, if it can be formatted, and a note about the bug.
+fn display_error_fallback(err: &Error, shell: &mut Shell) {
+    let message = panic::catch_unwind(AssertUnwindSafe(|| err.to_string()))
+        .unwrap_or_else(|_| "(the error message could not be displayed)".to_string());
+    drop(shell.error(message));
+    drop(shell.note("cargo failed to display this error in full, which is a bug"));
+}
+
+/// Displays only the primary human-readable message of an error (see
+/// `primary_human`), without its causes or any hints, leaving the exit code to
+/// carry the rest of the information.
+fn display_error_short(err: &Error, shell: &mut Shell) {
+    match util::errors::primary_human(err) {
+        Some(e) => drop(shell.error(e)),
+        None => drop(shell.error(err)),
+    }
+}
+
 /// Displays a warning, with an error object providing detailed information
 /// and context.
 pub fn display_warning_with_error(warning: &str, err: &Error, shell: &mut Shell) {
@@ -142,30 +267,171 @@ pub fn display_warning_with_error(warning: &str, err: &Error, shell: &mut Shell)
     _display_error(err, shell, false);
 }
 
+/// The number of causes displayed before the rest of the chain is collapsed,
+/// unless the shell is verbose. The innermost cause is always displayed.
+const MAX_CHAIN_DEPTH: usize = 5;
+
 fn _display_error(err: &Error, shell: &mut Shell, as_err: bool) -> bool {
     let verbosity = shell.verbosity();
+    let max_depth = match verbosity {
+        Verbose => usize::MAX,
+        _ => MAX_CHAIN_DEPTH,
+    };
     let is_verbose = |e: &(dyn std::error::Error + 'static)| -> bool {
         verbosity != Verbose && e.downcast_ref::<VerboseError>().is_some()
     };
+    let causes = display_chain(err);
     // Generally the top error shouldn't be verbose, but check it anyways.
-    if is_verbose(err.as_ref()) {
+    if is_verbose(causes[0].0) {
         return true;
     }
+    let links = if shell.err_supports_hyperlinks() {
+        file_links(err)
+    } else {
+        Vec::new()
+    };
+    let message = add_hyperlinks(&causes[0].1, &links);
     if as_err {
-        drop(shell.error(&err));
+        drop(shell.error(message));
     } else {
-        drop(writeln!(shell.err(), "{}", err));
+        drop(writeln!(shell.err(), "{}", message));
     }
-    for cause in err.chain().skip(1) {
+    let mut collapsed = false;
+    for (depth, (cause, message)) in causes.iter().skip(1).enumerate() {
         // If we're not in verbose mode then print remaining errors until one
         // marked as `VerboseError` appears.
-        if is_verbose(cause) {
+        if is_verbose(*cause) {
             return true;
         }
+        // Past the maximum depth only the innermost cause is displayed, as it
+        // is usually the most specific one.
+        let last = depth + 2 == causes.len();
+        if depth >= max_depth && !last {
+            if !collapsed {
+                let more = causes.len() - 2 - depth;
+                drop(writeln!(shell.err(), "\n... ({} more)", more));
+                collapsed = true;
+            }
+            continue;
+        }
         drop(writeln!(shell.err(), "\nCaused by:"));
-        drop(writeln!(shell.err(), "  {}", cause));
+        drop(writeln!(
+            shell.err(),
+            "  {}",
+            add_hyperlinks(message, &links)
+        ));
+    }
+    let snippet = err
+        .chain()
+        .filter_map(|e| e.downcast_ref::<ManifestError>())
+        .filter_map(|e| e.snippet())
+        .last();
+    if let Some(snippet) = snippet {
+        drop(writeln!(shell.err()));
+        for line in snippet.lines() {
+            drop(writeln!(shell.err(), "  {}", line));
+        }
+    }
+    collapsed
+}
+
+/// The paths of the manifests and config files `err` is about, as they are
+/// displayed in its messages, along with their `file://` URLs.
+fn file_links(err: &Error) -> Vec<(String, String)> {
+    err.chain()
+        .filter_map(|e| {
+            e.downcast_ref::<ManifestError>()
+                .map(|e| e.manifest_path().as_path())
+                .or_else(|| e.downcast_ref::<ConfigFileError>().map(|e| e.config_path()))
+        })
+        .filter_map(|path| {
+            let url = url::Url::from_file_path(path).ok()?;
+            Some((path.display().to_string(), url.to_string()))
+        })
+        .collect()
+}
+
+/// Turns each backtick-quoted path of `links` in `message` into an OSC 8
+/// hyperlink to its URL, which supporting terminals make clickable.
+///
+/// Only whole quoted paths are replaced, in a single pass, so a path which is
+/// a prefix of another, or which appears inside an inserted link, is left
+/// alone.
+fn add_hyperlinks(message: &str, links: &[(String, String)]) -> String {
+    let parts: Vec<&str> = message.split('`').collect();
+    let mut result = String::with_capacity(message.len());
+    for (i, part) in parts.iter().enumerate() {
+        if i > 0 {
+            result.push('`');
+        }
+        // Odd parts are between backticks, unless the last quote is unclosed.
+        let quoted = i % 2 == 1 && i + 1 < parts.len();
+        match links.iter().find(|(path, _)| quoted && path == part) {
+            Some((path, url)) => {
+                result.push_str(&format!("\x1b]8;;{}\x1b\\{}\x1b]8;;\x1b\\", url, path))
+            }
+            None => result.push_str(part),
+        }
+    }
+    result
+}
+
+#[test]
+#[cfg(unix)]
+fn test_add_hyperlinks() {
+    let err = anyhow::format_err!("expected a value")
+        .context("failed to parse manifest at `/foo/Cargo.toml`");
+    let err: Error = ManifestError::new(err, std::path::PathBuf::from("/foo/Cargo.toml")).into();
+    let links = file_links(&err);
+    assert_eq!(
+        links,
+        vec![(
+            "/foo/Cargo.toml".to_string(),
+            "file:///foo/Cargo.toml".to_string()
+        )]
+    );
+    assert_eq!(
+        add_hyperlinks(&err.to_string(), &links),
+        "failed to parse manifest at \
+         `\x1b]8;;file:///foo/Cargo.toml\x1b\\/foo/Cargo.toml\x1b]8;;\x1b\\`"
+    );
+    assert_eq!(
+        add_hyperlinks("expected a value", &links),
+        "expected a value"
+    );
+
+    // Only whole quoted occurrences are linked.
+    let links = vec![
+        ("/foo".to_string(), "file:///foo".to_string()),
+        (
+            "/foo/Cargo.toml".to_string(),
+            "file:///foo/Cargo.toml".to_string(),
+        ),
+    ];
+    assert_eq!(
+        add_hyperlinks("`/foo/Cargo.toml` in /foo, `/foo` and `/foo", &links),
+        "`\x1b]8;;file:///foo/Cargo.toml\x1b\\/foo/Cargo.toml\x1b]8;;\x1b\\` in /foo, \
+         `\x1b]8;;file:///foo\x1b\\/foo\x1b]8;;\x1b\\` and `/foo"
+    );
+}
+
+/// The links of `err`'s chain to display (see `dedup_chain`), with their
+/// messages. A `PhaseError` isn't displayed on its own, instead its
+/// `(during <phase>, +<elapsed>)` note is appended to the error it wraps.
+fn display_chain(err: &Error) -> Vec<(&(dyn std::error::Error + 'static), String)> {
+    let mut causes = Vec::new();
+    let mut phase = None;
+    for cause in util::errors::dedup_chain(err) {
+        match cause.downcast_ref::<PhaseError>() {
+            Some(e) => phase = Some(e),
+            None => {
+                let note = phase.take().map(|e| format!(" ({})", e));
+                let message = format!("{}{}", cause, note.unwrap_or_default());
+                causes.push((cause, message));
+            }
+        }
     }
-    false
+    causes
 }
 
 pub fn version() -> VersionInfo {
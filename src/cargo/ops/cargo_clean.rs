@@ -46,10 +46,34 @@ pub fn clean(ws: &Workspace<'_>, opts: &CleanOptions<'_>) -> CargoResult<()> {
     // If we have a spec, then we need to delete some packages, otherwise, just
     // remove the whole target directory and be done with it!
     //
-    // Note that we don't bother grabbing a lock here as we're just going to
-    // blow it all away anyway.
+    // Running builds hold a shared lock on the target directory, so lock it
+    // exclusively to wait for them to finish and keep them out during the
+    // removal.
     if opts.spec.is_empty() {
-        return rm_rf(&target_dir.into_path_unlocked(), config);
+        let target_dir = target_dir.into_path_unlocked();
+        let lock = if ws
+            .target_dir()
+            .join(".cargo-lock")
+            .as_path_unlocked()
+            .exists()
+        {
+            let lock = ws
+                .target_dir()
+                .open_rw(".cargo-lock", config, "build directory")?;
+            Some(lock)
+        } else {
+            None
+        };
+        match lock {
+            // The lock file itself goes last, once it's unlocked.
+            Some(lock) if lock.parent() == target_dir => {
+                lock.remove_siblings()
+                    .chain_err(|| anyhow::format_err!("could not remove build directory"))?;
+                drop(lock);
+                return rm_rf(&target_dir, config);
+            }
+            _ => return rm_rf(&target_dir, config),
+        }
     }
 
     // Clean specific packages.
@@ -39,7 +39,10 @@ use crate::core::{PackageId, PackageIdSpec, TargetKind, Workspace};
 use crate::ops;
 use crate::ops::resolve::WorkspaceResolve;
 use crate::util::config::Config;
-use crate::util::{closest_msg, profile, CargoResult};
+use crate::util::errors::{
+    CargoResultExt, NotFoundError, RustVersionError, TargetNotInstalledError,
+};
+use crate::util::{profile, CargoResult};
 
 /// Contains information about how a package should be compiled.
 ///
@@ -267,7 +270,17 @@ pub fn compile_ws<'a>(
 
     let _p = profile::start("compiling");
     let cx = Context::new(&bcx)?;
-    cx.compile(exec)
+    with_phase(ws.config(), "compile", cx.compile(exec))
+}
+
+/// Tags an error with the phase it was raised in when `-Z timings` is
+/// enabled, so that it is displayed alongside the error.
+fn with_phase<T>(config: &Config, phase: &'static str, result: CargoResult<T>) -> CargoResult<T> {
+    if config.cli_unstable().timings.is_some() {
+        result.phase(phase, config.creation_time())
+    } else {
+        result
+    }
 }
 
 pub fn create_bcx<'a, 'cfg>(
@@ -328,7 +341,8 @@ pub fn create_bcx<'a, 'cfg>(
         &specs,
         has_dev_units,
         crate::core::resolver::features::ForceAllTargets::No,
-    )?;
+    );
+    let resolve = with_phase(config, "resolve", resolve)?;
     let WorkspaceResolve {
         mut pkg_set,
         workspace_resolve,
@@ -495,6 +509,48 @@ pub fn create_bcx<'a, 'cfg>(
         interner,
     )?;
 
+    // Without the standard library for a target, rustc fails on the first
+    // crate with an error that doesn't say what's wrong with the setup. Only
+    // targets that something is built for are checked, as proc-macros and
+    // their dependencies are always built for the host.
+    if config.cli_unstable().build_std.is_none() {
+        for kind in &build_config.requested_kinds {
+            if let CompileKind::Target(target) = kind {
+                let libdir = &target_data.info(*kind).sysroot_target_libdir;
+                if !target.rustc_target().ends_with(".json")
+                    && !libdir.exists()
+                    && unit_graph.keys().any(|unit| unit.kind == *kind)
+                {
+                    return Err(TargetNotInstalledError::new(target.rustc_target(), true).into());
+                }
+            }
+        }
+    }
+
+    // Refuse to build packages which need a newer compiler, instead of
+    // letting rustc fail on whatever newer language feature they use.
+    let rustc_version = &target_data.rustc.version;
+    let rustc_version = semver::Version::new(
+        rustc_version.major,
+        rustc_version.minor,
+        rustc_version.patch,
+    );
+    let mut pkgs = unit_graph.keys().map(|unit| &unit.pkg).collect::<Vec<_>>();
+    pkgs.sort_by_key(|pkg| pkg.package_id());
+    for pkg in pkgs {
+        let required = match pkg.manifest().rust_version() {
+            Some(required) => required,
+            None => continue,
+        };
+        match crate::util::toml::parse_rust_version(required) {
+            Some(version) if version > rustc_version => {
+                let err = RustVersionError::new(pkg.package_id(), required, &rustc_version);
+                return Err(err.into());
+            }
+            _ => {}
+        }
+    }
+
     let bcx = BuildContext::new(
         ws,
         pkg_set,
@@ -1064,14 +1120,10 @@ fn find_named_targets<'a>(
             pkg.targets()
                 .iter()
                 .filter(|target| is_expected_kind(target))
+                .map(|target| target.name())
         });
-        let suggestion = closest_msg(target_name, targets, |t| t.name());
-        anyhow::bail!(
-            "no {} target named `{}`{}",
-            target_desc,
-            target_name,
-            suggestion
-        );
+        let what = format!("{} target", target_desc);
+        return Err(NotFoundError::new(what, target_name, targets).into());
     }
     Ok(proposals)
 }
@@ -0,0 +1,164 @@
+//! Implementation of `cargo config`.
+
+use crate::core::shell::Verbosity;
+use crate::drop_println;
+use crate::util::config::{Config, ConfigKey, ConfigValue as CV};
+use crate::util::errors::CargoResult;
+
+pub struct GetOptions<'a> {
+    /// The dotted key to show, or `None` to show the entire configuration.
+    pub key: Option<&'a str>,
+    /// Show secret values such as registry tokens instead of redacting them.
+    pub show_secrets: bool,
+}
+
+/// Prints the effective value of a config key, or of the entire
+/// configuration.
+///
+/// Returns `false` if the given key is not set.
+pub fn get(config: &Config, opts: &GetOptions<'_>) -> CargoResult<bool> {
+    let verbose = config.shell().verbosity() == Verbosity::Verbose;
+    match opts.key {
+        Some(key) => {
+            let mut key = ConfigKey::from_str(key);
+            match config.get_cv_with_env(&key)? {
+                Some(cv) => {
+                    print_cv(config, &mut key, &cv, opts, verbose)?;
+                    Ok(true)
+                }
+                None => Ok(false),
+            }
+        }
+        None => {
+            let mut names: Vec<&String> = config.values()?.keys().collect();
+            names.sort();
+            for name in names {
+                let mut key = ConfigKey::new();
+                key.push(name);
+                let cv = config.get_cv_with_env(&key)?.unwrap();
+                print_cv(config, &mut key, &cv, opts, true)?;
+            }
+            let mut env: Vec<_> = config.env_config_vars().collect();
+            if !env.is_empty() {
+                env.sort();
+                drop_println!(
+                    config,
+                    "# The following environment variables may affect the loaded values."
+                );
+                for (name, value) in env {
+                    let value = if !opts.show_secrets && is_secret_env(name) {
+                        "[REDACTED]"
+                    } else {
+                        value
+                    };
+                    drop_println!(config, "# {}={}", name, value);
+                }
+            }
+            Ok(true)
+        }
+    }
+}
+
+/// Prints `cv` (the value of `key`) as TOML, one line per value, with tables
+/// flattened to dotted keys. With `provenance`, each value is followed by a
+/// comment saying where it was defined.
+fn print_cv(
+    config: &Config,
+    key: &mut ConfigKey,
+    cv: &CV,
+    opts: &GetOptions<'_>,
+    provenance: bool,
+) -> CargoResult<()> {
+    let secret = !opts.show_secrets && is_secret(key);
+    match cv {
+        CV::Table(map, _) => {
+            let mut names: Vec<&String> = map.keys().collect();
+            names.sort();
+            for name in names {
+                key.push(name);
+                // Re-resolve the value so environment variables overriding
+                // values in the table are taken into account.
+                let cv = config.get_cv_with_env(key)?.unwrap();
+                print_cv(config, key, &cv, opts, provenance)?;
+                key.pop();
+            }
+        }
+        CV::List(list, _) if provenance && !secret => {
+            drop_println!(config, "{} = [", toml_key(key));
+            for (value, def) in list {
+                drop_println!(config, "    {}, # {}", toml_string(value), def);
+            }
+            drop_println!(config, "]");
+        }
+        _ => {
+            let value = if secret {
+                toml_string("[REDACTED]")
+            } else {
+                toml_value(cv)
+            };
+            if provenance {
+                drop_println!(
+                    config,
+                    "{} = {} # {}",
+                    toml_key(key),
+                    value,
+                    cv.definition()
+                );
+            } else {
+                drop_println!(config, "{} = {}", toml_key(key), value);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Whether the value at `key` is a secret which should not be shown unless
+/// asked for.
+fn is_secret(key: &ConfigKey) -> bool {
+    let parts: Vec<&str> = key.parts().collect();
+    matches!(
+        parts.as_slice(),
+        ["registry", "token"] | ["registries", _, "token"]
+    )
+}
+
+fn is_secret_env(name: &str) -> bool {
+    name == "CARGO_REGISTRY_TOKEN"
+        || (name.starts_with("CARGO_REGISTRIES_") && name.ends_with("_TOKEN"))
+}
+
+fn toml_value(cv: &CV) -> String {
+    match cv {
+        CV::Integer(i, _) => i.to_string(),
+        CV::Boolean(b, _) => b.to_string(),
+        CV::String(s, _) => toml_string(s),
+        CV::List(list, _) => {
+            let values: Vec<String> = list.iter().map(|(s, _)| toml_string(s)).collect();
+            format!("[{}]", values.join(", "))
+        }
+        CV::Table(..) => unreachable!("tables are flattened"),
+    }
+}
+
+fn toml_string(s: &str) -> String {
+    toml::Value::String(s.to_string()).to_string()
+}
+
+/// Formats `key` as a TOML dotted key, quoting the parts which are not bare
+/// keys, such as `target.'cfg(unix)'.runner`.
+fn toml_key(key: &ConfigKey) -> String {
+    key.parts()
+        .map(|part| {
+            if !part.is_empty()
+                && part
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+            {
+                part.to_string()
+            } else {
+                toml_string(part)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(".")
+}
@@ -2,7 +2,7 @@ use crate::core::compiler::RustcTargetData;
 use crate::core::resolver::{HasDevUnits, ResolveOpts};
 use crate::core::{Shell, Workspace};
 use crate::ops;
-use crate::util::CargoResult;
+use crate::util::{CargoResult, CargoResultExt};
 use std::collections::HashMap;
 use std::path::Path;
 use std::process::Command;
@@ -108,10 +108,7 @@ fn open_docs(path: &Path, shell: &mut Shell) -> CargoResult<()> {
             }
         }
         None => {
-            if let Err(e) = opener::open(&path) {
-                let e = e.into();
-                crate::display_warning_with_error("couldn't open docs", &e, shell);
-            }
+            opener::open(&path).demote_to_warning("couldn't open docs", shell);
         }
     };
 
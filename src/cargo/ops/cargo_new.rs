@@ -1,5 +1,5 @@
 use crate::core::{Shell, Workspace};
-use crate::util::errors::{CargoResult, CargoResultExt};
+use crate::util::errors::{CargoResult, CargoResultExt, PackageNameError, PackageNameRule};
 use crate::util::{existing_vcs_repo, FossilRepo, GitRepo, HgRepo, PijulRepo};
 use crate::util::{paths, restricted_names, Config};
 use git2::Config as GitConfig;
@@ -157,21 +157,19 @@ fn get_name<'a>(path: &'a Path, opts: &'a NewOptions) -> CargoResult<&'a str> {
 fn check_name(name: &str, name_help: &str, has_bin: bool, shell: &mut Shell) -> CargoResult<()> {
     restricted_names::validate_package_name(name, "crate name", name_help)?;
 
+    // Names that are valid but reserved get a suffixed name as suggestion.
+    let err = |rule| -> CargoResult<()> {
+        let e = PackageNameError::new(name, "crate name", rule)
+            .with_suggestion(Some(format!("{}-rs", name)))
+            .with_help(name_help);
+        Err(e.into())
+    };
     if restricted_names::is_keyword(name) {
-        anyhow::bail!(
-            "the name `{}` cannot be used as a crate name, it is a Rust keyword{}",
-            name,
-            name_help
-        );
+        return err(PackageNameRule::Keyword);
     }
     if restricted_names::is_conflicting_artifact_name(name) {
         if has_bin {
-            anyhow::bail!(
-                "the name `{}` cannot be used as a crate name, \
-                it conflicts with cargo's build directory names{}",
-                name,
-                name_help
-            );
+            return err(PackageNameRule::BuildDirectoryName);
         } else {
             shell.warn(format!(
                 "the name `{}` will not support binary \
@@ -182,11 +180,7 @@ fn check_name(name: &str, name_help: &str, has_bin: bool, shell: &mut Shell) ->
         }
     }
     if name == "test" {
-        anyhow::bail!(
-            "the name `test` cannot be used as a crate name, \
-            it conflicts with Rust's built-in test library{}",
-            name_help
-        );
+        return err(PackageNameRule::TestLibrary);
     }
     if ["core", "std", "alloc", "proc_macro", "proc-macro"].contains(&name) {
         shell.warn(format!(
@@ -197,11 +191,7 @@ fn check_name(name: &str, name_help: &str, has_bin: bool, shell: &mut Shell) ->
     }
     if restricted_names::is_windows_reserved(name) {
         if cfg!(windows) {
-            anyhow::bail!(
-                "cannot use name `{}`, it is a reserved Windows filename{}",
-                name,
-                name_help
-            );
+            return err(PackageNameRule::WindowsReserved);
         } else {
             shell.warn(format!(
                 "the name `{}` is a reserved Windows filename\n\
@@ -754,14 +744,11 @@ mod tests {
         }
     }
 
-    if let Err(e) = Workspace::new(&path.join("Cargo.toml"), config) {
-        crate::display_warning_with_error(
-            "compiling this new crate may not work due to invalid \
-             workspace configuration",
-            &e,
-            &mut config.shell(),
-        );
-    }
+    Workspace::new(&path.join("Cargo.toml"), config).demote_to_warning(
+        "compiling this new crate may not work due to invalid \
+         workspace configuration",
+        &mut config.shell(),
+    );
 
     Ok(())
 }
@@ -5,17 +5,17 @@ use std::io::SeekFrom;
 use std::path::{Path, PathBuf};
 use std::rc::Rc;
 use std::sync::Arc;
-use std::time::SystemTime;
 
 use flate2::read::GzDecoder;
 use flate2::{Compression, GzBuilder};
 use log::debug;
-use tar::{Archive, Builder, EntryType, Header};
+use tar::{Archive, Builder, EntryType, Header, HeaderMode};
 
 use crate::core::compiler::{BuildConfig, CompileMode, DefaultExecutor, Executor};
 use crate::core::{Feature, Shell, Verbosity, Workspace};
 use crate::core::{Package, PackageId, PackageSet, Resolve, Source, SourceId};
-use crate::sources::PathSource;
+use crate::sources::path::dangling_symlink_target;
+use crate::sources::{FileSelection, PathSource};
 use crate::util::errors::{CargoResult, CargoResultExt};
 use crate::util::paths;
 use crate::util::toml::TomlManifest;
@@ -86,7 +86,8 @@ pub fn package(ws: &Workspace<'_>, opts: &PackageOpts<'_>) -> CargoResult<Option
              the exclude list will be ignored",
         )?;
     }
-    let src_files = src.list_files(pkg)?;
+    let selected = src.list_files_with_reasons(pkg)?;
+    let src_files: Vec<PathBuf> = selected.iter().map(|(path, _)| path.clone()).collect();
 
     // Check (git) repository state, getting the current commit hash if not
     // dirty.
@@ -101,8 +102,23 @@ pub fn package(ws: &Workspace<'_>, opts: &PackageOpts<'_>) -> CargoResult<Option
     let ar_files = build_ar_list(ws, pkg, src_files, vcs_info)?;
 
     if opts.list {
+        // In verbose mode say why each file is in the package, so the
+        // selection can be audited.
+        let verbose = config.shell().verbosity() == Verbosity::Verbose;
+        let reasons: HashMap<PathBuf, FileSelection> = selected.into_iter().collect();
         for ar_file in ar_files {
-            drop_println!(config, "{}", ar_file.rel_str);
+            if !verbose {
+                drop_println!(config, "{}", ar_file.rel_str);
+                continue;
+            }
+            let reason = match &ar_file.contents {
+                FileContents::OnDisk(path) => match reasons.get(path) {
+                    Some(reason) => reason.to_string(),
+                    None => "added by cargo".to_string(),
+                },
+                FileContents::Generated(_) => "generated by cargo".to_string(),
+            };
+            drop_println!(config, "{} ({})", ar_file.rel_str, reason);
         }
         return Ok(None);
     }
@@ -137,6 +153,11 @@ pub fn package(ws: &Workspace<'_>, opts: &PackageOpts<'_>) -> CargoResult<Option
         fs::rename(&src_path, &dst_path)
             .chain_err(|| "failed to move temporary tarball into final location")?;
     }
+    let checksum = util::Sha256::new().update_file(dst.file())?.finish_hex();
+    dst.seek(SeekFrom::Start(0))?;
+    config
+        .shell()
+        .verbose(|shell| shell.status("Checksum", format!("{} {}", checksum, filename)))?;
     Ok(Some(dst))
 }
 
@@ -152,6 +173,13 @@ fn build_ar_list(
     for src_file in src_files {
         let rel_path = src_file.strip_prefix(&root)?.to_path_buf();
         check_filename(&rel_path, &mut ws.config().shell())?;
+        if let Some(target) = dangling_symlink_target(&src_file) {
+            anyhow::bail!(
+                "cannot package symbolic link `{}`, its target `{}` does not exist",
+                rel_path.display(),
+                target.display()
+            );
+        }
         let rel_str = rel_path
             .to_str()
             .ok_or_else(|| {
@@ -231,12 +259,18 @@ fn build_ar_list(
                             Update the license-file to point to the path relative \
                             to the root of the package to remove this warning.",
                             license_file,
-                            license_name.to_str().unwrap()
+                            license_name.to_string_lossy()
                         ))?;
                     } else {
+                        let rel_str = license_name.to_str().ok_or_else(|| {
+                            anyhow::format_err!(
+                                "non-utf8 license-file name: {}",
+                                license_name.to_string_lossy()
+                            )
+                        })?;
                         result.push(ArchiveFile {
                             rel_path: PathBuf::from(license_name),
-                            rel_str: license_name.to_str().unwrap().to_string(),
+                            rel_str: rel_str.to_string(),
                             contents: FileContents::OnDisk(abs_license_path),
                         });
                     }
@@ -472,6 +506,18 @@ fn check_repo_state(
     }
 }
 
+/// The modification time of every file in the archive, so that packaging the
+/// same sources always produces the same bytes. This is the value `tar` uses
+/// for `HeaderMode::Deterministic`, since some tools misbehave with a zero
+/// timestamp.
+const ARCHIVE_MTIME: u64 = 1153704088;
+
+/// Writes the package archive to `dst`.
+///
+/// The archive only depends on the paths and contents of `ar_files`: entries
+/// are sorted by path (see `build_ar_list`), and timestamps, owners and
+/// permissions are normalized, so the same sources always produce the same
+/// `.crate` file.
 fn tar(
     ws: &Workspace<'_>,
     ar_files: Vec<ArchiveFile>,
@@ -480,8 +526,10 @@ fn tar(
 ) -> CargoResult<()> {
     // Prepare the encoder and its header.
     let filename = Path::new(filename);
+    // flate2 always writes 255 ("unknown") as the operating system.
     let encoder = GzBuilder::new()
         .filename(util::path2bytes(filename)?)
+        .mtime(0)
         .write(dst, Compression::best());
 
     // Put all package files into a compressed archive.
@@ -531,7 +579,10 @@ fn tar(
                 let metadata = file.metadata().chain_err(|| {
                     format!("could not learn metadata for: `{}`", disk_path.display())
                 })?;
-                header.set_metadata(&metadata);
+                // Only keep the executable bit, as 0o755 or 0o644, with
+                // root as the owner.
+                header.set_metadata_in_mode(&metadata, HeaderMode::Deterministic);
+                header.set_mtime(ARCHIVE_MTIME);
                 header.set_cksum();
                 ar.append(&header, &mut file).chain_err(|| {
                     format!("could not archive source file `{}`", disk_path.display())
@@ -545,12 +596,9 @@ fn tar(
                 };
                 header.set_entry_type(EntryType::file());
                 header.set_mode(0o644);
-                header.set_mtime(
-                    SystemTime::now()
-                        .duration_since(SystemTime::UNIX_EPOCH)
-                        .unwrap()
-                        .as_secs(),
-                );
+                header.set_uid(0);
+                header.set_gid(0);
+                header.set_mtime(ARCHIVE_MTIME);
                 header.set_size(contents.len() as u64);
                 header.set_cksum();
                 ar.append(&header, contents.as_bytes())
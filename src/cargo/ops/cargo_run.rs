@@ -4,6 +4,7 @@ use std::path::Path;
 
 use crate::core::{TargetKind, Workspace};
 use crate::ops;
+use crate::util::errors::AmbiguousTargetError;
 use crate::util::{CargoResult, ProcessError};
 
 pub fn run(
@@ -51,17 +52,11 @@ pub fn run(
 
     if bins.len() > 1 {
         if !options.filter.is_specific() {
-            let names: Vec<&str> = bins
+            let names = bins
                 .into_iter()
-                .map(|(_pkg, target)| target.name())
+                .map(|(_pkg, target)| target.name().to_string())
                 .collect();
-            anyhow::bail!(
-                "`cargo run` could not determine which binary to run. \
-                 Use the `--bin` option to specify a binary, \
-                 or the `default-run` manifest key.\n\
-                 available binaries: {}",
-                names.join(", ")
-            )
+            return Err(AmbiguousTargetError::new(names).into());
         } else {
             anyhow::bail!(
                 "`cargo run` can run at most one executable, but \
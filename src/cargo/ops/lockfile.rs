@@ -1,7 +1,10 @@
+use std::collections::BTreeMap;
 use std::io::prelude::*;
 
-use crate::core::{resolver, Resolve, ResolveVersion, Workspace};
-use crate::util::errors::{CargoResult, CargoResultExt};
+use crate::core::{
+    resolver, InternedString, PackageId, Resolve, ResolveVersion, SourceId, Workspace,
+};
+use crate::util::errors::{CargoResult, CargoResultExt, MissingLockfileError, SourceMismatchError};
 use crate::util::toml as cargo_toml;
 use crate::util::Filesystem;
 
@@ -34,6 +37,7 @@ pub fn resolve_to_string(ws: &Workspace<'_>, resolve: &Resolve) -> CargoResult<S
 
 pub fn write_pkg_lockfile(ws: &Workspace<'_>, resolve: &Resolve) -> CargoResult<()> {
     let (orig, out, ws_root) = resolve_to_string_orig(ws, resolve)?;
+    let missing = orig.is_none();
 
     // If the lock file contents haven't changed so don't rewrite it. This is
     // helpful on read-only filesystems.
@@ -44,6 +48,14 @@ pub fn write_pkg_lockfile(ws: &Workspace<'_>, resolve: &Resolve) -> CargoResult<
     }
 
     if !ws.config().lock_update_allowed() {
+        if missing {
+            let flag = if ws.config().frozen() {
+                "--frozen"
+            } else {
+                "--locked"
+            };
+            return Err(MissingLockfileError::new(ws.root().join("Cargo.lock"), flag).into());
+        }
         if ws.config().offline() {
             anyhow::bail!("can't update in the offline mode");
         }
@@ -53,27 +65,156 @@ pub fn write_pkg_lockfile(ws: &Workspace<'_>, resolve: &Resolve) -> CargoResult<
         } else {
             "--frozen"
         };
-        anyhow::bail!(
+        let mut msg = format!(
             "the lock file {} needs to be updated but {} was passed to prevent this\n\
              If you want to try to generate the lock file without accessing the network, \
              use the --offline flag.",
             ws.root().to_path_buf().join("Cargo.lock").display(),
             flag
         );
+        if let Ok(Some(previous)) = load_pkg_lockfile(ws) {
+            let (changes, updatable) = lock_changes(&previous, resolve);
+            if !changes.is_empty() {
+                msg.push_str("\n\nThe following packages would change in the lock file:\n");
+                for change in changes {
+                    msg.push_str(&format!("  {}\n", change));
+                }
+                if updatable.is_empty() {
+                    msg.push_str("Build without ");
+                    msg.push_str(flag);
+                } else {
+                    msg.push_str("Run `cargo update");
+                    for name in updatable {
+                        msg.push_str(&format!(" -p {}", name));
+                    }
+                    msg.push('`');
+                }
+                msg.push_str(" locally and commit the lock file.");
+            }
+            // Switching a dependency between kinds of sources is an easy
+            // reason to miss, so point it out.
+            if let Some(mismatch) = source_mismatch(&previous, resolve) {
+                return Err(anyhow::Error::from(mismatch).context(msg));
+            }
+        }
+        anyhow::bail!(msg);
     }
 
     // Ok, if that didn't work just write it out
-    ws_root
+    let result = ws_root
         .open_rw("Cargo.lock", ws.config(), "Cargo.lock file")
         .and_then(|mut f| {
             f.file().set_len(0)?;
             f.write_all(out.as_bytes())?;
             Ok(())
-        })
-        .chain_err(|| format!("failed to write {}", ws.root().join("Cargo.lock").display()))?;
+        });
+    if let Err(e) = result {
+        let path = ws.root().join("Cargo.lock");
+        if is_read_only_error(&e) {
+            anyhow::bail!(
+                "the lock file {} needs to be updated but the source directory is read-only\n\
+                 Check in an up-to-date lock file, or build from a writable copy of the sources \
+                 to update it.",
+                path.display()
+            );
+        }
+        return Err(e).chain_err(|| format!("failed to write {}", path.display()));
+    }
     Ok(())
 }
 
+/// Describes, one line per package name, how the packages of `resolve`
+/// differ from the ones in the `previous` lock file. Also returns the names
+/// of changed packages which `cargo update -p` can update.
+fn lock_changes(previous: &Resolve, resolve: &Resolve) -> (Vec<String>, Vec<InternedString>) {
+    let mut by_name: BTreeMap<InternedString, (Vec<PackageId>, Vec<PackageId>)> = BTreeMap::new();
+    for id in previous.iter() {
+        by_name.entry(id.name()).or_default().0.push(id);
+    }
+    for id in resolve.iter() {
+        by_name.entry(id.name()).or_default().1.push(id);
+    }
+
+    let versions = |ids: &[PackageId], full: bool| {
+        ids.iter()
+            .map(|id| {
+                if full {
+                    id.to_string()
+                } else {
+                    format!("v{}", id.version())
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let mut changes = Vec::new();
+    let mut updatable = Vec::new();
+    for (name, (mut old, mut new)) in by_name {
+        old.sort();
+        new.sort();
+        if old == new {
+            continue;
+        }
+        if old.is_empty() {
+            changes.push(format!("{}: added {}", name, versions(&new, false)));
+            continue;
+        }
+        updatable.push(name);
+        if new.is_empty() {
+            changes.push(format!("{}: removed {}", name, versions(&old, false)));
+            continue;
+        }
+        // Only the sources differ, so show them.
+        let full = versions(&old, false) == versions(&new, false);
+        changes.push(format!(
+            "{}: {} -> {}",
+            name,
+            versions(&old, full),
+            versions(&new, full)
+        ));
+    }
+    (changes, updatable)
+}
+
+/// Finds a package of `resolve` which `previous` had from a different kind of
+/// source, like a dependency moved from git to a registry.
+fn source_mismatch(previous: &Resolve, resolve: &Resolve) -> Option<SourceMismatchError> {
+    let kind = |id: SourceId| (id.is_git(), id.is_registry(), id.is_path());
+    for id in resolve.iter() {
+        let locked: Vec<PackageId> = previous.iter().filter(|p| p.name() == id.name()).collect();
+        if locked.iter().any(|p| p.source_id() == id.source_id()) {
+            continue;
+        }
+        if let Some(old) = locked
+            .iter()
+            .find(|p| kind(p.source_id()) != kind(id.source_id()))
+        {
+            return Some(SourceMismatchError::new(
+                &id.name(),
+                old.source_id(),
+                id.source_id(),
+            ));
+        }
+    }
+    None
+}
+
+/// Whether `err` was caused by trying to write to a read-only file or
+/// filesystem.
+fn is_read_only_error(err: &anyhow::Error) -> bool {
+    err.chain()
+        .filter_map(|e| e.downcast_ref::<std::io::Error>())
+        .any(|e| {
+            #[cfg(unix)]
+            {
+                if e.raw_os_error() == Some(libc::EROFS) {
+                    return true;
+                }
+            }
+            e.kind() == std::io::ErrorKind::PermissionDenied
+        })
+}
+
 fn resolve_to_string_orig(
     ws: &Workspace<'_>,
     resolve: &Resolve,
@@ -495,7 +495,10 @@ pub fn needs_custom_http_transport(config: &Config) -> CargoResult<bool> {
 pub fn configure_http_handle(config: &Config, handle: &mut Easy) -> CargoResult<HttpTimeout> {
     let http = config.http_config()?;
     if let Some(proxy) = http_proxy(config)? {
-        handle.proxy(&proxy)?;
+        handle.proxy(&proxy.url)?;
+    }
+    if let Some(no_proxy) = no_proxy() {
+        handle.noproxy(&no_proxy)?;
     }
     if let Some(cainfo) = &http.cainfo {
         let cainfo = cainfo.resolve_path(config);
@@ -616,41 +619,187 @@ impl HttpTimeout {
     }
 }
 
+/// An HTTP proxy, along with a description of where it was configured.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpProxy {
+    pub url: String,
+    pub source: String,
+}
+
+/// The environment variables libcurl reads proxies from, in the order libcurl
+/// consults them.
+const PROXY_ENV_VARS: &[&str] = &["https_proxy", "HTTPS_PROXY", "http_proxy"];
+
 /// Finds an explicit HTTP proxy if one is available.
 ///
-/// Favor cargo's `http.proxy`, then git's `http.proxy`. Proxies specified
-/// via environment variables are picked up by libcurl.
-fn http_proxy(config: &Config) -> CargoResult<Option<String>> {
-    let http = config.http_config()?;
-    if let Some(s) = &http.proxy {
-        return Ok(Some(s.clone()));
+/// Favor cargo's `http.proxy`, then the proxy environment variables, then
+/// git's `http.proxy`. Proxies specified via environment variables are picked
+/// up by libcurl, so `None` is returned if any of them are set. libcurl
+/// ignores `HTTP_PROXY` though, so if it is the only one set it is returned
+/// to be used for all requests.
+fn http_proxy(config: &Config) -> CargoResult<Option<HttpProxy>> {
+    if let Some(proxy) = config.get_string("http.proxy")? {
+        let proxy = HttpProxy {
+            url: proxy.val,
+            source: format!("`http.proxy` in {}", proxy.definition),
+        };
+        validate_proxy(&proxy)?;
+        return Ok(Some(proxy));
+    }
+    if !env_proxies().is_empty() {
+        return Ok(None);
     }
-    if let Ok(cfg) = git2::Config::open_default() {
+    if let Some(url) = env::var("HTTP_PROXY").ok().filter(|s| !s.is_empty()) {
+        return Ok(Some(HttpProxy {
+            url,
+            source: "environment variable `HTTP_PROXY`".to_string(),
+        }));
+    }
+    // Strings can only be read from a snapshot of the configuration.
+    if let Ok(cfg) = git2::Config::open_default().and_then(|mut c| c.snapshot()) {
         if let Ok(s) = cfg.get_str("http.proxy") {
-            return Ok(Some(s.to_string()));
+            let proxy = HttpProxy {
+                url: s.to_string(),
+                source: "git's `http.proxy` configuration".to_string(),
+            };
+            validate_proxy(&proxy)?;
+            return Ok(Some(proxy));
         }
     }
     Ok(None)
 }
 
+/// Returns the proxies set in the environment along with the variable they
+/// were set in, in the order libcurl consults them.
+fn env_proxies() -> Vec<(&'static str, HttpProxy)> {
+    PROXY_ENV_VARS
+        .iter()
+        .filter_map(|var| {
+            let url = env::var(var).ok().filter(|s| !s.is_empty())?;
+            let source = format!("environment variable `{}`", var);
+            Some((*var, HttpProxy { url, source }))
+        })
+        .collect()
+}
+
+/// Returns the hosts which should be contacted without a proxy, from the
+/// `no_proxy` environment variable.
+fn no_proxy() -> Option<String> {
+    ["no_proxy", "NO_PROXY"]
+        .iter()
+        .filter_map(|v| env::var(v).ok())
+        .find(|s| !s.is_empty())
+}
+
+/// Checks if `host` matches the comma-separated list of hosts in `no_proxy`,
+/// using the same rules as libcurl: `*` matches everything, and an entry
+/// matches the host itself as well as all of its subdomains.
+fn no_proxy_matches(no_proxy: &str, host: &str) -> bool {
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    no_proxy.split(',').map(|s| s.trim()).any(|entry| {
+        if entry == "*" {
+            return true;
+        }
+        let entry = entry.trim_start_matches('.');
+        if entry.is_empty() {
+            return false;
+        }
+        host.eq_ignore_ascii_case(entry)
+            || (host.len() > entry.len()
+                && host[host.len() - entry.len()..].eq_ignore_ascii_case(entry)
+                && host.as_bytes()[host.len() - entry.len() - 1] == b'.')
+    })
+}
+
+/// Checks that a proxy looks like something libcurl will accept, so that a
+/// typo is reported along with where the proxy was configured rather than as
+/// an obscure connection failure.
+fn validate_proxy(proxy: &HttpProxy) -> CargoResult<()> {
+    // libcurl assumes `http://` for proxies without a scheme.
+    let url = if proxy.url.contains("://") {
+        proxy.url.clone()
+    } else {
+        format!("http://{}", proxy.url)
+    };
+    let err = match url::Url::parse(&url) {
+        Ok(url) => match url.scheme() {
+            "http" | "https" | "socks4" | "socks4a" | "socks5" | "socks5h" => {
+                if url.host_str().map_or(true, |h| h.is_empty()) {
+                    "it does not specify a host".to_string()
+                } else {
+                    return Ok(());
+                }
+            }
+            scheme => format!("the `{}` scheme is not supported for proxies", scheme),
+        },
+        Err(e) => e.to_string(),
+    };
+    bail!(
+        "invalid proxy URL `{}` from {}: {}",
+        proxy.url,
+        proxy.source,
+        err
+    )
+}
+
+/// Returns the proxy that will be used to contact `url`, if any, taking
+/// `no_proxy` into account.
+pub fn proxy_for_url(config: &Config, url: &str) -> CargoResult<Option<HttpProxy>> {
+    let url = match url::Url::parse(url) {
+        Ok(url) => url,
+        Err(_) => return Ok(None),
+    };
+    let host = match url.host_str() {
+        Some(host) if matches!(url.scheme(), "http" | "https") => host,
+        _ => return Ok(None),
+    };
+    if let Some(no_proxy) = no_proxy() {
+        if no_proxy_matches(&no_proxy, host) {
+            return Ok(None);
+        }
+    }
+    if let Some(proxy) = http_proxy(config)? {
+        return Ok(Some(proxy));
+    }
+    // Like libcurl, only `http_proxy` applies to `http` URLs, and
+    // `https_proxy` (in either case) to `https` URLs.
+    let is_https = url.scheme() == "https";
+    Ok(env_proxies()
+        .into_iter()
+        .find(|(var, _)| var.eq_ignore_ascii_case("https_proxy") == is_https)
+        .map(|(_, proxy)| proxy))
+}
+
+/// Prints a note under `--verbose` naming the proxy used to contact `url`.
+pub fn note_proxy_for_url(config: &Config, url: &str) -> CargoResult<()> {
+    if let Some(proxy) = proxy_for_url(config, url)? {
+        // Proxies from the configuration were validated when read, so this
+        // only warns about the environment, which other programs share.
+        if let Err(e) = validate_proxy(&proxy) {
+            config.shell().warn(e)?;
+        }
+        config.shell().verbose(|s| {
+            s.note(format!(
+                "using proxy `{}` for `{}` (from {})",
+                proxy.url, url, proxy.source
+            ))
+        })?;
+    }
+    Ok(())
+}
+
 /// Determine if an http proxy exists.
 ///
 /// Checks the following for existence, in order:
 ///
 /// * cargo's `http.proxy`
-/// * git's `http.proxy`
-/// * `http_proxy` env var
-/// * `HTTP_PROXY` env var
 /// * `https_proxy` env var
 /// * `HTTPS_PROXY` env var
+/// * `http_proxy` env var
+/// * `HTTP_PROXY` env var
+/// * git's `http.proxy`
 fn http_proxy_exists(config: &Config) -> CargoResult<bool> {
-    if http_proxy(config)?.is_some() {
-        Ok(true)
-    } else {
-        Ok(["http_proxy", "HTTP_PROXY", "https_proxy", "HTTPS_PROXY"]
-            .iter()
-            .any(|v| env::var(v).is_ok()))
-    }
+    Ok(http_proxy(config)?.is_some() || !env_proxies().is_empty())
 }
 
 pub fn registry_login(
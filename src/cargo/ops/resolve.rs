@@ -12,14 +12,18 @@
 
 use crate::core::compiler::{CompileKind, RustcTargetData};
 use crate::core::registry::PackageRegistry;
-use crate::core::resolver::features::{FeatureResolver, ForceAllTargets, ResolvedFeatures};
+use crate::core::resolver::features::{
+    FeatureResolver, FeaturesFor, ForceAllTargets, RequestedFeatures, ResolvedFeatures,
+};
 use crate::core::resolver::{self, HasDevUnits, Resolve, ResolveOpts};
 use crate::core::summary::Summary;
 use crate::core::Feature;
-use crate::core::{PackageId, PackageIdSpec, PackageSet, Source, SourceId, Workspace};
+use crate::core::{
+    InternedString, PackageId, PackageIdSpec, PackageSet, Source, SourceId, Workspace,
+};
 use crate::ops;
 use crate::sources::PathSource;
-use crate::util::errors::{CargoResult, CargoResultExt};
+use crate::util::errors::{CargoResult, CargoResultExt, DefaultFeatureConflictError};
 use crate::util::{profile, CanonicalUrl};
 use log::{debug, trace};
 use std::collections::HashSet;
@@ -151,6 +155,13 @@ pub fn resolve_ws_with_opts<'cfg>(
         has_dev_units,
         force_all_targets,
     )?;
+    warn_default_feature_conflicts(
+        ws,
+        specs,
+        &opts.features,
+        &resolved_with_overrides,
+        &resolved_features,
+    )?;
 
     Ok(WorkspaceResolve {
         pkg_set,
@@ -160,6 +171,52 @@ pub fn resolve_ws_with_opts<'cfg>(
     })
 }
 
+/// Warns about packages built with `--no-default-features` whose `default`
+/// feature is enabled anyway by the packages depending on them.
+fn warn_default_feature_conflicts(
+    ws: &Workspace<'_>,
+    specs: &[PackageIdSpec],
+    requested_features: &RequestedFeatures,
+    resolve: &Resolve,
+    resolved_features: &ResolvedFeatures,
+) -> CargoResult<()> {
+    if requested_features.all_features || requested_features.uses_default_features {
+        return Ok(());
+    }
+    let default = InternedString::new("default");
+    for (member, features) in ws.members_with_features(specs, requested_features)? {
+        if features.all_features
+            || features.uses_default_features
+            || features.features.contains(&default)
+            || !member.summary().features().contains_key(&default)
+        {
+            continue;
+        }
+        let pkg_id = member.package_id();
+        let activated = resolved_features
+            .activated_features_unverified(pkg_id, FeaturesFor::NormalOrDev)
+            .unwrap_or_default();
+        if !activated.contains(&default) {
+            continue;
+        }
+        let required_by: Vec<String> = resolve
+            .iter()
+            .filter(|&parent| {
+                resolve.deps(parent).any(|(dep_id, deps)| {
+                    dep_id == pkg_id && deps.iter().any(|dep| dep.uses_default_features())
+                })
+            })
+            .map(|parent| parent.to_string())
+            .collect();
+        if required_by.is_empty() {
+            continue;
+        }
+        let err = DefaultFeatureConflictError::new(&member.name(), required_by);
+        ws.config().shell().warn(err)?;
+    }
+    Ok(())
+}
+
 fn resolve_with_registry<'cfg>(
     ws: &Workspace<'cfg>,
     registry: &mut PackageRegistry<'cfg>,
@@ -335,7 +335,10 @@ fn cp_sources(
 
         paths::copy(&p, &dst)?;
         let cksum = Sha256::new().update_path(dst)?.finish_hex();
-        cksums.insert(relative.to_str().unwrap().replace("\\", "/"), cksum);
+        let relative = relative.to_str().ok_or_else(|| {
+            anyhow::format_err!("non-utf8 path in source directory: {}", p.display())
+        })?;
+        cksums.insert(relative.replace("\\", "/"), cksum);
     }
     Ok(())
 }
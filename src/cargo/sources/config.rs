@@ -98,6 +98,15 @@ impl<'cfg> SourceConfigMap<'cfg> {
         self.config
     }
 
+    /// Returns whether `id` is redirected to another source with
+    /// `replace-with`.
+    pub fn is_replaced(&self, id: SourceId) -> bool {
+        self.id2name
+            .get(&id)
+            .and_then(|name| self.cfgs.get(name))
+            .map_or(false, |cfg| cfg.replace_with.is_some())
+    }
+
     /// Get the `Source` for a given `SourceId`.
     pub fn load(
         &self,
@@ -7,7 +7,7 @@ use serde::Deserialize;
 use crate::core::source::MaybePackage;
 use crate::core::{Dependency, Package, PackageId, Source, SourceId, Summary};
 use crate::sources::PathSource;
-use crate::util::errors::{CargoResult, CargoResultExt};
+use crate::util::errors::{CargoResult, CargoResultExt, VendorError};
 use crate::util::paths;
 use crate::util::{Config, Sha256};
 
@@ -44,7 +44,14 @@ impl<'cfg> Debug for DirectorySource<'cfg> {
 impl<'cfg> Source for DirectorySource<'cfg> {
     fn query(&mut self, dep: &Dependency, f: &mut dyn FnMut(Summary)) -> CargoResult<()> {
         let packages = self.packages.values().map(|p| &p.0);
-        let matches = packages.filter(|pkg| dep.matches(pkg.summary()));
+        let mut matches = packages.filter(|pkg| dep.matches(pkg.summary())).peekable();
+        // A locked dependency comes from the lock file, which the vendor
+        // directory is expected to have every package of.
+        if matches.peek().is_none() && dep.is_locked() {
+            let version = dep.version_req().to_string();
+            let version = version.trim_start_matches('=');
+            return Err(VendorError::new(&dep.package_name(), version, &self.root).into());
+        }
         for summary in matches.map(|pkg| pkg.summary().clone()) {
             f(summary);
         }
@@ -151,7 +158,9 @@ impl<'cfg> Source for DirectorySource<'cfg> {
             .map(|p| &p.0)
             .cloned()
             .map(MaybePackage::Ready)
-            .ok_or_else(|| anyhow::format_err!("failed to find package with id: {}", id))
+            .ok_or_else(|| {
+                VendorError::new(&id.name(), &id.version().to_string(), &self.root).into()
+            })
     }
 
     fn finish_download(&mut self, _id: PackageId, _data: Vec<u8>) -> CargoResult<Package> {
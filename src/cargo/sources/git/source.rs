@@ -9,7 +9,7 @@ use crate::core::{Dependency, Package, PackageId, Summary};
 use crate::sources::git::utils::{GitRemote, GitRevision};
 use crate::sources::PathSource;
 use crate::util::errors::CargoResult;
-use crate::util::hex::short_hash;
+use crate::util::hasher::ShortDigest;
 use crate::util::Config;
 
 pub struct GitSource<'cfg> {
@@ -47,6 +47,14 @@ impl<'cfg> GitSource<'cfg> {
         Ok(source)
     }
 
+    /// Returns whether the repository behind `source_id` has been fetched
+    /// before, so that it can be checked out without network access.
+    pub fn is_cached(source_id: SourceId, config: &Config) -> bool {
+        let git_path = config.git_path();
+        let git_path = config.assert_package_cache_locked(&git_path);
+        git_path.join("db").join(ident(&source_id)).exists()
+    }
+
     pub fn url(&self) -> &Url {
         self.remote.url()
     }
@@ -69,7 +77,8 @@ fn ident(id: &SourceId) -> String {
 
     let ident = if ident == "" { "_empty" } else { ident };
 
-    format!("{}-{}", ident, short_hash(id.canonical_url()))
+    let url = id.canonical_url().raw_canonicalized_url().as_str();
+    format!("{}-{}", ident, ShortDigest::of(url.as_bytes()))
 }
 
 impl<'cfg> Debug for GitSource<'cfg> {
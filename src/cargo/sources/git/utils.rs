@@ -1,5 +1,6 @@
 use crate::core::GitReference;
-use crate::util::errors::{CargoResult, CargoResultExt};
+use crate::ops;
+use crate::util::errors::{CargoResult, CargoResultExt, GitDirtyCacheError, GitRefError};
 use crate::util::paths;
 use crate::util::process_builder::process;
 use crate::util::{network, Config, IntoUrl, Progress};
@@ -87,7 +88,31 @@ impl GitRemote {
     }
 
     pub fn rev_for(&self, path: &Path, reference: &GitReference) -> CargoResult<GitRevision> {
-        reference.resolve(&self.db_at(path)?.repo)
+        let repo = self.db_at(path)?.repo;
+        reference
+            .resolve(&repo)
+            .map_err(|e| self.missing_ref_error(e, reference))
+    }
+
+    /// Explains errors from resolving `reference` which are caused by it not
+    /// being in the repository.
+    fn missing_ref_error(&self, err: anyhow::Error, reference: &GitReference) -> anyhow::Error {
+        let missing = err
+            .chain()
+            .filter_map(|e| e.downcast_ref::<git2::Error>())
+            .any(|e| match e.code() {
+                git2::ErrorCode::NotFound | git2::ErrorCode::InvalidSpec => true,
+                _ => false,
+            });
+        if !missing {
+            return err;
+        }
+        let (kind, name) = match reference {
+            GitReference::Branch(s) => ("branch", s),
+            GitReference::Tag(s) => ("tag", s),
+            GitReference::Rev(s) => ("revision", s),
+        };
+        err.context(GitRefError::new(self.url.as_str(), kind, name))
     }
 
     pub fn checkout(
@@ -143,7 +168,9 @@ impl GitRemote {
                 let repo = self
                     .clone_into(into, cargo_config)
                     .map_err(|e| format_error(e, "clone"))?;
-                let rev = reference.resolve(&repo)?;
+                let rev = reference
+                    .resolve(&repo)
+                    .map_err(|e| self.missing_ref_error(e, reference))?;
                 (repo, rev)
             }
         };
@@ -206,6 +233,10 @@ impl GitDatabase {
                 // operation succeeds but the object isn't actually there.
                 co.fetch(cargo_config)?;
                 if co.has_object() {
+                    let modified = co.modified_files();
+                    if !modified.is_empty() {
+                        return Err(GitDirtyCacheError::new(dest, modified).into());
+                    }
                     co.reset(cargo_config)?;
                     assert!(co.is_fresh());
                     checkout = Some(co);
@@ -336,6 +367,37 @@ impl<'a> GitCheckout<'a> {
         }
     }
 
+    /// Returns the tracked files whose contents were changed since the
+    /// checkout was completed, which a `reset` would overwrite.
+    ///
+    /// A `reset` moves `HEAD` only after all files are checked out, so while
+    /// `HEAD` isn't at our revision, modified files may just be the remains of
+    /// an interrupted checkout and aren't reported. A repository which can't
+    /// be inspected reports nothing either, it gets repaired by a `reset`.
+    fn modified_files(&self) -> Vec<String> {
+        match self.repo.revparse_single("HEAD") {
+            Ok(ref head) if head.id() == self.revision.0 => {}
+            _ => return Vec::new(),
+        }
+        let mut opts = git2::StatusOptions::new();
+        opts.include_untracked(false)
+            .include_ignored(false)
+            .exclude_submodules(true);
+        let statuses = match self.repo.statuses(Some(&mut opts)) {
+            Ok(statuses) => statuses,
+            Err(_) => return Vec::new(),
+        };
+        statuses
+            .iter()
+            .filter(|entry| {
+                entry
+                    .status()
+                    .intersects(git2::Status::WT_MODIFIED | git2::Status::WT_TYPECHANGE)
+            })
+            .filter_map(|entry| entry.path().map(|path| path.to_string()))
+            .collect()
+    }
+
     fn fetch(&mut self, cargo_config: &Config) -> CargoResult<()> {
         info!("fetch {}", self.repo.path().display());
         let url = self.database.path.into_url()?;
@@ -735,6 +797,8 @@ pub fn fetch(
     // request we're about to issue.
     maybe_gc_repo(repo)?;
 
+    ops::note_proxy_for_url(config, url)?;
+
     // Unfortunately `libgit2` is notably lacking in the realm of authentication
     // when compared to the `git` command line. As a result, allow an escape
     // hatch for users that would prefer to use `git`-the-CLI for fetching
@@ -794,6 +858,11 @@ fn fetch_with_cli(
     config: &Config,
 ) -> CargoResult<()> {
     let mut cmd = process("git");
+    // Make git use the same proxy as cargo would, since git otherwise
+    // prefers its own `http.proxy` over cargo's and the environment.
+    if let Some(proxy) = ops::proxy_for_url(config, url)? {
+        cmd.arg("-c").arg(format!("http.proxy={}", proxy.url));
+    }
     cmd.arg("fetch")
         .arg("--tags") // fetch all tags
         .arg("--force") // handle force pushes
@@ -12,6 +12,34 @@ use crate::core::{Dependency, Package, PackageId, Source, SourceId, Summary};
 use crate::ops;
 use crate::util::{internal, paths, CargoResult, CargoResultExt, Config};
 
+/// Why `PathSource::list_files_with_reasons` selected a file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FileSelection {
+    /// The file is tracked by git.
+    Tracked,
+    /// The file isn't tracked by git, but isn't ignored either.
+    Untracked,
+    /// The file matches a pattern of the package's `include` list.
+    Included,
+    /// The package isn't in a git repository, and the file was found by
+    /// walking its directory.
+    Found,
+    /// The file is always packaged, like `Cargo.toml`.
+    Required,
+}
+
+impl fmt::Display for FileSelection {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            FileSelection::Tracked => "tracked by git",
+            FileSelection::Untracked => "untracked, not ignored by git",
+            FileSelection::Included => "matches `package.include`",
+            FileSelection::Found => "found in package directory",
+            FileSelection::Required => "always included",
+        })
+    }
+}
+
 pub struct PathSource<'cfg> {
     source_id: SourceId,
     path: PathBuf,
@@ -96,6 +124,15 @@ impl<'cfg> PathSource<'cfg> {
     /// are relevant for building this package, but it also contains logic to
     /// use other methods like .gitignore to filter the list of files.
     pub fn list_files(&self, pkg: &Package) -> CargoResult<Vec<PathBuf>> {
+        let files = self.list_files_with_reasons(pkg)?;
+        Ok(files.into_iter().map(|(path, _)| path).collect())
+    }
+
+    /// Like `list_files`, but also says why each file was selected.
+    pub fn list_files_with_reasons(
+        &self,
+        pkg: &Package,
+    ) -> CargoResult<Vec<(PathBuf, FileSelection)>> {
         self._list_files(pkg).chain_err(|| {
             format!(
                 "failed to determine list of files in {}",
@@ -104,7 +141,7 @@ impl<'cfg> PathSource<'cfg> {
         })
     }
 
-    fn _list_files(&self, pkg: &Package) -> CargoResult<Vec<PathBuf>> {
+    fn _list_files(&self, pkg: &Package) -> CargoResult<Vec<(PathBuf, FileSelection)>> {
         let root = pkg.root();
         let no_include_option = pkg.manifest().include().is_empty();
 
@@ -166,7 +203,15 @@ impl<'cfg> PathSource<'cfg> {
             // no include option and not git repo discovered (see rust-lang/cargo#7183).
             return self.list_files_walk_except_dot_files_and_dirs(pkg, &mut filter);
         }
-        self.list_files_walk(pkg, &mut filter)
+        let mut files = self.list_files_walk(pkg, FileSelection::Included, &mut filter)?;
+        for (path, reason) in &mut files {
+            if path.parent() == Some(root)
+                && (path.ends_with("Cargo.toml") || path.ends_with("Cargo.lock"))
+            {
+                *reason = FileSelection::Required;
+            }
+        }
+        Ok(files)
     }
 
     // Returns `Some(_)` if found sibling `Cargo.toml` and `.git` directory;
@@ -176,7 +221,7 @@ impl<'cfg> PathSource<'cfg> {
         pkg: &Package,
         root: &Path,
         filter: &mut dyn FnMut(&Path, bool) -> CargoResult<bool>,
-    ) -> CargoResult<Option<Vec<PathBuf>>> {
+    ) -> CargoResult<Option<Vec<(PathBuf, FileSelection)>>> {
         let repo = match git2::Repository::discover(root) {
             Ok(repo) => repo,
             Err(e) => {
@@ -222,7 +267,7 @@ impl<'cfg> PathSource<'cfg> {
         pkg: &Package,
         repo: &git2::Repository,
         filter: &mut dyn FnMut(&Path, bool) -> CargoResult<bool>,
-    ) -> CargoResult<Vec<PathBuf>> {
+    ) -> CargoResult<Vec<(PathBuf, FileSelection)>> {
         warn!("list_files_git {}", pkg.package_id());
         let index = repo.index()?;
         let root = repo
@@ -230,7 +275,7 @@ impl<'cfg> PathSource<'cfg> {
             .ok_or_else(|| anyhow::format_err!("can't list files on a bare repository"))?;
         let pkg_path = pkg.root();
 
-        let mut ret = Vec::<PathBuf>::new();
+        let mut ret = Vec::new();
 
         // We use information from the Git repository to guide us in traversing
         // its tree. The primary purpose of this is to take advantage of the
@@ -250,7 +295,7 @@ impl<'cfg> PathSource<'cfg> {
             } else {
                 Some(entry.mode == GIT_FILEMODE_COMMIT as u32)
             };
-            (join(root, &entry.path), is_dir)
+            (join(root, &entry.path), is_dir, FileSelection::Tracked)
         });
         let mut opts = git2::StatusOptions::new();
         opts.include_untracked(true);
@@ -261,15 +306,17 @@ impl<'cfg> PathSource<'cfg> {
         let untracked = statuses.iter().filter_map(|entry| match entry.status() {
             // Don't include Cargo.lock if it is untracked. Packaging will
             // generate a new one as needed.
-            git2::Status::WT_NEW if entry.path() != Some("Cargo.lock") => {
-                Some((join(root, entry.path_bytes()), None))
-            }
+            git2::Status::WT_NEW if entry.path() != Some("Cargo.lock") => Some((
+                join(root, entry.path_bytes()),
+                None,
+                FileSelection::Untracked,
+            )),
             _ => None,
         });
 
         let mut subpackages_found = Vec::new();
 
-        for (file_path, is_dir) in index_files.chain(untracked) {
+        for (file_path, is_dir, reason) in index_files.chain(untracked) {
             let file_path = file_path?;
 
             // Filter out files blatantly outside this package. This is helped a
@@ -290,7 +337,7 @@ impl<'cfg> PathSource<'cfg> {
                     let path = file_path.parent().unwrap();
                     if path != pkg_path {
                         warn!("subpackage found: {}", path.display());
-                        ret.retain(|p| !p.starts_with(path));
+                        ret.retain(|(p, _): &(PathBuf, _)| !p.starts_with(path));
                         subpackages_found.push(path.to_path_buf());
                         continue;
                     }
@@ -323,14 +370,14 @@ impl<'cfg> PathSource<'cfg> {
                         ret.extend(files.into_iter());
                     }
                     Err(..) => {
-                        PathSource::walk(&file_path, &mut ret, false, filter)?;
+                        self.walk(&file_path, &mut ret, false, reason, filter)?;
                     }
                 }
             } else if (*filter)(&file_path, is_dir)? {
                 assert!(!is_dir);
                 // We found a file!
                 warn!("  found {}", file_path.display());
-                ret.push(file_path);
+                ret.push((file_path, reason));
             }
         }
         return Ok(ret);
@@ -359,7 +406,7 @@ impl<'cfg> PathSource<'cfg> {
         &self,
         pkg: &Package,
         filter: &mut dyn FnMut(&Path, bool) -> CargoResult<bool>,
-    ) -> CargoResult<Vec<PathBuf>> {
+    ) -> CargoResult<Vec<(PathBuf, FileSelection)>> {
         let root = pkg.root();
         let mut exclude_dot_files_dir_builder = GitignoreBuilder::new(root);
         exclude_dot_files_dir_builder.add_line(None, ".*")?;
@@ -373,23 +420,38 @@ impl<'cfg> PathSource<'cfg> {
                     _ => filter(path, is_dir),
                 }
             };
-        self.list_files_walk(pkg, &mut filter_ignore_dot_files_and_dirs)
+        self.list_files_walk(
+            pkg,
+            FileSelection::Found,
+            &mut filter_ignore_dot_files_and_dirs,
+        )
     }
 
     fn list_files_walk(
         &self,
         pkg: &Package,
+        reason: FileSelection,
         filter: &mut dyn FnMut(&Path, bool) -> CargoResult<bool>,
-    ) -> CargoResult<Vec<PathBuf>> {
+    ) -> CargoResult<Vec<(PathBuf, FileSelection)>> {
         let mut ret = Vec::new();
-        PathSource::walk(pkg.root(), &mut ret, true, filter)?;
+        self.walk(pkg.root(), &mut ret, true, reason, filter)?;
         Ok(ret)
     }
 
+    /// Walks `path` collecting every file accepted by `filter` into `ret`,
+    /// selected for `reason`.
+    ///
+    /// Symbolic links are followed, so a link to a file is listed like a
+    /// regular file and a link to a directory is walked like one. A link to
+    /// a directory containing it would make the walk loop forever, so such
+    /// links are skipped with a warning. Dangling links are listed as-is and
+    /// left for the caller to deal with.
     fn walk(
+        &self,
         path: &Path,
-        ret: &mut Vec<PathBuf>,
+        ret: &mut Vec<(PathBuf, FileSelection)>,
         is_root: bool,
+        reason: FileSelection,
         filter: &mut dyn FnMut(&Path, bool) -> CargoResult<bool>,
     ) -> CargoResult<()> {
         let is_dir = path.is_dir();
@@ -397,7 +459,15 @@ impl<'cfg> PathSource<'cfg> {
             return Ok(());
         }
         if !is_dir {
-            ret.push(path.to_path_buf());
+            ret.push((path.to_path_buf(), reason));
+            return Ok(());
+        }
+        if !is_root && is_symlink_cycle(path) {
+            self.config.shell().warn(format!(
+                "skipping symbolic link `{}` which points to one of its parent \
+                 directories and would form a cycle",
+                path.display()
+            ))?;
             return Ok(());
         }
         // Don't recurse into any sub-packages that we have.
@@ -421,7 +491,7 @@ impl<'cfg> PathSource<'cfg> {
                 // Skip Cargo artifacts.
                 continue;
             }
-            PathSource::walk(&path, ret, false, filter)?;
+            self.walk(&path, ret, false, reason, filter)?;
         }
         Ok(())
     }
@@ -446,8 +516,18 @@ impl<'cfg> PathSource<'cfg> {
             // broken symlink, a permissions error, or a race
             // condition where this path was `rm`-ed -- either way,
             // we can ignore the error and treat the path's `mtime`
-            // as `0`.
-            let mtime = paths::mtime(&file).unwrap_or_else(|_| FileTime::zero());
+            // as `0`. Broken symlinks are worth a heads up though, as
+            // `cargo package` will refuse to include them.
+            let mtime = paths::mtime(&file).unwrap_or_else(|_| {
+                if let Some(target) = dangling_symlink_target(&file) {
+                    let _ = self.config.shell().warn(format!(
+                        "symbolic link `{}` points to `{}`, which does not exist",
+                        file.display(),
+                        target.display()
+                    ));
+                }
+                FileTime::zero()
+            });
             if mtime > max {
                 max = mtime;
                 max_path = file;
@@ -522,6 +602,10 @@ impl<'cfg> Source for PathSource<'cfg> {
 
     fn fingerprint(&self, pkg: &Package) -> CargoResult<String> {
         let (max, max_path) = self.last_modified_file(pkg)?;
+        // Use the path relative to the package so the fingerprint doesn't
+        // depend on where the package (or whatever a symbolic link inside of
+        // it points to) lives on disk.
+        let max_path = max_path.strip_prefix(pkg.root()).unwrap_or(&max_path);
         Ok(format!("{} ({})", max, max_path.display()))
     }
 
@@ -538,3 +622,28 @@ impl<'cfg> Source for PathSource<'cfg> {
         Ok(false)
     }
 }
+
+/// Returns whether `path` is a symbolic link to one of its own parent
+/// directories, which would make walking it recurse forever.
+fn is_symlink_cycle(path: &Path) -> bool {
+    let is_link = fs::symlink_metadata(path)
+        .map(|m| m.file_type().is_symlink())
+        .unwrap_or(false);
+    if !is_link {
+        return false;
+    }
+    match (fs::canonicalize(path), path.parent().map(fs::canonicalize)) {
+        (Ok(target), Some(Ok(parent))) => parent.starts_with(target),
+        _ => false,
+    }
+}
+
+/// Returns where `path` points to if it is a symbolic link whose target
+/// doesn't exist.
+pub fn dangling_symlink_target(path: &Path) -> Option<PathBuf> {
+    let target = fs::read_link(path).ok()?;
+    if fs::metadata(path).is_ok() {
+        return None;
+    }
+    Some(target)
+}
@@ -69,6 +69,7 @@
 use crate::core::dependency::Dependency;
 use crate::core::{InternedString, PackageId, SourceId, Summary};
 use crate::sources::registry::{RegistryData, RegistryPackage};
+use crate::util::errors::{DuplicateFeatureError, ErrorThrottle, IndexParseError};
 use crate::util::paths;
 use crate::util::{internal, CargoResult, Config, Filesystem, ToSemver};
 use log::info;
@@ -77,6 +78,7 @@ use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::Path;
 use std::str;
+use std::time::Duration;
 
 /// Crates.io treats hyphen and underscores as interchangeable, but the index and old Cargo do not.
 /// Therefore, the index must store uncanonicalized version of the name so old Cargo's can find it.
@@ -163,6 +165,17 @@ fn overflow_hyphen() {
     )
 }
 
+#[test]
+fn duplicate_feature_in_index() {
+    use crate::util::IntoUrl;
+
+    let source_id = SourceId::for_registry(&"https://example.com".into_url().unwrap()).unwrap();
+    let line = br#"{"name":"a","vers":"0.0.1","deps":[],"cksum":"bae3","features":{"f":["g"],"g":[],"f":[]}}"#;
+    let err = IndexSummary::parse(line, source_id).err().unwrap();
+    let err = err.downcast_ref::<DuplicateFeatureError>().unwrap();
+    assert_eq!(err.feature(), "f");
+}
+
 pub struct RegistryIndex<'cfg> {
     source_id: SourceId,
     path: Filesystem,
@@ -505,6 +518,18 @@ impl Summaries {
         // information. Here we parse every single line in the index (as we need
         // to find the versions)
         log::debug!("slow path for {:?}", relative);
+        let lenient = config
+            .get::<Option<bool>>("registry.lenient-index")?
+            .unwrap_or(false);
+        let package = relative
+            .file_name()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        // Every line of an index file written by a newer cargo is likely to
+        // fail the same way, so only log each distinct failure once.
+        let mut unparsed = ErrorThrottle::new(Duration::from_secs(60), |msg: &str| {
+            log::info!("failed to parse {:?} registry package: {}", relative, msg)
+        });
         let mut ret = Summaries::default();
         let mut hit_closure = false;
         let mut cache_bytes = None;
@@ -512,16 +537,51 @@ impl Summaries {
             ret.raw_data = contents.to_vec();
             let mut cache = SummariesCache::default();
             hit_closure = true;
-            for line in split(contents, b'\n') {
+            for (i, line) in split(contents, b'\n').enumerate() {
                 // Attempt forwards-compatibility on the index by ignoring
                 // everything that we ourselves don't understand, that should
                 // allow future cargo implementations to break the
                 // interpretation of each line here and older cargo will simply
                 // ignore the new lines.
+                //
+                // Lines which aren't JSON at all can't come from a future
+                // cargo though, they mean the index is corrupted.
                 let summary = match IndexSummary::parse(line, source_id) {
                     Ok(summary) => summary,
                     Err(e) => {
-                        log::info!("failed to parse {:?} registry package: {}", relative, e);
+                        // A feature defined twice isn't something a future
+                        // cargo would write, so say why the entry is unusable.
+                        if e.downcast_ref::<DuplicateFeatureError>().is_some() {
+                            config.shell().warn(format!(
+                                "skipping entry for package `{}` in registry index file \
+                                 `{}` on line {}: {}",
+                                package,
+                                relative.display(),
+                                i + 1,
+                                e
+                            ))?;
+                            continue;
+                        }
+                        let malformed = match e.downcast_ref::<serde_json::Error>() {
+                            Some(e) => e.is_syntax() || e.is_eof(),
+                            None => false,
+                        };
+                        if !malformed {
+                            unparsed.observe(&e);
+                            continue;
+                        }
+                        let err = IndexParseError::new(&package, relative, i + 1, e.to_string());
+                        if !lenient {
+                            return Err(err.into());
+                        }
+                        config.shell().warn(format!(
+                            "skipping malformed entry for package `{}` in registry index \
+                             file `{}` on line {}: {}",
+                            err.package,
+                            err.path.display(),
+                            err.line,
+                            err.message
+                        ))?;
                         continue;
                     }
                 };
@@ -542,6 +602,7 @@ impl Summaries {
             debug_assert!(cache_contents.is_none());
             return Ok(None);
         }
+        drop(unparsed);
         err?;
 
         // If we've got debug assertions enabled and the cache was previously
@@ -723,7 +784,7 @@ impl IndexSummary {
             .map(|dep| dep.into_dep(source_id))
             .collect::<CargoResult<Vec<_>>>()?;
         let namespaced_features = false;
-        let mut summary = Summary::new(pkgid, deps, &features, links, namespaced_features)?;
+        let mut summary = Summary::new(pkgid, deps, features, links, namespaced_features)?;
         summary.set_checksum(cksum);
         Ok(IndexSummary {
             summary,
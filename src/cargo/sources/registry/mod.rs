@@ -159,8 +159,8 @@
 //! ```
 
 use std::borrow::Cow;
-use std::collections::BTreeMap;
 use std::collections::HashSet;
+use std::fmt;
 use std::fs::{File, OpenOptions};
 use std::io::Write;
 use std::path::{Path, PathBuf};
@@ -168,6 +168,7 @@ use std::path::{Path, PathBuf};
 use flate2::read::GzDecoder;
 use log::debug;
 use semver::{Version, VersionReq};
+use serde::de;
 use serde::Deserialize;
 use tar::Archive;
 
@@ -230,7 +231,8 @@ pub struct RegistryPackage<'a> {
     vers: Version,
     #[serde(borrow)]
     deps: Vec<RegistryDependency<'a>>,
-    features: BTreeMap<InternedString, Vec<InternedString>>,
+    #[serde(deserialize_with = "deserialize_features")]
+    features: Vec<(InternedString, Vec<InternedString>)>,
     cksum: String,
     /// If `true`, Cargo will skip this version when resolving.
     ///
@@ -244,6 +246,39 @@ pub struct RegistryPackage<'a> {
     links: Option<InternedString>,
 }
 
+/// Deserializes the `features` table of an index entry, keeping every entry
+/// so that `Summary::new` can reject features defined more than once instead
+/// of the last definition silently winning.
+fn deserialize_features<'de, D>(
+    deserializer: D,
+) -> Result<Vec<(InternedString, Vec<InternedString>)>, D::Error>
+where
+    D: de::Deserializer<'de>,
+{
+    struct FeaturesVisitor;
+
+    impl<'de> de::Visitor<'de> for FeaturesVisitor {
+        type Value = Vec<(InternedString, Vec<InternedString>)>;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+            formatter.write_str("a table of features")
+        }
+
+        fn visit_map<A>(self, mut access: A) -> Result<Self::Value, A::Error>
+        where
+            A: de::MapAccess<'de>,
+        {
+            let mut features = Vec::new();
+            while let Some(entry) = access.next_entry()? {
+                features.push(entry);
+            }
+            Ok(features)
+        }
+    }
+
+    deserializer.deserialize_map(FeaturesVisitor)
+}
+
 #[test]
 fn escaped_char_in_json() {
     let _: RegistryPackage<'_> = serde_json::from_str(
@@ -103,7 +103,7 @@ impl<'de, 'config> de::Deserializer<'de> for Deserializer<'config> {
         Err(ConfigError::missing(&self.key))
     }
 
-    deserialize_method!(deserialize_bool, visit_bool, get_bool);
+    deserialize_method!(deserialize_bool, visit_bool, get_bool_priv);
     deserialize_method!(deserialize_i8, visit_i64, get_integer);
     deserialize_method!(deserialize_i16, visit_i64, get_integer);
     deserialize_method!(deserialize_i32, visit_i64, get_integer);
@@ -220,7 +220,7 @@ enum KeyKind {
 impl<'config> ConfigMapAccess<'config> {
     fn new_map(de: Deserializer<'config>) -> Result<ConfigMapAccess<'config>, ConfigError> {
         let mut fields = Vec::new();
-        if let Some(mut v) = de.config.get_table(&de.key)? {
+        if let Some(mut v) = de.config._get_table(&de.key)? {
             // `v: Value<HashMap<String, CV>>`
             for (key, _value) in v.val.drain() {
                 fields.push(KeyKind::CaseSensitive(key));
@@ -260,7 +260,7 @@ impl<'config> ConfigMapAccess<'config> {
         // possible fields on this key that we're *supposed* to use, so take
         // this opportunity to warn about any keys that aren't recognized as
         // fields and warn about them.
-        if let Some(mut v) = de.config.get_table(&de.key)? {
+        if let Some(mut v) = de.config._get_table(&de.key)? {
             for (t_key, value) in v.val.drain() {
                 if fields.iter().any(|k| match k {
                     KeyKind::Normal(s) => s == &t_key,
@@ -468,7 +468,7 @@ impl<'de, 'config> de::MapAccess<'de> for ValueDeserializer<'config> {
                 seed.deserialize(Tuple2Deserializer(0i32, path.to_string_lossy()))
             }
             Definition::Environment(env) => {
-                seed.deserialize(Tuple2Deserializer(1i32, env.as_ref()))
+                seed.deserialize(Tuple2Deserializer(1i32, env.as_str()))
             }
             Definition::Cli => seed.deserialize(Tuple2Deserializer(2i32, "")),
         }
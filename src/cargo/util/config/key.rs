@@ -84,7 +84,7 @@ impl ConfigKey {
     }
 
     /// Returns an iterator of the key parts as strings.
-    pub(super) fn parts(&self) -> impl Iterator<Item = &str> {
+    pub fn parts(&self) -> impl Iterator<Item = &str> {
         self.parts.iter().map(|p| p.0.as_ref())
     }
 }
@@ -51,7 +51,7 @@
 
 use std::cell::{RefCell, RefMut};
 use std::collections::hash_map::Entry::{Occupied, Vacant};
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::env;
 use std::fmt;
 use std::fs::{self, File};
@@ -74,9 +74,9 @@ use crate::core::compiler::rustdoc::RustdocExternMap;
 use crate::core::shell::Verbosity;
 use crate::core::{nightly_features_allowed, CliUnstable, Shell, SourceId, Workspace};
 use crate::ops;
-use crate::util::errors::{CargoResult, CargoResultExt};
+use crate::util::errors::{CargoResult, CargoResultExt, ConfigFileError, EnvConfigError};
 use crate::util::toml as cargo_toml;
-use crate::util::{paths, validate_package_name};
+use crate::util::{paths, profile, validate_package_name};
 use crate::util::{FileLock, Filesystem, IntoUrl, IntoUrlWithBase, Rustc};
 
 mod de;
@@ -86,7 +86,7 @@ mod value;
 pub use value::{Definition, OptValue, Value};
 
 mod key;
-use key::ConfigKey;
+pub use key::ConfigKey;
 
 mod path;
 pub use path::{ConfigRelativePath, PathAndArgs};
@@ -100,7 +100,7 @@ macro_rules! get_value_typed {
         /// Low-level private method for getting a config value as an OptValue.
         fn $name(&self, key: &ConfigKey) -> Result<OptValue<$ty>, ConfigError> {
             let cv = self.get_cv(key)?;
-            let env = self.get_env::<$ty>(key)?;
+            let env = self.get_env::<$ty>(key, $expected)?;
             match (cv, env) {
                 (Some(CV::$variant(val, definition)), Some(env)) => {
                     if definition.is_higher_priority(&env.definition) {
@@ -397,6 +397,30 @@ impl Config {
         self.values.try_borrow_with(|| self.load_values())
     }
 
+    /// Returns the paths of all config files that values were loaded from.
+    pub fn loaded_files(&self) -> CargoResult<BTreeSet<PathBuf>> {
+        fn add(def: &Definition, files: &mut BTreeSet<PathBuf>) {
+            if let Definition::Path(p) = def {
+                if p.is_file() {
+                    files.insert(p.clone());
+                }
+            }
+        }
+        fn collect(value: &ConfigValue, files: &mut BTreeSet<PathBuf>) {
+            add(value.definition(), files);
+            match value {
+                CV::Table(map, _) => map.values().for_each(|v| collect(v, files)),
+                CV::List(list, _) => list.iter().for_each(|(_, def)| add(def, files)),
+                _ => {}
+            }
+        }
+        let mut files = BTreeSet::new();
+        for value in self.values()?.values() {
+            collect(value, &mut files);
+        }
+        Ok(files)
+    }
+
     /// Gets a mutable copy of the on-disk config values.
     ///
     /// This requires the config values to already have been loaded. This
@@ -492,23 +516,66 @@ impl Config {
         Ok(Some(val.clone()))
     }
 
+    /// Get a configuration value by key, including environment variables.
+    ///
+    /// An environment variable is converted to the type of the value it
+    /// overrides in the config files if it can be, and is otherwise a string
+    /// (or a whitespace-separated list, if it overrides a list).
+    pub fn get_cv_with_env(&self, key: &ConfigKey) -> CargoResult<Option<ConfigValue>> {
+        let cv = self.get_cv(key)?;
+        let env = match self.env.get(key.as_env_key()) {
+            Some(env) => env,
+            None => return Ok(cv),
+        };
+        let def = Definition::Environment(key.as_env_key().to_string());
+        let env_cv = match &cv {
+            Some(cv) if cv.definition().is_higher_priority(&def) => return Ok(Some(cv.clone())),
+            Some(CV::Integer(..)) => env.parse().ok().map(|i| CV::Integer(i, def.clone())),
+            Some(CV::Boolean(..)) => env.parse().ok().map(|b| CV::Boolean(b, def.clone())),
+            Some(CV::List(..)) => Some(CV::List(
+                env.split_whitespace()
+                    .map(|s| (s.to_string(), def.clone()))
+                    .collect(),
+                def.clone(),
+            )),
+            _ => None,
+        };
+        Ok(Some(env_cv.unwrap_or_else(|| CV::String(env.clone(), def))))
+    }
+
+    /// Returns the environment variables starting with `CARGO_`, which may
+    /// override config values.
+    pub fn env_config_vars(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.env
+            .iter()
+            .filter(|(k, _)| k.starts_with("CARGO_"))
+            .map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+
     /// Helper primarily for testing.
     pub fn set_env(&mut self, env: HashMap<String, String>) {
         self.env = env;
     }
 
-    fn get_env<T>(&self, key: &ConfigKey) -> Result<OptValue<T>, ConfigError>
+    /// Gets a config value from the environment, converted from a string.
+    ///
+    /// Conversion errors are reported like type errors in config files, with
+    /// `expected` describing the type.
+    fn get_env<T>(&self, key: &ConfigKey, expected: &str) -> Result<OptValue<T>, ConfigError>
     where
         T: FromStr,
-        <T as FromStr>::Err: fmt::Display,
     {
         match self.env.get(key.as_env_key()) {
             Some(value) => {
                 let definition = Definition::Environment(key.as_env_key().to_string());
                 Ok(Some(Value {
-                    val: value
-                        .parse()
-                        .map_err(|e| ConfigError::new(format!("{}", e), definition.clone()))?,
+                    val: value.parse().map_err(|_| {
+                        let env_key = key.as_env_key();
+                        let key = key.to_string();
+                        ConfigError::from(anyhow::Error::from(EnvConfigError::new(
+                            env_key, &key, value, expected,
+                        )))
+                    })?,
                     definition,
                 }))
             }
@@ -542,6 +609,20 @@ impl Config {
         self.get::<Option<Value<String>>>(key)
     }
 
+    /// Get a boolean config value.
+    ///
+    /// See `get` for more details.
+    pub fn get_bool(&self, key: &str) -> CargoResult<OptValue<bool>> {
+        self.get::<Option<Value<bool>>>(key)
+    }
+
+    /// Get an integer config value.
+    ///
+    /// See `get` for more details.
+    pub fn get_i64(&self, key: &str) -> CargoResult<OptValue<i64>> {
+        self.get::<Option<Value<i64>>>(key)
+    }
+
     /// Get a config value that is expected to be a path.
     ///
     /// This returns a relative path if the value does not contain any
@@ -568,8 +649,9 @@ impl Config {
 
     /// Get a list of strings.
     ///
-    /// DO NOT USE outside of the config module. `pub` will be removed in the
-    /// future.
+    /// Lists defined in multiple config files are concatenated, starting with
+    /// the highest precedence file. Each element remembers where it was
+    /// defined.
     ///
     /// NOTE: this does **not** support environment variables. Use `get` instead
     /// if you want that.
@@ -578,6 +660,18 @@ impl Config {
         self._get_list(&key)
     }
 
+    /// Get a table of config values.
+    ///
+    /// Tables defined in multiple config files are merged, with each value
+    /// remembering where it was defined.
+    ///
+    /// NOTE: this does **not** support environment variables. Use `get` instead
+    /// if you want that.
+    pub fn get_table(&self, key: &str) -> CargoResult<OptValue<HashMap<String, ConfigValue>>> {
+        let key = ConfigKey::from_str(key);
+        self._get_table(&key)
+    }
+
     fn _get_list(&self, key: &ConfigKey) -> CargoResult<OptValue<Vec<(String, Definition)>>> {
         match self.get_cv(key)? {
             Some(CV::List(val, definition)) => Ok(Some(Value { val, definition })),
@@ -653,7 +747,7 @@ impl Config {
     /// Low-level method for getting a config value as a `OptValue<HashMap<String, CV>>`.
     ///
     /// NOTE: This does not read from env. The caller is responsible for that.
-    fn get_table(&self, key: &ConfigKey) -> CargoResult<OptValue<HashMap<String, CV>>> {
+    fn _get_table(&self, key: &ConfigKey) -> CargoResult<OptValue<HashMap<String, CV>>> {
         match self.get_cv(key)? {
             Some(CV::Table(val, definition)) => Ok(Some(Value { val, definition })),
             Some(val) => self.expected("table", key, &val),
@@ -662,7 +756,7 @@ impl Config {
     }
 
     get_value_typed! {get_integer, i64, Integer, "an integer"}
-    get_value_typed! {get_bool, bool, Boolean, "true/false"}
+    get_value_typed! {get_bool_priv, bool, Boolean, "true/false"}
     get_value_typed! {get_string_priv, String, String, "a string"}
 
     /// Generate an error when the given value is the wrong type.
@@ -701,10 +795,21 @@ impl Config {
         struct TermConfig {
             verbose: Option<bool>,
             color: Option<String>,
+            // Read by the `cargo` binary, only listed so it isn't reported as
+            // an unused key.
+            #[serde(rename = "error-format")]
+            _error_format: Option<String>,
         }
 
-        // Ignore errors in the configuration files.
-        let term = self.get::<TermConfig>("term").unwrap_or_default();
+        // Errors loading the configuration files are reported by whatever
+        // needs them later on, but a bad `term` value would go unnoticed, so
+        // warn about it and fall back to the defaults.
+        let term = if self.values().is_ok() {
+            let term = self.get::<TermConfig>("term");
+            term.unwrap_or_warn(&mut self.shell(), TermConfig::default())
+        } else {
+            TermConfig::default()
+        };
 
         let color = color.or_else(|| term.color.as_deref());
 
@@ -742,6 +847,16 @@ impl Config {
                 .unwrap_or(false);
         self.target_dir = cli_target_dir;
 
+        if let Some(path) = &self.unstable_flags.diagnostics_stream {
+            let path = self.cwd.join(path);
+            let file = File::create(&path).chain_err(|| {
+                format!("failed to create diagnostics stream `{}`", path.display())
+            })?;
+            self.shell().set_diagnostics_stream(Box::new(file));
+        }
+        self.shell()
+            .set_error_summary(self.unstable_flags.error_summary);
+
         if nightly_features_allowed() {
             if let Some(val) = self.get::<Option<bool>>("unstable.mtime_on_use")? {
                 self.unstable_flags.mtime_on_use |= val;
@@ -781,6 +896,7 @@ impl Config {
     }
 
     fn load_values_from(&self, path: &Path) -> CargoResult<HashMap<String, ConfigValue>> {
+        let _p = profile::start("loading config");
         // This definition path is ignored, this is just a temporary container
         // representing the entire file.
         let mut cfg = CV::Table(HashMap::new(), Definition::Path(PathBuf::from(".")));
@@ -812,16 +928,21 @@ impl Config {
                 path.display()
             );
         }
-        let contents = fs::read_to_string(path)
-            .chain_err(|| format!("failed to read configuration file `{}`", path.display()))?;
-        let toml = cargo_toml::parse(&contents, path, self)
-            .chain_err(|| format!("could not parse TOML configuration in `{}`", path.display()))?;
-        let value = CV::from_toml(Definition::Path(path.to_path_buf()), toml).chain_err(|| {
-            format!(
-                "failed to load TOML configuration from `{}`",
-                path.display()
-            )
+        let contents = fs::read_to_string(path).map_err(|e| {
+            let cause = if path.is_dir() {
+                anyhow!("expected a file, but found a directory")
+            } else if e.kind() == io::ErrorKind::PermissionDenied {
+                anyhow::Error::from(e).context("the file is not readable")
+            } else {
+                e.into()
+            };
+            ConfigFileError::new(cause, path.to_path_buf())
         })?;
+        let toml = cargo_toml::parse(&contents, path, self)
+            .map_err(|e| ConfigFileError::new(e, path.to_path_buf()))?;
+        let mut key = Vec::new();
+        let value = CV::from_toml_at(Definition::Path(path.to_path_buf()), toml, &mut key)
+            .map_err(|e| ConfigFileError::new(e, path.to_path_buf()).with_key(key.join(".")))?;
         let value = self.load_includes(value, seen)?;
         Ok(value)
     }
@@ -1206,7 +1327,7 @@ impl Config {
             key: ConfigKey::from_str(key),
             env_prefix_ok: true,
         };
-        T::deserialize(d).map_err(|e| e.into())
+        T::deserialize(d).map_err(ConfigError::into_error)
     }
 
     pub fn assert_package_cache_locked<'a>(&self, f: &'a Filesystem) -> &'a Path {
@@ -1324,11 +1445,23 @@ impl ConfigError {
 
     fn with_key_context(self, key: &ConfigKey, definition: Definition) -> ConfigError {
         ConfigError {
-            error: anyhow::Error::from(self)
+            error: self
+                .into_error()
                 .context(format!("could not load config key `{}`", key)),
             definition: Some(definition),
         }
     }
+
+    /// Converts into an `anyhow::Error`. Without a definition to prefix the
+    /// message with, this error is displayed like the wrapped one, which is
+    /// then returned as is so that it can be downcast, as an `EnvConfigError`
+    /// for example.
+    fn into_error(self) -> anyhow::Error {
+        match self.definition {
+            Some(_) => self.into(),
+            None => self.error,
+        }
+    }
 }
 
 impl std::error::Error for ConfigError {
@@ -1397,6 +1530,25 @@ impl fmt::Debug for ConfigValue {
 
 impl ConfigValue {
     fn from_toml(def: Definition, toml: toml::Value) -> CargoResult<ConfigValue> {
+        let mut key = Vec::new();
+        CV::from_toml_at(def, toml, &mut key).map_err(|e| {
+            if key.is_empty() {
+                e
+            } else {
+                e.context(format!("failed to parse key `{}`", key.join(".")))
+            }
+        })
+    }
+
+    /// Converts `toml` into a `ConfigValue`.
+    ///
+    /// `key` is the path of the table `toml` is nested in. On error it is left
+    /// holding the path of the offending value.
+    fn from_toml_at(
+        def: Definition,
+        toml: toml::Value,
+        key: &mut Vec<String>,
+    ) -> CargoResult<ConfigValue> {
         match toml {
             toml::Value::String(val) => Ok(CV::String(val, def)),
             toml::Value::Boolean(b) => Ok(CV::Boolean(b, def)),
@@ -1412,10 +1564,11 @@ impl ConfigValue {
             )),
             toml::Value::Table(val) => Ok(CV::Table(
                 val.into_iter()
-                    .map(|(key, value)| {
-                        let value = CV::from_toml(def.clone(), value)
-                            .chain_err(|| format!("failed to parse key `{}`", key))?;
-                        Ok((key, value))
+                    .map(|(k, value)| {
+                        key.push(k.clone());
+                        let value = CV::from_toml_at(def.clone(), value, key)?;
+                        key.pop();
+                        Ok((k, value))
                     })
                     .collect::<CargoResult<_>>()?,
                 def,
@@ -1615,7 +1768,8 @@ pub fn save_credentials(cfg: &Config, token: String, registry: Option<String>) -
         )
     })?;
 
-    let mut toml = cargo_toml::parse(&contents, file.path(), cfg)?;
+    let mut toml = cargo_toml::parse(&contents, file.path(), cfg)
+        .map_err(|e| ConfigFileError::new(e, file.path().to_path_buf()))?;
 
     // Move the old token location to the new one.
     if let Some(token) = toml.as_table_mut().unwrap().remove("token") {
@@ -1738,6 +1892,8 @@ pub struct CargoBuildConfig {
     pub rustc: Option<PathBuf>,
     pub rustdoc: Option<PathBuf>,
     pub out_dir: Option<ConfigRelativePath>,
+    pub path_dependency_escape: Option<String>,
+    pub script_output_limit: Option<u64>,
 }
 
 /// A type to deserialize a list of strings from a toml file.
@@ -76,7 +76,7 @@ pub(super) fn load_target_triple(config: &Config, triple: &str) -> CargoResult<T
     let linker: OptValue<ConfigRelativePath> = config.get(&format!("target.{}.linker", triple))?;
     // Links do not support environment variables.
     let target_key = ConfigKey::from_str(&format!("target.{}", triple));
-    let links_overrides = match config.get_table(&target_key)? {
+    let links_overrides = match config._get_table(&target_key)? {
         Some(links) => parse_links_overrides(&target_key, links.val)?,
         None => BTreeMap::new(),
     };
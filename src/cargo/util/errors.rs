@@ -1,12 +1,17 @@
 #![allow(unknown_lints)]
 
-use crate::core::{TargetKind, Workspace};
+use crate::core::compiler::CompileKind;
+use crate::core::{InternedString, PackageId, Shell, SourceId, TargetKind, Workspace};
 use crate::ops::CompileOptions;
+use crate::util::lev_distance;
 use anyhow::Error;
+use std::collections::HashMap;
 use std::fmt;
-use std::path::PathBuf;
+use std::io::Read;
+use std::path::{Path, PathBuf};
 use std::process::{ExitStatus, Output};
-use std::str;
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
 
 pub type CargoResult<T> = anyhow::Result<T>;
 
@@ -16,6 +21,25 @@ pub trait CargoResultExt<T, E> {
     where
         F: FnOnce() -> D,
         D: fmt::Display + Send + Sync + 'static;
+
+    /// Records that an error was raised during `phase`, `start.elapsed()`
+    /// into the run. See `PhaseError`.
+    fn phase(self, phase: &'static str, start: Instant) -> CargoResult<T>;
+
+    /// Marks the error as worth retrying or not, overriding the heuristics
+    /// of `network::with_retry`. See `RetryableError`.
+    fn retryable(self, retryable: bool) -> CargoResult<T>;
+
+    /// Displays the error as a warning instead of failing, for operations
+    /// such as optional tools whose failure shouldn't stop cargo.
+    ///
+    /// Returns `None` if there was an error.
+    fn demote_to_warning(self, warning: &str, shell: &mut Shell) -> Option<T>;
+
+    /// Warns with the error, and its causes on the same line, and returns
+    /// `default` instead of failing, for optional lookups which shouldn't
+    /// stop cargo.
+    fn unwrap_or_warn(self, shell: &mut Shell, default: T) -> T;
 }
 
 impl<T, E> CargoResultExt<T, E> for Result<T, E>
@@ -29,6 +53,34 @@ where
     {
         self.map_err(|e| e.into().context(f()))
     }
+
+    fn phase(self, phase: &'static str, start: Instant) -> CargoResult<T> {
+        self.map_err(|e| PhaseError::new(e.into(), phase, start.elapsed()).into())
+    }
+
+    fn retryable(self, retryable: bool) -> CargoResult<T> {
+        self.map_err(|e| RetryableError::new(e.into(), retryable).into())
+    }
+
+    fn demote_to_warning(self, warning: &str, shell: &mut Shell) -> Option<T> {
+        match self {
+            Ok(val) => Some(val),
+            Err(e) => {
+                crate::display_warning_with_error(warning, &e.into(), shell);
+                None
+            }
+        }
+    }
+
+    fn unwrap_or_warn(self, shell: &mut Shell, default: T) -> T {
+        match self {
+            Ok(val) => val,
+            Err(e) => {
+                drop(shell.warn(format!("{:#}", e.into())));
+                default
+            }
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -121,15 +173,210 @@ impl fmt::Display for InternalError {
     }
 }
 
+// =============================================================================
+// Phase error
+
+/// An error wrapper recording which phase of the build (such as "resolve")
+/// was running when the error was raised, and how long cargo had been running
+/// at that point.
+///
+/// The wrapped error is its source, and its own message is just the phase and
+/// elapsed time, which `display_error` appends to the wrapped message.
+pub struct PhaseError {
+    inner: Error,
+    phase: &'static str,
+    elapsed: Duration,
+}
+
+impl PhaseError {
+    pub fn new(inner: Error, phase: &'static str, elapsed: Duration) -> PhaseError {
+        PhaseError {
+            inner,
+            phase,
+            elapsed,
+        }
+    }
+
+    pub fn phase(&self) -> &'static str {
+        self.phase
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+}
+
+impl std::error::Error for PhaseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(self.inner.as_ref())
+    }
+}
+
+impl fmt::Debug for PhaseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.inner.fmt(f)
+    }
+}
+
+impl fmt::Display for PhaseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "during {}, +{}",
+            self.phase,
+            crate::util::elapsed(self.elapsed)
+        )
+    }
+}
+
+// =============================================================================
+// Coded error
+
+/// An error wrapper attaching a stable numeric code to an error, so scripts
+/// can dispatch on specific failures without matching on messages.
+///
+/// Like `PhaseError`, this adds no displayable info of its own. Errors
+/// without a code report `None` from `error_code`.
+pub struct CodedError {
+    inner: Error,
+    code: u32,
+}
+
+impl CodedError {
+    pub fn new(inner: Error, code: u32) -> CodedError {
+        CodedError { inner, code }
+    }
+
+    pub fn code(&self) -> u32 {
+        self.code
+    }
+}
+
+impl std::error::Error for CodedError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.inner.source()
+    }
+}
+
+impl fmt::Debug for CodedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.inner.fmt(f)
+    }
+}
+
+impl fmt::Display for CodedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.inner.fmt(f)
+    }
+}
+
+// =============================================================================
+// Retryable error
+
+/// An error wrapper recording whether the code raising the error knows that
+/// retrying the operation may, or can't, succeed.
+///
+/// Like `CodedError`, this adds no displayable info of its own. Errors
+/// without the wrapper report `None` from `is_retryable`, leaving the
+/// decision to the heuristics of the retry helper.
+pub struct RetryableError {
+    inner: Error,
+    retryable: bool,
+}
+
+impl RetryableError {
+    pub fn new(inner: Error, retryable: bool) -> RetryableError {
+        RetryableError { inner, retryable }
+    }
+
+    pub fn retryable(&self) -> bool {
+        self.retryable
+    }
+}
+
+impl std::error::Error for RetryableError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.inner.source()
+    }
+}
+
+impl fmt::Debug for RetryableError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.inner.fmt(f)
+    }
+}
+
+impl fmt::Display for RetryableError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.inner.fmt(f)
+    }
+}
+
+// =============================================================================
+// Multi error
+
+/// Several independent errors reported at once, such as the failures of each
+/// check run by `validate_all`.
+///
+/// A single error is displayed as if it wasn't wrapped. Otherwise every error
+/// is listed with its causes on the same line.
+pub struct MultiError {
+    errors: Vec<Error>,
+}
+
+impl MultiError {
+    pub fn new(errors: Vec<Error>) -> MultiError {
+        assert!(!errors.is_empty(), "a MultiError needs at least one error");
+        MultiError { errors }
+    }
+
+    pub fn errors(&self) -> &[Error] {
+        &self.errors
+    }
+}
+
+impl std::error::Error for MultiError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self.errors.as_slice() {
+            [error] => error.source(),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Debug for MultiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(&self.errors).finish()
+    }
+}
+
+impl fmt::Display for MultiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let [error] = self.errors.as_slice() {
+            // Not `error.fmt(f)`, its causes are shown through `source`.
+            return write!(f, "{}", error);
+        }
+        write!(f, "{} errors occurred:", self.errors.len())?;
+        for error in &self.errors {
+            let error = format!("{:#}", error).replace('\n', "\n      ");
+            write!(f, "\n    {}", error)?;
+        }
+        Ok(())
+    }
+}
+
 // =============================================================================
 // Manifest error
 
 /// Error wrapper related to a particular manifest and providing it's path.
 ///
-/// This error adds no displayable info of it's own.
+/// This error adds no displayable info of it's own, except for an optional
+/// snippet of the manifest around the position of the error, which is shown
+/// after the error's causes.
 pub struct ManifestError {
     cause: Error,
     manifest: PathBuf,
+    snippet: Option<String>,
 }
 
 impl ManifestError {
@@ -137,13 +384,25 @@ impl ManifestError {
         Self {
             cause: cause.into(),
             manifest,
+            snippet: None,
         }
     }
 
+    /// Attaches a snippet of the manifest's source, rendered with line
+    /// numbers and a caret pointing at the error.
+    pub fn with_snippet(mut self, snippet: Option<String>) -> Self {
+        self.snippet = snippet;
+        self
+    }
+
     pub fn manifest_path(&self) -> &PathBuf {
         &self.manifest
     }
 
+    pub fn snippet(&self) -> Option<&str> {
+        self.snippet.as_deref()
+    }
+
     /// Returns an iterator over the `ManifestError` chain of causes.
     ///
     /// So if this error was not caused by another `ManifestError` this will be empty.
@@ -187,147 +446,2069 @@ impl<'a> Iterator for ManifestCauses<'a> {
 impl<'a> ::std::iter::FusedIterator for ManifestCauses<'a> {}
 
 // =============================================================================
-// Process errors
+// Unknown key warning
+
+/// A key in a manifest which Cargo doesn't know about and ignores.
+///
+/// Unknown keys are not an error so that manifests written for newer versions
+/// of Cargo (or crates.io) keep working, but they are reported as warnings
+/// since they are most likely typos.
 #[derive(Debug)]
-pub struct ProcessError {
-    /// A detailed description to show to the user why the process failed.
-    pub desc: String,
-    /// The exit status of the process.
-    ///
-    /// This can be `None` if the process failed to launch (like process not found).
-    pub exit: Option<ExitStatus>,
-    /// The output from the process.
-    ///
-    /// This can be `None` if the process failed to launch, or the output was not captured.
-    pub output: Option<Output>,
+pub struct UnknownKeyWarning {
+    /// The full dotted path to the key, such as `badges.travis-ci.brnach`.
+    key: String,
 }
 
-impl fmt::Display for ProcessError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        self.desc.fmt(f)
+impl UnknownKeyWarning {
+    pub fn new(key: String) -> Self {
+        UnknownKeyWarning { key }
     }
 }
 
-impl std::error::Error for ProcessError {}
+impl fmt::Display for UnknownKeyWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unused manifest key: {}", self.key)
+    }
+}
 
 // =============================================================================
-// Cargo test errors.
+// Config file error
 
-/// Error when testcases fail
+/// Error loading a particular config file, providing its path and, if the
+/// problem is with a specific value, the key the value was found under.
+///
+/// This is the config counterpart of `ManifestError`, so that problems with
+/// `.cargo/config` files are not mistaken for problems with a manifest.
 #[derive(Debug)]
-pub struct CargoTestError {
-    pub test: Test,
-    pub desc: String,
-    pub exit: Option<ExitStatus>,
-    pub causes: Vec<ProcessError>,
+pub struct ConfigFileError {
+    cause: Error,
+    path: PathBuf,
+    key: Option<String>,
 }
 
-impl fmt::Display for CargoTestError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        self.desc.fmt(f)
+impl ConfigFileError {
+    pub fn new<E: Into<Error>>(cause: E, path: PathBuf) -> Self {
+        Self {
+            cause: cause.into(),
+            path,
+            key: None,
+        }
     }
-}
 
-impl std::error::Error for CargoTestError {}
+    /// Attributes the error to the value at the dotted key path `key`.
+    pub fn with_key(mut self, key: String) -> Self {
+        self.key = Some(key);
+        self
+    }
 
-#[derive(Debug)]
-pub enum Test {
-    Multiple,
-    Doc,
-    UnitTest {
-        kind: TargetKind,
-        name: String,
-        pkg_name: String,
-    },
-}
+    pub fn config_path(&self) -> &Path {
+        &self.path
+    }
 
-impl CargoTestError {
-    pub fn new(test: Test, errors: Vec<ProcessError>) -> Self {
-        if errors.is_empty() {
-            panic!("Cannot create CargoTestError from empty Vec")
-        }
-        let desc = errors
-            .iter()
-            .map(|error| error.desc.clone())
-            .collect::<Vec<String>>()
-            .join("\n");
-        CargoTestError {
-            test,
-            desc,
-            exit: errors[0].exit,
-            causes: errors,
-        }
+    pub fn key(&self) -> Option<&str> {
+        self.key.as_deref()
     }
+}
 
-    pub fn hint(&self, ws: &Workspace<'_>, opts: &CompileOptions) -> String {
-        match self.test {
-            Test::UnitTest {
-                ref kind,
-                ref name,
-                ref pkg_name,
-            } => {
-                let pkg_info = if opts.spec.needs_spec_flag(ws) {
-                    format!("-p {} ", pkg_name)
-                } else {
-                    String::new()
-                };
+impl std::error::Error for ConfigFileError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(self.cause.as_ref())
+    }
+}
 
-                match *kind {
-                    TargetKind::Bench => {
-                        format!("test failed, to rerun pass '{}--bench {}'", pkg_info, name)
-                    }
-                    TargetKind::Bin => {
-                        format!("test failed, to rerun pass '{}--bin {}'", pkg_info, name)
-                    }
-                    TargetKind::Lib(_) => format!("test failed, to rerun pass '{}--lib'", pkg_info),
-                    TargetKind::Test => {
-                        format!("test failed, to rerun pass '{}--test {}'", pkg_info, name)
-                    }
-                    TargetKind::ExampleBin | TargetKind::ExampleLib(_) => {
-                        format!("test failed, to rerun pass '{}--example {}", pkg_info, name)
-                    }
-                    _ => "test failed.".into(),
-                }
-            }
-            Test::Doc => "test failed, to rerun pass '--doc'".into(),
-            _ => "test failed.".into(),
+impl fmt::Display for ConfigFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.key {
+            Some(key) => write!(
+                f,
+                "invalid value for key `{}` in configuration file `{}`",
+                key,
+                self.path.display()
+            ),
+            None => write!(
+                f,
+                "could not load configuration file `{}`",
+                self.path.display()
+            ),
         }
     }
 }
 
 // =============================================================================
-// CLI errors
-
-pub type CliResult = Result<(), CliError>;
+// Env config error
 
+/// Error for a config value set through an environment variable, such as
+/// `CARGO_BUILD_JOBS`, which can't be converted to the type of the key.
 #[derive(Debug)]
-/// The CLI error is the error type used at Cargo's CLI-layer.
-///
-/// All errors from the lib side of Cargo will get wrapped with this error.
-/// Other errors (such as command-line argument validation) will create this
-/// directly.
-pub struct CliError {
-    /// The error to display. This can be `None` in rare cases to exit with a
-    /// code without displaying a message. For example `cargo run -q` where
-    /// the resulting process exits with a nonzero code (on Windows), or an
-    /// external subcommand that exits nonzero (we assume it printed its own
-    /// message).
-    pub error: Option<anyhow::Error>,
-    /// The process exit code.
-    pub exit_code: i32,
+pub struct EnvConfigError {
+    /// The name of the environment variable.
+    pub var: String,
+    /// The dotted config key the variable sets.
+    pub key: String,
+    /// The value of the variable.
+    pub value: String,
+    /// A description of the expected format, such as `an integer`.
+    pub expected: String,
 }
 
-impl CliError {
-    pub fn new(error: anyhow::Error, code: i32) -> CliError {
-        CliError {
-            error: Some(error),
-            exit_code: code,
+impl EnvConfigError {
+    pub fn new(var: &str, key: &str, value: &str, expected: &str) -> EnvConfigError {
+        EnvConfigError {
+            var: var.to_string(),
+            key: key.to_string(),
+            value: value.to_string(),
+            expected: expected.to_string(),
         }
     }
+}
 
-    pub fn code(code: i32) -> CliError {
-        CliError {
-            error: None,
+impl fmt::Display for EnvConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "error in environment variable `{}`: `{}` expected {}, but found `{}`",
+            self.var, self.key, self.expected, self.value
+        )
+    }
+}
+
+impl std::error::Error for EnvConfigError {}
+
+// =============================================================================
+// Target table error
+
+/// Error for an explicitly declared target table (such as `[[bin]]` or
+/// `[[example]]`) that cannot be turned into a target.
+#[derive(Debug)]
+pub struct TargetTableError {
+    /// The kind of table, such as `bin` or `example`.
+    pub kind: String,
+    /// The zero-based position of the table within its array of tables.
+    pub index: usize,
+    /// Why the table was rejected.
+    pub reason: String,
+}
+
+impl TargetTableError {
+    pub fn new(kind: &str, index: usize, reason: impl Into<String>) -> TargetTableError {
+        TargetTableError {
+            kind: kind.to_string(),
+            index,
+            reason: reason.into(),
+        }
+    }
+}
+
+impl fmt::Display for TargetTableError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid `[[{kind}]]` table `{kind}[{index}]`: {reason}",
+            kind = self.kind,
+            index = self.index,
+            reason = self.reason
+        )
+    }
+}
+
+impl std::error::Error for TargetTableError {}
+
+// =============================================================================
+// Crate type error
+
+/// Error for a target which is a `proc-macro`, through `crate-type` or
+/// `proc-macro = true`, and has other crate types as well. rustc can't build
+/// a proc-macro crate as anything else.
+#[derive(Debug)]
+pub struct CrateTypeError {
+    /// The target, like ``library `foo` ``.
+    pub target: String,
+    /// The crate types other than `proc-macro`.
+    pub others: Vec<String>,
+}
+
+impl CrateTypeError {
+    pub fn new(target: String, others: Vec<String>) -> CrateTypeError {
+        CrateTypeError { target, others }
+    }
+}
+
+impl fmt::Display for CrateTypeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let others: Vec<_> = self.others.iter().map(|t| format!("`{}`", t)).collect();
+        write!(
+            f,
+            "{} has the `proc-macro` crate type, which can't be mixed with {}\n\
+             A proc-macro crate can only export procedural macros. Move the rest of \
+             the code into a separate library package.",
+            self.target,
+            others.join(", ")
+        )
+    }
+}
+
+impl std::error::Error for CrateTypeError {}
+
+// =============================================================================
+// Duplicate feature error
+
+/// Error for a `[features]` table which defines the same feature more than
+/// once, such as an index entry with a repeated key.
+///
+/// Rejecting these avoids silently keeping whichever definition came last.
+#[derive(Debug)]
+pub struct DuplicateFeatureError {
+    feature: String,
+}
+
+impl DuplicateFeatureError {
+    pub fn new(feature: impl Into<String>) -> DuplicateFeatureError {
+        DuplicateFeatureError {
+            feature: feature.into(),
+        }
+    }
+
+    pub fn feature(&self) -> &str {
+        &self.feature
+    }
+}
+
+impl fmt::Display for DuplicateFeatureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "feature `{}` is defined more than once", self.feature)
+    }
+}
+
+impl std::error::Error for DuplicateFeatureError {}
+
+// =============================================================================
+// Cross-package feature error
+
+/// Error for `--features` naming `member/feature` for workspace members other
+/// than the current package, which is the only one `--features` applies to
+/// without `-Z package-features`.
+#[derive(Debug)]
+pub struct CrossPackageFeatureError {
+    /// The name of the current package.
+    pub current: String,
+    /// The `member/feature` values naming other members.
+    pub features: Vec<String>,
+}
+
+impl CrossPackageFeatureError {
+    pub fn new(current: &str, features: Vec<String>) -> CrossPackageFeatureError {
+        CrossPackageFeatureError {
+            current: current.to_string(),
+            features,
+        }
+    }
+}
+
+impl fmt::Display for CrossPackageFeatureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let features: Vec<_> = self.features.iter().map(|f| format!("`{}`", f)).collect();
+        write!(
+            f,
+            "`--features` only applies to the current package `{}`, \
+             but {} select features of other workspace members\n\
+             Run the command from the directory of each member, or pass \
+             `-Z package-features` to select features of several members at once.",
+            self.current,
+            features.join(", ")
+        )
+    }
+}
+
+impl std::error::Error for CrossPackageFeatureError {}
+
+// =============================================================================
+// Links conflict error
+
+/// Error for two packages in one build declaring the same `links` value, as a
+/// native library can be linked only once.
+#[derive(Debug)]
+pub struct LinksConflictError {
+    links: String,
+    /// The package which first claimed `links`, followed by the packages
+    /// depending on it up to the root.
+    previous: Vec<PackageId>,
+    /// Same as `previous`, for the package which also claimed `links`.
+    package: Vec<PackageId>,
+}
+
+impl LinksConflictError {
+    pub fn new(links: &str, previous: Vec<PackageId>, package: Vec<PackageId>) -> Self {
+        LinksConflictError {
+            links: links.to_string(),
+            previous,
+            package,
+        }
+    }
+
+    pub fn links(&self) -> &str {
+        &self.links
+    }
+
+    /// The two packages linking to the native library.
+    pub fn packages(&self) -> (PackageId, PackageId) {
+        (self.previous[0], self.package[0])
+    }
+}
+
+impl fmt::Display for LinksConflictError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "multiple packages link to native library `{}`, \
+             but a native library can be linked only once\n\n",
+            self.links
+        )?;
+        describe_path(f, &self.previous)?;
+        write!(f, "\nlinks to native library `{}`\n\n", self.links)?;
+        describe_path(f, &self.package)?;
+        write!(f, "\nalso links to native library `{}`", self.links)
+    }
+}
+
+impl std::error::Error for LinksConflictError {}
+
+/// Writes `path`, a package followed by the packages depending on it up to
+/// the root, with one package per line.
+fn describe_path(f: &mut fmt::Formatter<'_>, path: &[PackageId]) -> fmt::Result {
+    write!(f, "package `{}`", path[0])?;
+    for dep in path.iter().skip(1) {
+        write!(f, "\n    ... which is depended on by `{}`", dep)?;
+    }
+    Ok(())
+}
+
+// =============================================================================
+// Multiple versions error
+
+/// Error for several versions of one package in a build, when the package
+/// can't be duplicated because of its `links` value.
+///
+/// This is the common case of `LinksConflictError`, when a package's
+/// versions disagree only on the version of a `-sys` crate.
+#[derive(Debug)]
+pub struct MultipleVersionsError {
+    links: String,
+    /// For each version, the package followed by the packages depending on
+    /// it up to the root.
+    paths: Vec<Vec<PackageId>>,
+}
+
+impl MultipleVersionsError {
+    pub fn new(links: &str, paths: Vec<Vec<PackageId>>) -> Self {
+        MultipleVersionsError {
+            links: links.to_string(),
+            paths,
+        }
+    }
+
+    /// The name of the duplicated package.
+    pub fn name(&self) -> InternedString {
+        self.paths[0][0].name()
+    }
+
+    /// The selected versions of the package.
+    pub fn versions(&self) -> Vec<&semver::Version> {
+        self.paths.iter().map(|path| path[0].version()).collect()
+    }
+}
+
+impl fmt::Display for MultipleVersionsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "multiple versions of package `{}` were selected, but only one can be built \
+             because it links to native library `{}`",
+            self.name(),
+            self.links
+        )?;
+        for path in &self.paths {
+            write!(f, "\n\npackage `{}`", path[0])?;
+            for dep in path.iter().skip(1) {
+                write!(f, "\n    ... which is depended on by `{}`", dep)?;
+            }
+        }
+        write!(
+            f,
+            "\n\nUpdate the dependencies so that they require the same version of `{}`.",
+            self.name()
+        )
+    }
+}
+
+impl std::error::Error for MultipleVersionsError {}
+
+// =============================================================================
+// Target kind error
+
+/// Error for a unit planned to be built for the wrong platform, such as a
+/// build dependency or procedural macro built for the target when it must
+/// run on the host.
+#[derive(Debug)]
+pub struct TargetKindError {
+    package: PackageId,
+    /// Why the package must be built for `expected`, such as "a build
+    /// dependency of `foo v0.1.0`".
+    role: String,
+    expected: CompileKind,
+    selected: CompileKind,
+}
+
+impl TargetKindError {
+    pub fn new(
+        package: PackageId,
+        role: impl Into<String>,
+        expected: CompileKind,
+        selected: CompileKind,
+    ) -> TargetKindError {
+        TargetKindError {
+            package,
+            role: role.into(),
+            expected,
+            selected,
+        }
+    }
+
+    pub fn package(&self) -> PackageId {
+        self.package
+    }
+
+    pub fn expected(&self) -> CompileKind {
+        self.expected
+    }
+
+    pub fn selected(&self) -> CompileKind {
+        self.selected
+    }
+}
+
+impl fmt::Display for TargetKindError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let describe = |kind: CompileKind| match kind {
+            CompileKind::Host => "the host".to_string(),
+            CompileKind::Target(target) => format!("target `{}`", target.short_name()),
+        };
+        write!(
+            f,
+            "package `{}` is {} and must be built for {}, but was selected to be built for {}",
+            self.package,
+            self.role,
+            describe(self.expected),
+            describe(self.selected)
+        )
+    }
+}
+
+impl std::error::Error for TargetKindError {}
+
+// =============================================================================
+// Ambiguous target error
+
+/// Error for `cargo run` finding several binaries to run without having been
+/// told which one to pick.
+#[derive(Debug)]
+pub struct AmbiguousTargetError {
+    candidates: Vec<String>,
+}
+
+impl AmbiguousTargetError {
+    pub fn new(candidates: Vec<String>) -> AmbiguousTargetError {
+        AmbiguousTargetError { candidates }
+    }
+
+    /// The names of the binaries which could have been run.
+    pub fn candidates(&self) -> &[String] {
+        &self.candidates
+    }
+}
+
+impl fmt::Display for AmbiguousTargetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "`cargo run` could not determine which binary to run. \
+             Use the `--bin` option to specify a binary, \
+             or the `default-run` manifest key.\n\
+             available binaries: {}",
+            self.candidates.join(", ")
+        )
+    }
+}
+
+impl std::error::Error for AmbiguousTargetError {}
+
+// =============================================================================
+// Crate name mismatch error
+
+/// Error for a library whose root source file declares a `#![crate_name]`
+/// that differs from the crate name cargo derives from the manifest.
+#[derive(Debug)]
+pub struct CrateNameMismatchError {
+    /// The library's name in the manifest, which is the package name unless
+    /// `[lib] name` is set.
+    pub manifest_name: String,
+    /// The name cargo passes to rustc, with hyphens replaced by underscores.
+    pub crate_name: String,
+    /// The name declared with `#![crate_name]`.
+    pub declared: String,
+    /// The library's root source file.
+    pub path: PathBuf,
+}
+
+impl fmt::Display for CrateNameMismatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "`{}` declares `#![crate_name = \"{}\"]`, but the library is compiled as `{}`",
+            self.path.display(),
+            self.declared,
+            self.crate_name
+        )?;
+        if self.manifest_name != self.crate_name {
+            write!(
+                f,
+                "\n\nThe crate name is the library name `{}` from `Cargo.toml` \
+                 with hyphens replaced by underscores.",
+                self.manifest_name
+            )?;
+        }
+        write!(
+            f,
+            "\nRemove the attribute, or set `name = \"{}\"` in the `[lib]` table \
+             of `Cargo.toml`.",
+            self.declared
+        )
+    }
+}
+
+impl std::error::Error for CrateNameMismatchError {}
+
+// =============================================================================
+// Package name error
+
+/// Error for a package name (or a name with the same restrictions, like a
+/// registry name) that cannot be used.
+#[derive(Debug)]
+pub struct PackageNameError {
+    /// The rejected name.
+    pub name: String,
+    /// What the name is used as, such as `package name` or `crate name`.
+    pub what: String,
+    /// The rule the name breaks.
+    pub rule: PackageNameRule,
+    /// A valid name close to the rejected one, if one could be found.
+    pub suggestion: Option<String>,
+    /// Extra text appended to the message, such as how to pick another name.
+    pub help: String,
+}
+
+/// The rules checked by `restricted_names` and `cargo new`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PackageNameRule {
+    Empty,
+    StartsWithDigit,
+    InvalidStartChar(char),
+    InvalidChar(char),
+    Keyword,
+    BuildDirectoryName,
+    TestLibrary,
+    WindowsReserved,
+}
+
+impl PackageNameError {
+    pub fn new(name: &str, what: &str, rule: PackageNameRule) -> PackageNameError {
+        PackageNameError {
+            name: name.to_string(),
+            what: what.to_string(),
+            rule,
+            suggestion: None,
+            help: String::new(),
+        }
+    }
+
+    pub fn with_suggestion(mut self, suggestion: Option<String>) -> PackageNameError {
+        self.suggestion = suggestion;
+        self
+    }
+
+    pub fn with_help(mut self, help: &str) -> PackageNameError {
+        self.help = help.to_string();
+        self
+    }
+}
+
+impl fmt::Display for PackageNameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (name, what) = (&self.name, &self.what);
+        match self.rule {
+            PackageNameRule::Empty => write!(f, "{} cannot be an empty string", what)?,
+            PackageNameRule::StartsWithDigit => write!(
+                f,
+                "the name `{}` cannot be used as a {}, the name cannot start with a digit",
+                name, what
+            )?,
+            PackageNameRule::InvalidStartChar(ch) => write!(
+                f,
+                "invalid character `{}` in {}: `{}`, \
+                 the first character must be a Unicode XID start character \
+                 (most letters or `_`)",
+                ch, what, name
+            )?,
+            PackageNameRule::InvalidChar(ch) => write!(
+                f,
+                "invalid character `{}` in {}: `{}`, \
+                 characters must be Unicode XID characters \
+                 (numbers, `-`, `_`, or most letters)",
+                ch, what, name
+            )?,
+            PackageNameRule::Keyword => write!(
+                f,
+                "the name `{}` cannot be used as a {}, it is a Rust keyword",
+                name, what
+            )?,
+            PackageNameRule::BuildDirectoryName => write!(
+                f,
+                "the name `{}` cannot be used as a {}, \
+                 it conflicts with cargo's build directory names",
+                name, what
+            )?,
+            PackageNameRule::TestLibrary => write!(
+                f,
+                "the name `{}` cannot be used as a {}, \
+                 it conflicts with Rust's built-in test library",
+                name, what
+            )?,
+            PackageNameRule::WindowsReserved => write!(
+                f,
+                "cannot use name `{}`, it is a reserved Windows filename",
+                name
+            )?,
+        }
+        if let Some(suggestion) = &self.suggestion {
+            write!(f, "\nhelp: a valid name would be `{}`", suggestion)?;
+        }
+        write!(f, "{}", self.help)
+    }
+}
+
+impl std::error::Error for PackageNameError {}
+
+// =============================================================================
+// Not found error
+
+/// Error for a named item (a subcommand, feature, target, registry, ...) that
+/// does not exist, along with the names that do.
+#[derive(Debug)]
+pub struct NotFoundError {
+    /// What kind of thing was looked up, such as `bin target` or `feature`.
+    pub what: String,
+    /// The name that was requested.
+    pub name: String,
+    /// The names that would have been accepted.
+    pub candidates: Vec<String>,
+}
+
+impl NotFoundError {
+    pub fn new(
+        what: impl Into<String>,
+        name: impl Into<String>,
+        candidates: impl IntoIterator<Item = impl Into<String>>,
+    ) -> NotFoundError {
+        NotFoundError {
+            what: what.into(),
+            name: name.into(),
+            candidates: candidates.into_iter().map(|c| c.into()).collect(),
+        }
+    }
+
+    /// The candidate closest to the requested name, if any is close enough
+    /// to be worth suggesting.
+    pub fn closest(&self) -> Option<&str> {
+        lev_distance::closest(&self.name, self.candidates.iter(), |c| c).map(|c| c.as_str())
+    }
+
+    /// A listing of every candidate, displayed after the headline.
+    pub fn detail(&self) -> String {
+        if self.candidates.is_empty() {
+            return format!("there are no {}s available", self.what);
+        }
+        let mut detail = format!("available {}s:", self.what);
+        for candidate in &self.candidates {
+            detail.push_str("\n    ");
+            detail.push_str(candidate);
+        }
+        detail
+    }
+}
+
+impl fmt::Display for NotFoundError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "no {} named `{}`", self.what, self.name)?;
+        if let Some(closest) = self.closest() {
+            write!(f, "\n\n\tDid you mean `{}`?", closest)?;
+        }
+        write!(f, "\n\n{}", self.detail())
+    }
+}
+
+impl std::error::Error for NotFoundError {}
+
+// =============================================================================
+// Source unavailable error
+
+/// Error for a dependency whose source can only be reached over the network
+/// while cargo is running with `--offline`.
+#[derive(Debug)]
+pub struct SourceUnavailableError {
+    /// The name of the dependency.
+    pub dependency: String,
+    /// The kind of source, such as `git`.
+    pub kind: &'static str,
+    /// The URL of the source.
+    pub url: String,
+}
+
+impl SourceUnavailableError {
+    pub fn new(dependency: &str, kind: &'static str, url: &str) -> SourceUnavailableError {
+        SourceUnavailableError {
+            dependency: dependency.to_string(),
+            kind,
+            url: url.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for SourceUnavailableError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "dependency `{}` requires the {} source `{}`, which has not been \
+             downloaded before and cannot be fetched in the offline mode (--offline)",
+            self.dependency, self.kind, self.url
+        )
+    }
+}
+
+impl std::error::Error for SourceUnavailableError {}
+
+// =============================================================================
+// Git ref error
+
+/// Error for a git dependency whose branch, tag or revision can't be found in
+/// its repository.
+#[derive(Debug)]
+pub struct GitRefError {
+    /// The URL of the repository.
+    pub url: String,
+    /// The kind of reference, such as `branch`.
+    pub kind: &'static str,
+    /// The name of the branch or tag, or the revision.
+    pub reference: String,
+}
+
+impl GitRefError {
+    pub fn new(url: &str, kind: &'static str, reference: &str) -> GitRefError {
+        GitRefError {
+            url: url.to_string(),
+            kind,
+            reference: reference.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for GitRefError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} `{}` does not exist in git repository `{}`\n\
+             It may have been deleted from the repository, or be mistyped.",
+            self.kind, self.reference, self.url
+        )
+    }
+}
+
+impl std::error::Error for GitRefError {}
+
+// =============================================================================
+// Vendor error
+
+/// Error for a package pinned by the lock file which is missing from the
+/// vendor directory of a directory source.
+#[derive(Debug)]
+pub struct VendorError {
+    /// The name of the package.
+    pub package: String,
+    /// The version of the package.
+    pub version: String,
+    /// The vendor directory which was searched.
+    pub root: PathBuf,
+}
+
+impl VendorError {
+    pub fn new(package: &str, version: &str, root: &Path) -> VendorError {
+        VendorError {
+            package: package.to_string(),
+            version: version.to_string(),
+            root: root.to_path_buf(),
+        }
+    }
+}
+
+impl fmt::Display for VendorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "package `{} v{}` is missing from the vendor directory `{}`\n\
+             The directory may be out of date with the lock file. Run `cargo vendor` \
+             again to re-vendor the dependencies.",
+            self.package,
+            self.version,
+            self.root.display()
+        )
+    }
+}
+
+impl std::error::Error for VendorError {}
+
+// =============================================================================
+// Git dirty cache error
+
+/// Error for a checkout in the git cache whose files were modified locally,
+/// which checking it out again would overwrite.
+#[derive(Debug)]
+pub struct GitDirtyCacheError {
+    /// The path of the checkout.
+    pub path: PathBuf,
+    /// The modified files, relative to the checkout.
+    pub files: Vec<String>,
+}
+
+impl GitDirtyCacheError {
+    pub fn new(path: &Path, files: Vec<String>) -> GitDirtyCacheError {
+        GitDirtyCacheError {
+            path: path.to_path_buf(),
+            files,
+        }
+    }
+}
+
+impl fmt::Display for GitDirtyCacheError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "the git checkout `{}` has local modifications, which checking it out \
+             again would overwrite:",
+            self.path.display()
+        )?;
+        for file in &self.files {
+            write!(f, "\n    {}", file)?;
+        }
+        write!(
+            f,
+            "\nMove away any changes to keep, then remove the directory so that \
+             Cargo checks it out again."
+        )
+    }
+}
+
+impl std::error::Error for GitDirtyCacheError {}
+
+// =============================================================================
+// Excluded package error
+
+/// Error for a package whose `package.workspace` key points to a workspace
+/// which lists it in `workspace.exclude`.
+#[derive(Debug)]
+pub struct ExcludedPackageError {
+    /// The manifest of the excluded package.
+    pub manifest: PathBuf,
+    /// The manifest of the workspace root.
+    pub root: PathBuf,
+    /// The entry of `workspace.exclude` matching the package.
+    pub entry: String,
+}
+
+impl ExcludedPackageError {
+    pub fn new(manifest: &Path, root: &Path, entry: &str) -> ExcludedPackageError {
+        ExcludedPackageError {
+            manifest: manifest.to_path_buf(),
+            root: root.to_path_buf(),
+            entry: entry.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for ExcludedPackageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "package `{}` has a `package.workspace` key pointing to the workspace `{}`, \
+             but that workspace excludes it with the `workspace.exclude` entry `{}`\n\
+             Remove the entry to make the package a member of the workspace, or remove \
+             the `package.workspace` key to keep it out of the workspace.",
+            self.manifest.display(),
+            self.root.display(),
+            self.entry
+        )
+    }
+}
+
+impl std::error::Error for ExcludedPackageError {}
+
+// =============================================================================
+// Path escape error
+
+/// Error for a path dependency of a workspace member which points outside of
+/// the workspace root.
+#[derive(Debug)]
+pub struct PathEscapeError {
+    /// The name of the dependency.
+    pub dependency: String,
+    /// The name of the package declaring the dependency.
+    pub package: String,
+    /// The absolute path the dependency resolves to.
+    pub path: PathBuf,
+    /// The root of the workspace.
+    pub root: PathBuf,
+}
+
+impl PathEscapeError {
+    pub fn new(dependency: &str, package: &str, path: &Path, root: &Path) -> PathEscapeError {
+        PathEscapeError {
+            dependency: dependency.to_string(),
+            package: package.to_string(),
+            path: path.to_path_buf(),
+            root: root.to_path_buf(),
+        }
+    }
+}
+
+impl fmt::Display for PathEscapeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "path dependency `{}` of package `{}` resolves to `{}`, \
+             which is outside of the workspace root `{}`\n\
+             It may not exist on other machines. Move it into the workspace, or set \
+             `build.path-dependency-escape` to `allow`.",
+            self.dependency,
+            self.package,
+            self.path.display(),
+            self.root.display()
+        )
+    }
+}
+
+impl std::error::Error for PathEscapeError {}
+
+// =============================================================================
+// Source mismatch error
+
+/// Error for a package which the lock file records from one kind of source,
+/// such as git, while the manifest now asks for it from another kind, such
+/// as a registry.
+#[derive(Debug)]
+pub struct SourceMismatchError {
+    /// The name of the package.
+    pub package: String,
+    /// The kind of source recorded in the lock file.
+    pub locked_kind: &'static str,
+    /// The URL of the source recorded in the lock file.
+    pub locked: String,
+    /// The kind of source the manifest asks for.
+    pub requested_kind: &'static str,
+    /// The URL of the source the manifest asks for.
+    pub requested: String,
+}
+
+impl SourceMismatchError {
+    pub fn new(package: &str, locked: SourceId, requested: SourceId) -> SourceMismatchError {
+        SourceMismatchError {
+            package: package.to_string(),
+            locked_kind: source_kind(locked),
+            locked: locked.url().to_string(),
+            requested_kind: source_kind(requested),
+            requested: requested.url().to_string(),
+        }
+    }
+}
+
+fn source_kind(id: SourceId) -> &'static str {
+    if id.is_git() {
+        "git"
+    } else if id.is_registry() {
+        "registry"
+    } else if id.is_path() {
+        "path"
+    } else {
+        "directory"
+    }
+}
+
+impl fmt::Display for SourceMismatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "the lock file has package `{}` from the {} source `{}`, \
+             but the manifest now requires it from the {} source `{}`\n\
+             Run `cargo update -p {}` to update the lock file.",
+            self.package,
+            self.locked_kind,
+            self.locked,
+            self.requested_kind,
+            self.requested,
+            self.package
+        )
+    }
+}
+
+impl std::error::Error for SourceMismatchError {}
+
+// =============================================================================
+// Missing lock file error
+
+/// Error for a workspace without a lock file when `--locked` or `--frozen`
+/// requires one.
+#[derive(Debug)]
+pub struct MissingLockfileError {
+    /// The path the lock file is expected at.
+    pub path: PathBuf,
+    /// The flag requiring the lock file, such as `--locked`.
+    pub flag: &'static str,
+}
+
+impl MissingLockfileError {
+    pub fn new(path: PathBuf, flag: &'static str) -> MissingLockfileError {
+        MissingLockfileError { path, flag }
+    }
+}
+
+impl fmt::Display for MissingLockfileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "the lock file {} is missing but {} was passed, which requires it to exist\n\
+             Run `cargo generate-lockfile` to create it, and commit it with the sources.",
+            self.path.display(),
+            self.flag
+        )
+    }
+}
+
+impl std::error::Error for MissingLockfileError {}
+
+// =============================================================================
+// Index parse error
+
+/// Error for a line of a registry index file that isn't valid JSON.
+#[derive(Debug)]
+pub struct IndexParseError {
+    /// The name of the package the index file is for.
+    pub package: String,
+    /// The path of the index file, relative to the root of the index.
+    pub path: PathBuf,
+    /// The 1-based line number of the malformed entry.
+    pub line: usize,
+    /// Why the line couldn't be parsed.
+    pub message: String,
+}
+
+impl IndexParseError {
+    pub fn new(package: &str, path: &Path, line: usize, message: String) -> IndexParseError {
+        IndexParseError {
+            package: package.to_string(),
+            path: path.to_path_buf(),
+            line,
+            message,
+        }
+    }
+}
+
+impl fmt::Display for IndexParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "malformed entry for package `{}` in registry index file `{}` on line {}: {}\n\
+             The index may be corrupted. Set `registry.lenient-index = true` to skip \
+             malformed entries.",
+            self.package,
+            self.path.display(),
+            self.line,
+            self.message
+        )
+    }
+}
+
+impl std::error::Error for IndexParseError {}
+
+// =============================================================================
+// Fingerprint error
+
+/// Error for a fingerprint from a previous build that exists but can't be
+/// read. The unit is rebuilt, so this is displayed as a warning.
+#[derive(Debug)]
+pub struct FingerprintError {
+    /// The unit the fingerprint is for, like `foo v0.1.0 (/path/to/foo) lib`.
+    pub unit: String,
+    /// The fingerprint file that couldn't be read.
+    pub path: PathBuf,
+    /// Why the file couldn't be read.
+    pub message: String,
+}
+
+impl FingerprintError {
+    pub fn new(unit: String, path: &Path, message: String) -> FingerprintError {
+        FingerprintError {
+            unit,
+            path: path.to_path_buf(),
+            message,
+        }
+    }
+}
+
+impl fmt::Display for FingerprintError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "failed to read the fingerprint of {} from `{}`: {}\n\
+             It will be rebuilt. If this keeps happening, the target directory may be \
+             corrupted, and `cargo clean` will reset it.",
+            self.unit,
+            self.path.display(),
+            self.message
+        )
+    }
+}
+
+impl std::error::Error for FingerprintError {}
+
+// =============================================================================
+// Profile inheritance error
+
+/// Error for a custom profile whose `inherits` names a profile which isn't
+/// defined.
+#[derive(Debug)]
+pub struct ProfileInheritanceError {
+    /// The name of the profile with the `inherits` setting.
+    pub profile: String,
+    /// The name of the missing parent profile.
+    pub parent: String,
+    /// The defined profile closest to `parent`, if any.
+    pub closest: Option<String>,
+}
+
+impl ProfileInheritanceError {
+    pub fn new<'a>(
+        profile: &str,
+        parent: &str,
+        defined: impl Iterator<Item = &'a str>,
+    ) -> ProfileInheritanceError {
+        ProfileInheritanceError {
+            profile: profile.to_string(),
+            parent: parent.to_string(),
+            closest: lev_distance::closest(parent, defined, |s| *s).map(|s| s.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for ProfileInheritanceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "profile `{}` inherits from `{}`, but that profile is not defined",
+            self.profile, self.parent
+        )?;
+        if let Some(closest) = &self.closest {
+            write!(f, "\n\n\tDid you mean `{}`?", closest)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ProfileInheritanceError {}
+
+// =============================================================================
+// Default feature conflict error
+
+/// Error for a package built with `--no-default-features` whose `default`
+/// feature is enabled anyway, because other packages in the build depend on it
+/// with default features. Features are unified, so this is displayed as a
+/// warning.
+#[derive(Debug)]
+pub struct DefaultFeatureConflictError {
+    /// The name of the package built with `--no-default-features`.
+    pub package: String,
+    /// The packages depending on `package` with default features.
+    pub required_by: Vec<String>,
+}
+
+impl DefaultFeatureConflictError {
+    pub fn new(package: &str, required_by: Vec<String>) -> DefaultFeatureConflictError {
+        DefaultFeatureConflictError {
+            package: package.to_string(),
+            required_by,
+        }
+    }
+}
+
+impl fmt::Display for DefaultFeatureConflictError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "`--no-default-features` has no effect on package `{}`, \
+             its `default` feature is required by:",
+            self.package
+        )?;
+        for parent in &self.required_by {
+            write!(f, "\n  {}", parent)?;
+        }
+        write!(
+            f,
+            "\nAdd `default-features = false` to their dependency on `{}` to build it \
+             without its default features.",
+            self.package
+        )
+    }
+}
+
+impl std::error::Error for DefaultFeatureConflictError {}
+
+// =============================================================================
+// Rust version error
+
+/// Error for a package whose `rust-version` is newer than the version of
+/// rustc building it.
+#[derive(Debug)]
+pub struct RustVersionError {
+    /// The package, like `foo v0.1.0`.
+    pub package: String,
+    /// The `rust-version` of the package.
+    pub required: String,
+    /// The version of rustc, from `rustc -vV`.
+    pub detected: String,
+}
+
+impl RustVersionError {
+    pub fn new(package: PackageId, required: &str, detected: &semver::Version) -> RustVersionError {
+        RustVersionError {
+            package: format!("{} v{}", package.name(), package.version()),
+            required: required.to_string(),
+            detected: detected.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for RustVersionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "package `{}` cannot be built because it requires rustc {} or newer, \
+             while the currently active rustc version is {}\n\
+             Update the toolchain, or use an older version of the package which \
+             supports rustc {}.",
+            self.package, self.required, self.detected, self.detected
+        )
+    }
+}
+
+impl std::error::Error for RustVersionError {}
+
+// =============================================================================
+// Target not installed error
+
+/// Error for a `--target` which the toolchain can't compile for, either
+/// because rustc doesn't know the target at all, or because the standard
+/// library for it isn't installed.
+#[derive(Debug)]
+pub struct TargetNotInstalledError {
+    /// The target triple.
+    pub target: String,
+    /// Whether rustc knows the target, so only its standard library is
+    /// missing.
+    pub supported: bool,
+}
+
+impl TargetNotInstalledError {
+    pub fn new(target: &str, supported: bool) -> TargetNotInstalledError {
+        TargetNotInstalledError {
+            target: target.to_string(),
+            supported,
+        }
+    }
+}
+
+impl fmt::Display for TargetNotInstalledError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.supported {
+            write!(
+                f,
+                "the standard library for target `{0}` is not installed\n\
+                 If the toolchain is managed by rustup, run `rustup target add {0}` to \
+                 install it.",
+                self.target
+            )
+        } else {
+            write!(
+                f,
+                "target `{}` is not supported by the installed rustc\n\
+                 Run `rustc --print target-list` to see the supported targets. If the \
+                 target was added recently, updating the toolchain may help.",
+                self.target
+            )
+        }
+    }
+}
+
+impl std::error::Error for TargetNotInstalledError {}
+
+// =============================================================================
+// Output collision error
+
+/// Error for two compilation units that would write the same output file.
+#[derive(Debug)]
+pub struct OutputCollisionError {
+    /// The unit found to collide, described by its target and package.
+    pub unit: String,
+    /// The unit that already claimed `path`, described the same way.
+    pub other_unit: String,
+    /// The contested output file.
+    pub path: PathBuf,
+}
+
+impl fmt::Display for OutputCollisionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "output filename collision.\n\
+             The {} has the same output filename as the {}.\n\
+             Colliding filename is: {}\n\
+             The targets should have unique names.\n\
+             Consider renaming one of them, for example with the `name` \
+             key of its target table in `Cargo.toml`.",
+            self.unit,
+            self.other_unit,
+            self.path.display()
+        )
+    }
+}
+
+impl std::error::Error for OutputCollisionError {}
+
+// =============================================================================
+// Process errors
+#[derive(Debug)]
+pub struct ProcessError {
+    /// A detailed description to show to the user why the process failed.
+    pub desc: String,
+    /// The exit status of the process.
+    ///
+    /// This can be `None` if the process failed to launch (like process not found).
+    pub exit: Option<ExitStatus>,
+    /// The output from the process.
+    ///
+    /// This can be `None` if the process failed to launch, or the output was not captured.
+    pub output: Option<Output>,
+}
+
+impl ProcessError {
+    /// The captured output of the process, formatted by `format_output`, or
+    /// an empty string if nothing was captured.
+    pub fn detail(&self) -> String {
+        self.output.as_ref().map(format_output).unwrap_or_default()
+    }
+}
+
+impl fmt::Display for ProcessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.desc.fmt(f)
+    }
+}
+
+impl std::error::Error for ProcessError {}
+
+/// Renders the captured output of a process for display, with each stream
+/// under a `[stdout]` or `[stderr]` header and its lines indented.
+///
+/// The streams are decoded lossily, and streams with no output are omitted.
+pub fn format_output(output: &Output) -> String {
+    let mut ret = String::new();
+    for (name, stream) in &[("stdout", &output.stdout), ("stderr", &output.stderr)] {
+        let stream = String::from_utf8_lossy(stream);
+        if stream.trim().is_empty() {
+            continue;
+        }
+        ret.push_str(&format!("\n[{}]\n", name));
+        for line in stream.lines() {
+            ret.push_str("  ");
+            ret.push_str(line);
+            ret.push('\n');
+        }
+    }
+    ret
+}
+
+// =============================================================================
+// Output overflow error
+
+/// Error when a process wrote more output than the limit set with
+/// `ProcessBuilder::output_limit`, and was killed.
+#[derive(Debug)]
+pub struct OutputOverflowError {
+    /// The command line of the process.
+    pub process: String,
+    /// The stream which exceeded the limit, `stdout` or `stderr`.
+    pub stream: &'static str,
+    /// The limit, in bytes.
+    pub limit: usize,
+}
+
+impl OutputOverflowError {
+    pub fn new(process: &str, stream: &'static str, limit: usize) -> OutputOverflowError {
+        OutputOverflowError {
+            process: process.to_string(),
+            stream,
+            limit,
+        }
+    }
+}
+
+impl fmt::Display for OutputOverflowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "process {} wrote more than {} bytes to {}, so it was stopped",
+            self.process, self.limit, self.stream
+        )
+    }
+}
+
+impl std::error::Error for OutputOverflowError {}
+
+// =============================================================================
+// Build script error
+
+/// Error when a build script fails.
+///
+/// If the script's stderr looks like it failed because an environment
+/// variable was not set, a hint naming the variable is shown.
+#[derive(Debug)]
+pub struct BuildScriptError {
+    cause: Error,
+    package: String,
+    missing_env_var: Option<MissingEnvVar>,
+}
+
+/// An environment variable a build script appears to have required.
+#[derive(Debug, PartialEq, Eq)]
+pub enum MissingEnvVar {
+    Named(String),
+    /// The script complained about a missing variable without naming it, for
+    /// example by unwrapping `std::env::var`.
+    Unnamed,
+}
+
+impl BuildScriptError {
+    pub fn new(cause: Error, package: String) -> Self {
+        let missing_env_var = cause
+            .chain()
+            .filter_map(|e| e.downcast_ref::<ProcessError>())
+            .filter_map(|e| e.output.as_ref())
+            .find_map(|output| missing_env_var(&String::from_utf8_lossy(&output.stderr)));
+        Self {
+            cause,
+            package,
+            missing_env_var,
+        }
+    }
+
+    pub fn missing_env_var(&self) -> Option<&MissingEnvVar> {
+        self.missing_env_var.as_ref()
+    }
+}
+
+impl std::error::Error for BuildScriptError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(self.cause.as_ref())
+    }
+}
+
+impl fmt::Display for BuildScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "failed to run custom build command for `{}`",
+            self.package
+        )?;
+        match &self.missing_env_var {
+            Some(MissingEnvVar::Named(name)) => write!(
+                f,
+                "\nhelp: the build script may require the environment variable `{}` to be set",
+                name
+            ),
+            Some(MissingEnvVar::Unnamed) => write!(
+                f,
+                "\nhelp: the build script may require an environment variable that is not set"
+            ),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Looks for common ways of reporting an unset environment variable in the
+/// stderr of a build script.
+///
+/// This recognizes messages mentioning an environment variable alongside
+/// wording like "not set" or "not found", `NAME must be set` style messages,
+/// and the `NotPresent` error from unwrapping `std::env::var`.
+pub fn missing_env_var(stderr: &str) -> Option<MissingEnvVar> {
+    const PHRASES: &[&str] = &[
+        "not set",
+        "not found",
+        "not defined",
+        "not present",
+        "notpresent",
+        "must be set",
+        "is required",
+        "is missing",
+    ];
+    let mut unnamed = false;
+    for line in stderr.lines() {
+        let lower = line.to_lowercase();
+        if !PHRASES.iter().any(|p| lower.contains(p)) {
+            continue;
+        }
+        let mentions_env = lower.contains("environment variable")
+            || lower.contains("env var")
+            || line.contains("NotPresent");
+        let name = line
+            .split(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .find(|word| is_env_var_name(word));
+        match name {
+            Some(name) if mentions_env || name.contains('_') => {
+                return Some(MissingEnvVar::Named(name.to_string()));
+            }
+            _ => unnamed |= mentions_env,
+        }
+    }
+    if unnamed {
+        Some(MissingEnvVar::Unnamed)
+    } else {
+        None
+    }
+}
+
+/// Whether `word` looks like the name of an environment variable, such as
+/// `SDK_PATH`, rather than a log level shouted at the start of a message.
+fn is_env_var_name(word: &str) -> bool {
+    const LOG_LEVELS: &[&str] = &["ERROR", "WARNING", "WARN", "NOTE", "INFO", "DEBUG", "FATAL"];
+    !LOG_LEVELS.contains(&word)
+        && word.len() >= 2
+        && word.starts_with(|c: char| c.is_ascii_uppercase() || c == '_')
+        && word
+            .chars()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
+        && word.chars().any(|c| c.is_ascii_uppercase())
+}
+
+// =============================================================================
+// Build script output error
+
+/// Error for a `cargo:` line printed by a build script which isn't a valid
+/// instruction.
+#[derive(Debug)]
+pub struct BuildScriptOutputError {
+    /// The package the build script is for, like `foo v0.1.0 (/path/to/foo)`.
+    pub package: String,
+    /// The offending line, without surrounding whitespace.
+    pub line: String,
+    /// Why the line is invalid.
+    pub reason: String,
+}
+
+impl BuildScriptOutputError {
+    pub fn new(package: &str, line: &str, reason: impl Into<String>) -> BuildScriptOutputError {
+        BuildScriptOutputError {
+            package: package.to_string(),
+            line: line.to_string(),
+            reason: reason.into(),
+        }
+    }
+}
+
+impl fmt::Display for BuildScriptOutputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid line `{}` in the output of the build script of `{}`: {}",
+            self.line, self.package, self.reason
+        )
+    }
+}
+
+impl std::error::Error for BuildScriptOutputError {}
+
+// =============================================================================
+// Cargo test errors.
+
+/// Error when testcases fail
+#[derive(Debug)]
+pub struct CargoTestError {
+    pub test: Test,
+    pub desc: String,
+    pub exit: Option<ExitStatus>,
+    pub causes: Vec<ProcessError>,
+}
+
+impl fmt::Display for CargoTestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.desc.fmt(f)
+    }
+}
+
+impl std::error::Error for CargoTestError {}
+
+#[derive(Debug)]
+pub enum Test {
+    Multiple,
+    Doc,
+    UnitTest {
+        kind: TargetKind,
+        name: String,
+        pkg_name: String,
+    },
+}
+
+impl CargoTestError {
+    pub fn new(test: Test, errors: Vec<ProcessError>) -> Self {
+        if errors.is_empty() {
+            panic!("Cannot create CargoTestError from empty Vec")
+        }
+        let desc = errors
+            .iter()
+            .map(|error| error.desc.clone())
+            .collect::<Vec<String>>()
+            .join("\n");
+        CargoTestError {
+            test,
+            desc,
+            exit: errors[0].exit,
+            causes: errors,
+        }
+    }
+
+    pub fn hint(&self, ws: &Workspace<'_>, opts: &CompileOptions) -> String {
+        match self.test {
+            Test::UnitTest {
+                ref kind,
+                ref name,
+                ref pkg_name,
+            } => {
+                let pkg_info = if opts.spec.needs_spec_flag(ws) {
+                    format!("-p {} ", pkg_name)
+                } else {
+                    String::new()
+                };
+
+                match *kind {
+                    TargetKind::Bench => {
+                        format!("test failed, to rerun pass '{}--bench {}'", pkg_info, name)
+                    }
+                    TargetKind::Bin => {
+                        format!("test failed, to rerun pass '{}--bin {}'", pkg_info, name)
+                    }
+                    TargetKind::Lib(_) => format!("test failed, to rerun pass '{}--lib'", pkg_info),
+                    TargetKind::Test => {
+                        format!("test failed, to rerun pass '{}--test {}'", pkg_info, name)
+                    }
+                    TargetKind::ExampleBin | TargetKind::ExampleLib(_) => {
+                        format!("test failed, to rerun pass '{}--example {}", pkg_info, name)
+                    }
+                    _ => "test failed.".into(),
+                }
+            }
+            Test::Doc => "test failed, to rerun pass '--doc'".into(),
+            _ => "test failed.".into(),
+        }
+    }
+}
+
+// =============================================================================
+// Error encoding
+
+/// Encodes `err` into a compact binary frame, for sending errors between
+/// processes. `decode_error` turns the frame back into an error.
+///
+/// Each link of the chain is kept along with whether it is an
+/// `InternalError`, as well as the code from `error_code`. Anything else
+/// about the original error types is lost.
+///
+/// The frame is a little-endian `u32` length followed by that many bytes:
+/// the number of links as a `u32`, then each link from the outermost as a
+/// flag byte (`1` if internal) and a length-prefixed UTF-8 message, and
+/// finally a `0` byte, or a `1` byte followed by the code as a `u32`.
+///
+/// Frames are at most `MAX_ERROR_FRAME` bytes long, not counting the length.
+/// If the messages don't fit, each is cut to an equal share of the space and
+/// ends with `...`.
+pub fn encode_error(err: &Error) -> Vec<u8> {
+    fn put_u32(buf: &mut Vec<u8>, n: usize) {
+        buf.extend_from_slice(&(n as u32).to_le_bytes());
+    }
+
+    let mut links = err
+        .chain()
+        .map(|link| {
+            let internal = link.downcast_ref::<InternalError>().is_some();
+            (internal, link.to_string())
+        })
+        .collect::<Vec<_>>();
+    let code = error_code(err);
+    let overhead = 4 + links.len() * 5 + if code.is_some() { 5 } else { 1 };
+    let budget = (MAX_ERROR_FRAME as usize).saturating_sub(overhead);
+    if links.iter().map(|(_, msg)| msg.len()).sum::<usize>() > budget {
+        let share = budget / links.len();
+        for (_, msg) in links.iter_mut().filter(|(_, msg)| msg.len() > share) {
+            let mut end = share.saturating_sub(3);
+            while !msg.is_char_boundary(end) {
+                end -= 1;
+            }
+            msg.truncate(end);
+            msg.push_str("...");
+        }
+    }
+
+    let mut payload = Vec::new();
+    put_u32(&mut payload, links.len());
+    for (internal, msg) in links {
+        payload.push(internal as u8);
+        put_u32(&mut payload, msg.len());
+        payload.extend_from_slice(msg.as_bytes());
+    }
+    match code {
+        Some(code) => {
+            payload.push(1);
+            put_u32(&mut payload, code as usize);
+        }
+        None => payload.push(0),
+    }
+
+    let mut frame = Vec::with_capacity(payload.len() + 4);
+    put_u32(&mut frame, payload.len());
+    frame.extend_from_slice(&payload);
+    frame
+}
+
+/// The largest frame `encode_error` writes and `decode_error` accepts, so a
+/// corrupt length can't make it allocate gigabytes.
+const MAX_ERROR_FRAME: u32 = 1 << 20;
+
+/// Reads one frame written by `encode_error` from `reader`, and rebuilds an
+/// error with the same chain of messages, internal links and code.
+pub fn decode_error(reader: &mut dyn Read) -> CargoResult<Error> {
+    fn get_u32(buf: &mut &[u8]) -> CargoResult<u32> {
+        if buf.len() < 4 {
+            anyhow::bail!("truncated error frame");
+        }
+        let mut n = [0; 4];
+        n.copy_from_slice(&buf[..4]);
+        *buf = &buf[4..];
+        Ok(u32::from_le_bytes(n))
+    }
+    fn get_u8(buf: &mut &[u8]) -> CargoResult<u8> {
+        let (&b, rest) = buf
+            .split_first()
+            .ok_or_else(|| anyhow::format_err!("truncated error frame"))?;
+        *buf = rest;
+        Ok(b)
+    }
+
+    let mut len = [0; 4];
+    reader
+        .read_exact(&mut len)
+        .chain_err(|| "failed to read error frame")?;
+    let len = u32::from_le_bytes(len);
+    if len > MAX_ERROR_FRAME {
+        anyhow::bail!(
+            "error frame of {} bytes exceeds the limit of {} bytes",
+            len,
+            MAX_ERROR_FRAME
+        );
+    }
+    let mut payload = vec![0; len as usize];
+    reader
+        .read_exact(&mut payload)
+        .chain_err(|| "failed to read error frame")?;
+
+    let buf = &mut &payload[..];
+    let count = get_u32(buf)?;
+    let mut links = Vec::new();
+    for _ in 0..count {
+        let internal = get_u8(buf)? == 1;
+        let len = get_u32(buf)? as usize;
+        if buf.len() < len {
+            anyhow::bail!("truncated error frame");
+        }
+        let msg = String::from_utf8(buf[..len].to_vec())
+            .map_err(|_| anyhow::format_err!("invalid UTF-8 in error frame"))?;
+        *buf = &buf[len..];
+        links.push((internal, msg));
+    }
+    let code = match get_u8(buf)? {
+        0 => None,
+        _ => Some(get_u32(buf)?),
+    };
+    if !buf.is_empty() {
+        anyhow::bail!("unexpected trailing data in error frame");
+    }
+
+    let mut links = links.into_iter().rev();
+    let (internal, msg) = links
+        .next()
+        .ok_or_else(|| anyhow::format_err!("empty error frame"))?;
+    let mut err = anyhow::format_err!("{}", msg);
+    if internal {
+        err = InternalError::new(err).into();
+    }
+    for (internal, msg) in links {
+        err = err.context(msg);
+        if internal {
+            err = InternalError::new(err).into();
+        }
+    }
+    if let Some(code) = code {
+        err = CodedError::new(err, code).into();
+    }
+    Ok(err)
+}
+
+// =============================================================================
+// Annotated error
+
+/// A typed error along with a context message and structured fields, such as
+/// the package the error happened in.
+///
+/// Converting it into an `anyhow::Error` adds an `Annotation` as context on
+/// top of the error, so `downcast_ref` still finds the typed error, and the
+/// fields can be looked up with `annotation`.
+#[derive(Debug)]
+pub struct Annotated<E> {
+    error: E,
+    annotation: Annotation,
+}
+
+/// The context added by an `Annotated` error. Only the message is displayed.
+#[derive(Debug)]
+pub struct Annotation {
+    pub context: String,
+    pub fields: Vec<(&'static str, String)>,
+}
+
+impl<E> Annotated<E> {
+    pub fn new(error: E, context: impl Into<String>) -> Annotated<E> {
+        Annotated {
+            error,
+            annotation: Annotation {
+                context: context.into(),
+                fields: Vec::new(),
+            },
+        }
+    }
+
+    /// Adds a field, replacing any previous value for `key`.
+    pub fn field(mut self, key: &'static str, value: impl fmt::Display) -> Annotated<E> {
+        let fields = &mut self.annotation.fields;
+        fields.retain(|(k, _)| *k != key);
+        fields.push((key, value.to_string()));
+        self
+    }
+
+    pub fn error(&self) -> &E {
+        &self.error
+    }
+}
+
+impl Annotation {
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|(k, _)| *k == key)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+impl fmt::Display for Annotation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.context.fmt(f)
+    }
+}
+
+impl<E: Into<Error>> From<Annotated<E>> for Error {
+    fn from(annotated: Annotated<E>) -> Error {
+        annotated.error.into().context(annotated.annotation)
+    }
+}
+
+/// Returns the value of `key` in the outermost `Annotation` of `err`'s chain.
+pub fn annotation<'a>(err: &'a Error, key: &str) -> Option<&'a str> {
+    err.downcast_ref::<Annotation>()
+        .and_then(|annotation| annotation.get(key))
+}
+
+// =============================================================================
+// Error throttle
+
+/// Passes errors on to a hook, such as one that logs them, while suppressing
+/// errors identical to one passed on within the last `window`.
+///
+/// Suppressed errors are counted, and a message with the count is passed to
+/// the hook the next time the error gets through, or when the throttle is
+/// flushed or dropped.
+pub struct ErrorThrottle<F: FnMut(&str)> {
+    hook: F,
+    window: Duration,
+    seen: HashMap<String, Throttled>,
+}
+
+struct Throttled {
+    passed_at: Instant,
+    suppressed: usize,
+}
+
+impl<F: FnMut(&str)> ErrorThrottle<F> {
+    pub fn new(window: Duration, hook: F) -> ErrorThrottle<F> {
+        ErrorThrottle {
+            hook,
+            window,
+            seen: HashMap::new(),
+        }
+    }
+
+    /// Passes `err` to the hook unless it was recently passed already.
+    pub fn observe(&mut self, err: &Error) {
+        self.observe_at(err, Instant::now());
+    }
+
+    fn observe_at(&mut self, err: &Error, now: Instant) {
+        let msg = format!("{:#}", err);
+        let window = self.window;
+        match self.seen.get_mut(&msg) {
+            Some(t) if now.duration_since(t.passed_at) < window => {
+                t.suppressed += 1;
+                return;
+            }
+            Some(t) => {
+                let suppressed = std::mem::replace(t, Throttled::new(now)).suppressed;
+                if suppressed > 0 {
+                    (self.hook)(&repeated(&msg, suppressed));
+                }
+            }
+            None => {
+                self.seen.insert(msg.clone(), Throttled::new(now));
+            }
+        }
+        (self.hook)(&msg);
+    }
+
+    /// Passes the counts of all suppressed errors to the hook.
+    pub fn flush(&mut self) {
+        let mut pending = self
+            .seen
+            .iter_mut()
+            .filter(|(_, t)| t.suppressed > 0)
+            .map(|(msg, t)| (msg.as_str(), std::mem::replace(&mut t.suppressed, 0)))
+            .collect::<Vec<_>>();
+        pending.sort();
+        for (msg, suppressed) in pending {
+            (self.hook)(&repeated(msg, suppressed));
+        }
+    }
+}
+
+impl Throttled {
+    fn new(now: Instant) -> Throttled {
+        Throttled {
+            passed_at: now,
+            suppressed: 0,
+        }
+    }
+}
+
+impl<F: FnMut(&str)> Drop for ErrorThrottle<F> {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
+fn repeated(msg: &str, count: usize) -> String {
+    format!(
+        "{} (repeated {} more time{})",
+        msg,
+        count,
+        if count == 1 { "" } else { "s" }
+    )
+}
+
+// =============================================================================
+// CLI errors
+
+pub type CliResult = Result<(), CliError>;
+
+/// A function which rewrites errors as they're turned into a `CliError`.
+pub type CliErrorTransform = Box<dyn Fn(anyhow::Error) -> anyhow::Error + Send + Sync>;
+
+lazy_static::lazy_static! {
+    static ref CLI_ERROR_TRANSFORM: RwLock<Option<CliErrorTransform>> = RwLock::new(None);
+}
+
+/// Registers a transform which is applied to every error on its way to the
+/// CLI boundary, replacing any previously registered one. `None` restores
+/// the default of leaving errors untouched.
+///
+/// This is meant for tools embedding Cargo which want to add context to
+/// errors, such as guidance specific to their environment. The transform
+/// must not create a `CliError` itself.
+pub fn set_cli_error_transform(transform: Option<CliErrorTransform>) {
+    *CLI_ERROR_TRANSFORM.write().unwrap() = transform;
+}
+
+#[derive(Debug)]
+/// The CLI error is the error type used at Cargo's CLI-layer.
+///
+/// All errors from the lib side of Cargo will get wrapped with this error.
+/// Other errors (such as command-line argument validation) will create this
+/// directly.
+pub struct CliError {
+    /// The error to display. This can be `None` in rare cases to exit with a
+    /// code without displaying a message. For example `cargo run -q` where
+    /// the resulting process exits with a nonzero code (on Windows), or an
+    /// external subcommand that exits nonzero (we assume it printed its own
+    /// message).
+    pub error: Option<anyhow::Error>,
+    /// The process exit code.
+    pub exit_code: i32,
+}
+
+impl CliError {
+    /// Creates a new CLI error, passing `error` through the transform
+    /// registered with `set_cli_error_transform`, if any.
+    pub fn new(error: anyhow::Error, code: i32) -> CliError {
+        let error = match &*CLI_ERROR_TRANSFORM.read().unwrap() {
+            Some(transform) => transform(error),
+            None => error,
+        };
+        CliError {
+            error: Some(error),
+            exit_code: code,
+        }
+    }
+
+    pub fn code(code: i32) -> CliError {
+        CliError {
+            error: None,
             exit_code: code,
         }
     }
@@ -346,6 +2527,100 @@ impl From<clap::Error> for CliError {
     }
 }
 
+/// Error for running `cargo` with arguments it doesn't understand, but no
+/// subcommand, such as `cargo --some-flag`.
+#[derive(Debug)]
+pub struct NoSubcommandError {
+    /// The first argument that was not recognized.
+    pub arg: String,
+}
+
+impl NoSubcommandError {
+    pub fn new(arg: impl Into<String>) -> NoSubcommandError {
+        NoSubcommandError { arg: arg.into() }
+    }
+}
+
+impl fmt::Display for NoSubcommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "unexpected argument `{}`, but no subcommand was given\n\n\
+             Usage:\n    cargo [OPTIONS] [SUBCOMMAND]\n\n\
+             Run `cargo --list` to see the available subcommands, \
+             or `cargo --help` for more information.",
+            self.arg
+        )
+    }
+}
+
+impl std::error::Error for NoSubcommandError {}
+
+// =============================================================================
+// Error kind
+
+/// A broad classification of errors, for grouping several of them in a
+/// summary. See `kind`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ErrorKind {
+    /// A problem with a `Cargo.toml`.
+    Manifest,
+    /// A problem with a config file or a `CARGO_*` environment variable.
+    Config,
+    /// A failure to reach a registry or git repository.
+    Network,
+    /// A compiler, build script, or other process which failed.
+    Compile,
+    /// A filesystem or other I/O failure.
+    Io,
+    /// Anything else.
+    Other,
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ErrorKind::Manifest => "manifest",
+            ErrorKind::Config => "config",
+            ErrorKind::Network => "network",
+            ErrorKind::Compile => "compile",
+            ErrorKind::Io => "I/O",
+            ErrorKind::Other => "other",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Classifies `err` by the outermost link of its chain with a known type, so
+/// that, for example, an I/O error reading a manifest is a manifest error.
+pub fn kind(err: &Error) -> ErrorKind {
+    err.chain().find_map(link_kind).unwrap_or(ErrorKind::Other)
+}
+
+fn link_kind(e: &(dyn std::error::Error + 'static)) -> Option<ErrorKind> {
+    if e.is::<ManifestError>() {
+        Some(ErrorKind::Manifest)
+    } else if e.is::<ConfigFileError>()
+        || e.is::<EnvConfigError>()
+        || e.is::<crate::util::config::ConfigError>()
+    {
+        Some(ErrorKind::Config)
+    } else if e.is::<HttpNot200>()
+        || e.is::<curl::Error>()
+        || e.is::<SourceUnavailableError>()
+        || e.downcast_ref::<git2::Error>()
+            .map_or(false, |e| e.class() == git2::ErrorClass::Net)
+    {
+        Some(ErrorKind::Network)
+    } else if e.is::<ProcessError>() || e.is::<BuildScriptError>() {
+        Some(ErrorKind::Compile)
+    } else if e.is::<std::io::Error>() {
+        Some(ErrorKind::Io)
+    } else {
+        None
+    }
+}
+
 // =============================================================================
 // Construction helpers
 
@@ -362,30 +2637,14 @@ pub fn process_error(
         Some(s) => status_to_string(s),
         None => "never executed".to_string(),
     };
-    let mut desc = format!("{} ({})", &msg, exit);
-
-    if let Some(out) = output {
-        match str::from_utf8(&out.stdout) {
-            Ok(s) if !s.trim().is_empty() => {
-                desc.push_str("\n--- stdout\n");
-                desc.push_str(s);
-            }
-            Ok(..) | Err(..) => {}
-        }
-        match str::from_utf8(&out.stderr) {
-            Ok(s) if !s.trim().is_empty() => {
-                desc.push_str("\n--- stderr\n");
-                desc.push_str(s);
-            }
-            Ok(..) | Err(..) => {}
-        }
-    }
-
-    return ProcessError {
-        desc,
+    let mut error = ProcessError {
+        desc: format!("{} ({})", &msg, exit),
         exit: status,
         output: output.cloned(),
     };
+    let detail = error.detail();
+    error.desc.push_str(&detail);
+    return error;
 
     #[cfg(unix)]
     fn status_to_string(status: ExitStatus) -> String {
@@ -473,6 +2732,656 @@ pub fn is_simple_exit_code(code: i32) -> bool {
     code >= 0 && code <= 127
 }
 
+/// Creates an `InternalError` from the formatted message of any value, such as
+/// an error from a library whose error type only implements `Display`.
+///
+/// Borrowed values work too, as in `internal(&err)`. The result isn't meant
+/// for humans: it's skipped by `primary_human`, and displaying it asks the
+/// user to file a bug report. Use `anyhow::format_err!("{}", err)` for errors
+/// that users can act on.
 pub fn internal<S: fmt::Display>(error: S) -> anyhow::Error {
     InternalError::new(anyhow::format_err!("{}", error)).into()
 }
+
+/// Returns the outermost link of `err`'s chain that is meant for humans, that
+/// is, one not wrapped in an `InternalError`, and which is not a `PhaseError`
+/// note.
+///
+/// Renderers can headline this link when a long chain of internal context
+/// surrounds the one actionable message.
+pub fn primary_human(err: &Error) -> Option<&(dyn std::error::Error + 'static)> {
+    err.chain().find(|e| {
+        e.downcast_ref::<InternalError>().is_none() && e.downcast_ref::<PhaseError>().is_none()
+    })
+}
+
+/// Returns the links of `err`'s chain, without those whose message is the
+/// same as the one of the link before them.
+///
+/// Context added with the same message at several layers otherwise shows up
+/// as repeated "Caused by" entries. The outermost link of each run is kept.
+pub fn dedup_chain(err: &Error) -> Vec<&(dyn std::error::Error + 'static)> {
+    let mut links = Vec::new();
+    let mut prev = None;
+    for link in err.chain() {
+        let msg = link.to_string();
+        if prev.as_ref() != Some(&msg) {
+            links.push(link);
+            prev = Some(msg);
+        }
+    }
+    links
+}
+
+/// Runs all of the independent `checks`, and reports every failure at once
+/// in a `MultiError`.
+///
+/// Returns `Ok(())` only if all checks passed.
+pub fn validate_all(checks: Vec<CargoResult<()>>) -> CargoResult<()> {
+    let errors: Vec<Error> = checks.into_iter().filter_map(Result::err).collect();
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(MultiError::new(errors).into())
+    }
+}
+
+/// Returns the code of the outermost `CodedError` in `err`'s chain, if any.
+pub fn error_code(err: &Error) -> Option<u32> {
+    err.chain()
+        .find_map(|e| e.downcast_ref::<CodedError>())
+        .map(|e| e.code())
+}
+
+/// Whether the error was explicitly marked as retryable or not with
+/// `CargoResultExt::retryable`. The outermost mark wins.
+pub fn is_retryable(err: &Error) -> Option<bool> {
+    err.chain()
+        .find_map(|e| e.downcast_ref::<RetryableError>())
+        .map(|e| e.retryable())
+}
+
+#[test]
+fn test_not_found_error() {
+    let err = NotFoundError::new("feature", "serd", vec!["serde", "rayon"]);
+    assert_eq!(err.closest(), Some("serde"));
+    assert_eq!(
+        err.to_string(),
+        "no feature named `serd`\n\n\tDid you mean `serde`?\n\n\
+         available features:\n    serde\n    rayon"
+    );
+
+    let err = NotFoundError::new("feature", "tokio", vec!["serde", "rayon"]);
+    assert_eq!(err.closest(), None);
+    assert_eq!(
+        err.to_string(),
+        "no feature named `tokio`\n\navailable features:\n    serde\n    rayon"
+    );
+
+    let err = NotFoundError::new("feature", "tokio", Vec::<String>::new());
+    assert_eq!(
+        err.to_string(),
+        "no feature named `tokio`\n\nthere are no features available"
+    );
+}
+
+#[test]
+fn test_primary_human() {
+    let err = internal("index out of bounds");
+    assert!(primary_human(&err).is_none());
+
+    let err = err.context("failed to load manifest");
+    let human = primary_human(&err).unwrap();
+    assert_eq!(human.to_string(), "failed to load manifest");
+
+    let err: Error = InternalError::new(
+        anyhow::format_err!("no matching package named `foo` found")
+            .context("failed to select a version"),
+    )
+    .into();
+    let human = primary_human(&err).unwrap();
+    assert_eq!(human.to_string(), "no matching package named `foo` found");
+}
+
+#[test]
+fn test_phase_error() {
+    let err = Error::from(HttpNot200 {
+        code: 503,
+        url: "https://example.com/a".to_string(),
+    });
+    let err: Error = PhaseError::new(err, "resolve", Duration::from_secs(2)).into();
+    let err = err.context("failed to select a version");
+    assert!(err
+        .chain()
+        .any(|e| e.downcast_ref::<HttpNot200>().is_some()));
+
+    let buffer = TestBuffer::default();
+    let mut shell = buffer.shell();
+    crate::display_error(&err, &mut shell);
+    assert_eq!(
+        buffer.contents(),
+        "error: failed to select a version\n\n\
+         Caused by:\n  \
+         failed to get 200 response from `https://example.com/a`, got 503 \
+         (during resolve, +2.00s)\n"
+    );
+}
+
+#[test]
+fn test_format_output() {
+    #[cfg(unix)]
+    use std::os::unix::process::ExitStatusExt;
+    #[cfg(windows)]
+    use std::os::windows::process::ExitStatusExt;
+
+    let output = Output {
+        status: ExitStatus::from_raw(1),
+        stdout: b"line one\nline two\n".to_vec(),
+        stderr: b"bad \xff byte".to_vec(),
+    };
+    assert_eq!(
+        format_output(&output),
+        "\n[stdout]\n  line one\n  line two\n\n[stderr]\n  bad \u{FFFD} byte\n"
+    );
+
+    let output = Output {
+        stdout: Vec::new(),
+        ..output
+    };
+    assert_eq!(format_output(&output), "\n[stderr]\n  bad \u{FFFD} byte\n");
+}
+
+#[test]
+fn test_exit_code() {
+    assert_eq!(crate::exit_code(&Ok(())), 0);
+    let err = anyhow::format_err!("failed");
+    assert_eq!(crate::exit_code(&Err(err.into())), 101);
+    assert_eq!(crate::exit_code(&Err(CliError::code(3))), 3);
+}
+
+#[test]
+fn test_exit_code_with_warnings() {
+    use crate::exit_code_with_warnings;
+    assert_eq!(exit_code_with_warnings(&Ok(()), 0, None), 0);
+    assert_eq!(exit_code_with_warnings(&Ok(()), 3, None), 0);
+    assert_eq!(exit_code_with_warnings(&Ok(()), 0, Some(2)), 0);
+    assert_eq!(exit_code_with_warnings(&Ok(()), 3, Some(2)), 2);
+    let err = anyhow::format_err!("failed");
+    assert_eq!(exit_code_with_warnings(&Err(err.into()), 3, Some(2)), 101);
+    assert_eq!(
+        exit_code_with_warnings(&Err(CliError::code(4)), 3, Some(2)),
+        4
+    );
+}
+
+#[test]
+fn test_missing_env_var() {
+    use MissingEnvVar::*;
+    let named = |s: &str| Some(Named(s.to_string()));
+    assert_eq!(
+        missing_env_var(
+            "thread 'main' panicked at 'called `Result::unwrap()` on an `Err` value: \
+             NotPresent', build.rs:2:5"
+        ),
+        Some(Unnamed)
+    );
+    assert_eq!(
+        missing_env_var(
+            "thread 'main' panicked at 'SDK_PATH must be set: NotPresent', build.rs:2:5"
+        ),
+        named("SDK_PATH")
+    );
+    assert_eq!(
+        missing_env_var("error: environment variable `OPENSSL_DIR` not found"),
+        named("OPENSSL_DIR")
+    );
+    assert_eq!(
+        missing_env_var("ERROR: environment variable JAVA_HOME is not set"),
+        named("JAVA_HOME")
+    );
+    assert_eq!(
+        missing_env_var("running build\nLLVM_CONFIG_PATH is required"),
+        named("LLVM_CONFIG_PATH")
+    );
+    assert_eq!(missing_env_var("error: file not found"), None);
+    assert_eq!(missing_env_var("HELLO must be set"), None);
+    assert_eq!(missing_env_var(""), None);
+}
+
+#[test]
+fn test_error_code() {
+    use crate::util::machine_message::{ErrorReport, Message};
+
+    let err = anyhow::format_err!("no matching package named `foo` found");
+    assert_eq!(error_code(&err), None);
+    assert_eq!(
+        ErrorReport::new(&err).to_json_string(),
+        r#"{"reason":"cargo-error","message":"no matching package named `foo` found","causes":[],"code":null,"fields":{}}"#
+    );
+
+    let err = Error::from(CodedError::new(err, 12)).context("failed to select a version");
+    assert_eq!(error_code(&err), Some(12));
+    assert_eq!(
+        err.chain().map(|e| e.to_string()).collect::<Vec<_>>(),
+        [
+            "failed to select a version",
+            "no matching package named `foo` found"
+        ]
+    );
+    assert_eq!(
+        ErrorReport::new(&err).to_json_string(),
+        r#"{"reason":"cargo-error","message":"failed to select a version","causes":["no matching package named `foo` found"],"code":12,"fields":{}}"#
+    );
+
+    // The outermost code wins.
+    let err: Error = CodedError::new(err, 3).into();
+    assert_eq!(error_code(&err), Some(3));
+}
+
+#[cfg(windows)]
+#[test]
+fn test_process_error_windows_path() {
+    let cmd = crate::util::process(r"\\?\C:\foo/target\debug/build-script-build");
+    let err = process_error(
+        &format!("process didn't exit successfully: {}", cmd),
+        None,
+        None,
+    );
+    assert_eq!(
+        err.to_string(),
+        r"process didn't exit successfully: `C:\foo\target\debug\build-script-build`"
+    );
+}
+
+#[test]
+fn test_target_kind_error() {
+    use crate::core::compiler::CompileTarget;
+    use crate::core::SourceId;
+
+    let source = SourceId::from_url("registry+https://example.com/index").unwrap();
+    let bar = PackageId::new("bar", "0.1.0", source).unwrap();
+    let target = CompileKind::Target(CompileTarget::new("x86_64-unknown-linux-gnu").unwrap());
+    let err = TargetKindError::new(bar, "a build dependency", CompileKind::Host, target);
+    assert_eq!(
+        err.to_string(),
+        "package `bar v0.1.0 (registry `https://example.com/index`)` is a build dependency \
+         and must be built for the host, but was selected to be built for \
+         target `x86_64-unknown-linux-gnu`"
+    );
+}
+
+#[test]
+fn test_ambiguous_target_error() {
+    let err = AmbiguousTargetError::new(vec!["a".to_string(), "b".to_string()]);
+    assert_eq!(err.candidates(), ["a", "b"]);
+    assert!(err
+        .to_string()
+        .ends_with("or the `default-run` manifest key.\navailable binaries: a, b"));
+}
+
+#[test]
+fn test_cli_error_transform() {
+    // Other tests may create errors concurrently, so only touch ours.
+    set_cli_error_transform(Some(Box::new(|err| {
+        if err.to_string() == "transform me" {
+            err.context("transformed")
+        } else {
+            err
+        }
+    })));
+    let err = CliError::new(anyhow::format_err!("transform me"), 101);
+    let other: CliError = anyhow::format_err!("leave me").into();
+    set_cli_error_transform(None);
+    let untouched = CliError::new(anyhow::format_err!("transform me"), 101);
+
+    let err = err.error.unwrap();
+    assert_eq!(err.to_string(), "transformed");
+    assert_eq!(err.root_cause().to_string(), "transform me");
+    assert_eq!(other.error.unwrap().to_string(), "leave me");
+    assert_eq!(untouched.error.unwrap().to_string(), "transform me");
+}
+
+#[test]
+fn test_encode_error() {
+    let err = internal("unexpected state").context("failed to load `foo`");
+    let err = Error::from(CodedError::new(err, 7)).context("could not compile");
+    let frame = encode_error(&err);
+    let mut reader = &frame[..];
+    let decoded = decode_error(&mut reader).unwrap();
+    assert!(reader.is_empty());
+
+    let chain = |e: &Error| e.chain().map(|e| e.to_string()).collect::<Vec<_>>();
+    assert_eq!(chain(&decoded), chain(&err));
+    assert_eq!(error_code(&decoded), Some(7));
+    let internal = |e: &Error| {
+        e.chain()
+            .map(|e| e.downcast_ref::<InternalError>().is_some())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(internal(&decoded), [false, false, true]);
+    assert_eq!(internal(&decoded), internal(&err));
+
+    // Frames can be read one after the other from a stream.
+    let plain = anyhow::format_err!("plain");
+    let mut stream = encode_error(&plain);
+    stream.extend(frame.iter());
+    let mut reader = &stream[..];
+    let first = decode_error(&mut reader).unwrap();
+    assert_eq!(chain(&first), ["plain"]);
+    assert_eq!(error_code(&first), None);
+    assert_eq!(chain(&decode_error(&mut reader).unwrap()), chain(&err));
+
+    assert!(decode_error(&mut &frame[..frame.len() - 1]).is_err());
+
+    // A message which exactly fills the frame is kept as is, and a longer one
+    // is cut to fit.
+    let max = MAX_ERROR_FRAME as usize;
+    let fits = anyhow::format_err!("{}", "x".repeat(max - 10));
+    let frame = encode_error(&fits);
+    assert_eq!(frame.len(), max + 4);
+    assert_eq!(chain(&decode_error(&mut &frame[..]).unwrap()), chain(&fits));
+    let long = anyhow::format_err!("{}", "é".repeat(max)).context("outer");
+    let frame = encode_error(&long);
+    assert!(frame.len() <= max + 4);
+    let decoded = chain(&decode_error(&mut &frame[..]).unwrap());
+    assert_eq!(decoded[0], "outer");
+    assert!(decoded[1].starts_with("éé") && decoded[1].ends_with("é..."));
+
+    let oversized = (MAX_ERROR_FRAME + 1).to_le_bytes();
+    let err = decode_error(&mut &oversized[..]).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "error frame of 1048577 bytes exceeds the limit of 1048576 bytes"
+    );
+}
+
+#[test]
+fn test_error_throttle() {
+    let mut seen = Vec::new();
+    let start = Instant::now();
+    {
+        let mut throttle = ErrorThrottle::new(Duration::from_secs(10), |msg: &str| {
+            seen.push(msg.to_string())
+        });
+        let a = anyhow::format_err!("a");
+        let b = anyhow::format_err!("inner").context("b");
+        throttle.observe_at(&a, start);
+        throttle.observe_at(&a, start + Duration::from_secs(1));
+        throttle.observe_at(&b, start + Duration::from_secs(2));
+        throttle.observe_at(&a, start + Duration::from_secs(3));
+        throttle.observe_at(&a, start + Duration::from_secs(11));
+        throttle.observe_at(&b, start + Duration::from_secs(12));
+        throttle.observe_at(&b, start + Duration::from_secs(13));
+    }
+    assert_eq!(
+        seen,
+        [
+            "a",
+            "b: inner",
+            "a (repeated 2 more times)",
+            "a",
+            "b: inner",
+            "b: inner (repeated 1 more time)",
+        ]
+    );
+}
+
+#[test]
+fn test_annotated() {
+    let err: Error = Annotated::new(process_error("process didn't exit", None, None), "outer")
+        .field("package", "foo")
+        .field("package", "bar")
+        .into();
+    assert_eq!(err.to_string(), "outer");
+    assert_eq!(annotation(&err, "package"), Some("bar"));
+    assert_eq!(annotation(&err, "target"), None);
+    let perr = err.downcast_ref::<ProcessError>().unwrap();
+    assert_eq!(perr.desc, "process didn't exit (never executed)");
+
+    let err = err.context("more context");
+    assert!(err.downcast_ref::<ProcessError>().is_some());
+    assert_eq!(annotation(&err, "package"), Some("bar"));
+
+    // Fields are only for tooling, so they're not displayed.
+    use crate::util::machine_message::{ErrorReport, Message};
+    let err: Error = Annotated::new(err, "failed to compile `foo`")
+        .field("target", "foo")
+        .field("package", "foo v0.1.0")
+        .into();
+    assert_eq!(
+        ErrorReport::new(&err).to_json_string(),
+        r#"{"reason":"cargo-error","message":"failed to compile `foo`","causes":["more context","outer","process didn't exit (never executed)"],"code":null,"fields":{"package":"foo v0.1.0","target":"foo"}}"#
+    );
+}
+
+#[test]
+fn test_display_errors() {
+    let buffer = TestBuffer::default();
+    let mut shell = buffer.shell();
+    assert_eq!(crate::display_errors(&[], &mut shell), 0);
+    assert_eq!(buffer.contents(), "");
+
+    let errors = vec![
+        CliError::new(anyhow::format_err!("first"), 3),
+        CliError::code(7),
+        CliError::new(anyhow::format_err!("inner").context("second"), 101),
+    ];
+    assert_eq!(crate::display_errors(&errors, &mut shell), 101);
+    assert_eq!(
+        buffer.contents(),
+        "error: first\n\nerror: second\n\nCaused by:\n  inner\n"
+    );
+}
+
+#[test]
+fn test_display_error_panic() {
+    #[derive(Debug)]
+    struct Malformed;
+
+    impl fmt::Display for Malformed {
+        fn fmt(&self, _f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            panic!("malformed detail")
+        }
+    }
+
+    impl std::error::Error for Malformed {}
+
+    let buffer = TestBuffer::default();
+    let mut shell = buffer.shell();
+    let err = Error::new(Malformed).context("failed to build `foo`");
+    crate::display_error(&err, &mut shell);
+    assert_eq!(
+        buffer.contents(),
+        "error: failed to build `foo`\n\
+         note: cargo failed to display this error in full, which is a bug\n"
+    );
+
+    let buffer = TestBuffer::default();
+    let mut shell = buffer.shell();
+    crate::display_error(&Error::new(Malformed), &mut shell);
+    assert_eq!(
+        buffer.contents(),
+        "error: (the error message could not be displayed)\n\
+         note: cargo failed to display this error in full, which is a bug\n"
+    );
+}
+
+/// Output for a `Shell`, which can be read after writing to the shell.
+#[cfg(test)]
+#[derive(Clone, Default)]
+struct TestBuffer(std::rc::Rc<std::cell::RefCell<Vec<u8>>>);
+
+#[cfg(test)]
+impl TestBuffer {
+    fn shell(&self) -> Shell {
+        Shell::from_write(Box::new(self.clone()))
+    }
+
+    fn contents(&self) -> String {
+        String::from_utf8(self.0.borrow().clone()).unwrap()
+    }
+}
+
+#[cfg(test)]
+impl std::io::Write for TestBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.borrow_mut().write(buf)
+    }
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_display_error_max_chain_depth() {
+    let err = (1..=7).fold(anyhow::format_err!("cause 7"), |err, i| {
+        err.context(format!("cause {}", 7 - i))
+    });
+    let buffer = TestBuffer::default();
+    let mut shell = buffer.shell();
+    shell.set_verbosity(crate::core::shell::Verbosity::Normal);
+    crate::display_error(&err, &mut shell);
+    assert_eq!(
+        buffer.contents(),
+        "error: cause 0\n\
+         \nCaused by:\n  cause 1\n\
+         \nCaused by:\n  cause 2\n\
+         \nCaused by:\n  cause 3\n\
+         \nCaused by:\n  cause 4\n\
+         \nCaused by:\n  cause 5\n\
+         \n... (1 more)\n\
+         \nCaused by:\n  cause 7\n\
+         \nTo learn more, run the command again with --verbose.\n"
+    );
+
+    // The whole chain is displayed in verbose mode.
+    let buffer = TestBuffer::default();
+    let mut shell = buffer.shell();
+    crate::display_error(&err, &mut shell);
+    assert!(buffer.contents().ends_with("Caused by:\n  cause 7\n"));
+
+    // The manifest snippet follows a collapsed chain.
+    let err = ManifestError::new(err, PathBuf::from("Cargo.toml"))
+        .with_snippet(Some("1 | [package]".to_string()));
+    let buffer = TestBuffer::default();
+    let mut shell = buffer.shell();
+    shell.set_verbosity(crate::core::shell::Verbosity::Normal);
+    crate::display_error(&err.into(), &mut shell);
+    assert!(buffer.contents().ends_with(
+        "\n... (1 more)\n\
+         \nCaused by:\n  cause 7\n\
+         \n  1 | [package]\n\
+         \nTo learn more, run the command again with --verbose.\n"
+    ));
+}
+
+#[test]
+fn test_unwrap_or_warn() {
+    let buffer = TestBuffer::default();
+    let mut shell = buffer.shell();
+    assert_eq!(Ok::<_, Error>(1).unwrap_or_warn(&mut shell, 0), 1);
+    let err = anyhow::format_err!("no such file").context("failed to read `a`");
+    assert_eq!(Err::<u32, _>(err).unwrap_or_warn(&mut shell, 0), 0);
+    assert_eq!(
+        buffer.contents(),
+        "warning: failed to read `a`: no such file\n"
+    );
+    assert_eq!(shell.warning_count(), 1);
+}
+
+#[test]
+fn test_dedup_chain() {
+    let err = anyhow::format_err!("no such file")
+        .context("failed to read `a`")
+        .context("failed to read `a`")
+        .context("failed to load")
+        .context("failed to read `a`");
+    let messages = |links: Vec<&(dyn std::error::Error + 'static)>| {
+        links.iter().map(|e| e.to_string()).collect::<Vec<_>>()
+    };
+    assert_eq!(
+        messages(dedup_chain(&err)),
+        [
+            "failed to read `a`",
+            "failed to load",
+            "failed to read `a`",
+            "no such file"
+        ]
+    );
+    assert_eq!(err.chain().count(), 5);
+}
+
+#[test]
+fn test_validate_all() {
+    assert!(validate_all(vec![]).is_ok());
+    assert!(validate_all(vec![Ok(()), Ok(())]).is_ok());
+
+    let err = validate_all(vec![
+        Ok(()),
+        Err(anyhow::format_err!("inner").context("outer")),
+    ])
+    .unwrap_err();
+    assert_eq!(format!("{:#}", err), "outer: inner");
+
+    let err = validate_all(vec![
+        Err(anyhow::format_err!("inner").context("first")),
+        Ok(()),
+        Err(anyhow::format_err!("second\nhint")),
+    ])
+    .unwrap_err();
+    assert_eq!(err.downcast_ref::<MultiError>().unwrap().errors().len(), 2);
+    assert_eq!(
+        err.to_string(),
+        "2 errors occurred:\n    first: inner\n    second\n      hint"
+    );
+}
+
+#[test]
+fn test_kind() {
+    let io = || std::io::Error::new(std::io::ErrorKind::NotFound, "no such file");
+    assert_eq!(kind(&anyhow::format_err!("oops")), ErrorKind::Other);
+    assert_eq!(kind(&Error::from(io()).context("reading")), ErrorKind::Io);
+
+    let err = ManifestError::new(Error::from(io()), PathBuf::from("Cargo.toml"));
+    assert_eq!(kind(&err.into()), ErrorKind::Manifest);
+
+    let err = HttpNot200 {
+        code: 503,
+        url: "https://example.com".to_string(),
+    };
+    assert_eq!(
+        kind(&Error::from(err).context("failed to download")),
+        ErrorKind::Network
+    );
+}
+
+#[test]
+fn test_display_error_summary() {
+    let errors = vec![
+        Error::from(HttpNot200 {
+            code: 503,
+            url: "https://example.com/a".to_string(),
+        }),
+        anyhow::format_err!("oops"),
+        Error::from(HttpNot200 {
+            code: 504,
+            url: "https://example.com/b".to_string(),
+        }),
+    ];
+    let err = Error::from(MultiError::new(errors));
+
+    let buffer = TestBuffer::default();
+    let mut shell = buffer.shell();
+    crate::display_error(&err, &mut shell);
+    assert!(!buffer.contents().contains("by kind"));
+
+    let buffer = TestBuffer::default();
+    let mut shell = buffer.shell();
+    shell.set_error_summary(true);
+    crate::display_error(&err, &mut shell);
+    assert!(buffer.contents().ends_with(
+        "\n\nnote: 3 errors occurred, by kind:\n  \
+         network: 2 (first: failed to get 200 response from `https://example.com/a`, got 503)\n  \
+         other: 1 (first: oops)\n"
+    ));
+}
@@ -2,6 +2,8 @@ use std::fs::{File, OpenOptions};
 use std::io;
 use std::io::{Read, Seek, SeekFrom, Write};
 use std::path::{Display, Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
 
 use termcolor::Color::Cyan;
 
@@ -10,6 +12,10 @@ use crate::util::paths;
 use crate::util::Config;
 use sys::*;
 
+/// How long to keep retrying a contended lock before printing that cargo is
+/// blocked waiting for it.
+const BLOCKING_GRACE_PERIOD: Duration = Duration::from_millis(200);
+
 #[derive(Debug)]
 pub struct FileLock {
     f: Option<File>,
@@ -175,11 +181,26 @@ impl Filesystem {
             path.as_ref(),
             OpenOptions::new().read(true).write(true).create(true),
             State::Exclusive,
-            config,
+            Some(config),
             msg,
         )
     }
 
+    /// Like `open_rw`, but for threads without access to the `Config`, such
+    /// as the jobs of a build, so blocking for the lock isn't reported.
+    pub fn open_rw_unreported<P>(&self, path: P) -> CargoResult<FileLock>
+    where
+        P: AsRef<Path>,
+    {
+        self.open(
+            path.as_ref(),
+            OpenOptions::new().read(true).write(true).create(true),
+            State::Exclusive,
+            None,
+            "",
+        )
+    }
+
     /// Opens shared access to a file, returning the locked version of a file.
     ///
     /// This function will fail if `path` doesn't already exist, but if it does
@@ -197,7 +218,24 @@ impl Filesystem {
             path.as_ref(),
             OpenOptions::new().read(true),
             State::Shared,
-            config,
+            Some(config),
+            msg,
+        )
+    }
+
+    /// Opens shared access to a file like `open_ro`, but creates the file
+    /// (including intermediate directories) if it doesn't already exist.
+    pub fn open_ro_create<P>(&self, path: P, config: &Config, msg: &str) -> CargoResult<FileLock>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        paths::create_dir_all(self.root.join(path).parent().unwrap())?;
+        self.open(
+            path,
+            OpenOptions::new().read(true).write(true).create(true),
+            State::Shared,
+            Some(config),
             msg,
         )
     }
@@ -207,7 +245,7 @@ impl Filesystem {
         path: &Path,
         opts: &OpenOptions,
         state: State,
-        config: &Config,
+        config: Option<&Config>,
         msg: &str,
     ) -> CargoResult<FileLock> {
         let path = self.root.join(path);
@@ -268,14 +306,15 @@ impl PartialEq<Filesystem> for Path {
 ///
 /// This function will acquire the lock on a `path`, printing out a nice message
 /// to the console if we have to wait for it. It will first attempt to use `try`
-/// to acquire a lock on the crate, and in the case of contention it will emit a
-/// status message based on `msg` to `config`'s shell, and then use `block` to
-/// block waiting to acquire a lock.
+/// to acquire a lock on the crate, and in the case of contention it will keep
+/// trying for `BLOCKING_GRACE_PERIOD`, then emit a status message based on
+/// `msg` to `config`'s shell (if given), and then use `block` to block waiting
+/// to acquire a lock.
 ///
 /// Returns an error if the lock could not be acquired or if any error other
 /// than a contention error happens.
 fn acquire(
-    config: &Config,
+    config: Option<&Config>,
     msg: &str,
     path: &Path,
     lock_try: &dyn Fn() -> io::Result<()>,
@@ -311,8 +350,20 @@ fn acquire(
             }
         }
     }
-    let msg = format!("waiting for file lock on {}", msg);
-    config.shell().status_with_color("Blocking", &msg, Cyan)?;
+    // Contention is often brief, such as another cargo finishing up, so retry
+    // for a moment before telling the user we're blocked.
+    let deadline = Instant::now() + BLOCKING_GRACE_PERIOD;
+    while Instant::now() < deadline {
+        thread::sleep(Duration::from_millis(10));
+        if lock_try().is_ok() {
+            return Ok(());
+        }
+    }
+
+    if let Some(config) = config {
+        let msg = format!("waiting for file lock on {}", msg);
+        config.shell().status_with_color("Blocking", &msg, Cyan)?;
+    }
 
     lock_block().chain_err(|| format!("failed to lock file: {}", path.display()))?;
     return Ok(());
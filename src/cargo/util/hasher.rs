@@ -1,10 +1,25 @@
-//! Implementation of a hasher that produces the same values across releases.
+//! Hashers used throughout Cargo.
 //!
-//! The hasher should be fast and have a low chance of collisions (but is not
-//! sufficient for cryptographic purposes).
+//! [`StableHasher`] produces the same values across releases. It should be
+//! fast and have a low chance of collisions (but is not sufficient for
+//! cryptographic purposes).
+//!
+//! [`ContentHasher`] is an incremental SHA-256 hasher for anything which
+//! identifies content: file and directory digests, checksums and the names
+//! of directories keyed by content. [`ShortDigest`] is the truncated form of
+//! its output used where a full digest would be unwieldy, like directory
+//! names.
 #![allow(deprecated)]
 
+use std::fmt;
+use std::fs::File;
 use std::hash::{Hasher, SipHasher};
+use std::io::{self, Read};
+use std::path::Path;
+
+use walkdir::WalkDir;
+
+use crate::util::{paths, CargoResult, CargoResultExt, Sha256};
 
 pub struct StableHasher(SipHasher);
 
@@ -22,3 +37,240 @@ impl Hasher for StableHasher {
         self.0.write(bytes)
     }
 }
+
+/// An incremental SHA-256 hasher.
+pub struct ContentHasher(Sha256);
+
+impl ContentHasher {
+    pub fn new() -> ContentHasher {
+        ContentHasher(Sha256::new())
+    }
+
+    /// Feeds raw bytes into the hasher.
+    pub fn update(&mut self, bytes: &[u8]) -> &mut ContentHasher {
+        self.0.update(bytes);
+        self
+    }
+
+    /// Feeds one field of a larger value into the hasher.
+    ///
+    /// The field is prefixed with its length, so that the fields `ab` and `c`
+    /// hash differently from `a` and `bc`.
+    pub fn update_field(&mut self, bytes: &[u8]) -> &mut ContentHasher {
+        self.update(&(bytes.len() as u64).to_le_bytes());
+        self.update(bytes)
+    }
+
+    /// Feeds everything read from `reader` into the hasher, a chunk at a
+    /// time so large inputs are never held in memory.
+    pub fn update_reader(&mut self, mut reader: impl Read) -> io::Result<&mut ContentHasher> {
+        let mut buf = [0; 64 * 1024];
+        loop {
+            let n = reader.read(&mut buf)?;
+            if n == 0 {
+                break Ok(self);
+            }
+            self.update(&buf[..n]);
+        }
+    }
+
+    pub fn finish(&mut self) -> Digest {
+        Digest(self.0.finish())
+    }
+}
+
+impl Default for ContentHasher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A complete SHA-256 digest produced by [`ContentHasher`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Digest([u8; 32]);
+
+impl Digest {
+    /// The digest of `bytes`.
+    pub fn of(bytes: &[u8]) -> Digest {
+        ContentHasher::new().update(bytes).finish()
+    }
+
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+
+    /// The first 8 bytes of the digest.
+    pub fn short(&self) -> ShortDigest {
+        let mut short = [0; 8];
+        short.copy_from_slice(&self.0[..8]);
+        ShortDigest(short)
+    }
+}
+
+impl fmt::Display for Digest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&hex::encode(self.0))
+    }
+}
+
+impl fmt::Debug for Digest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Digest({})", self)
+    }
+}
+
+/// A truncated [`Digest`], displayed as 16 hex digits.
+///
+/// This is what's used to name directories after their contents, and its
+/// value for a given input never changes between releases.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ShortDigest([u8; 8]);
+
+impl ShortDigest {
+    /// The short digest of `bytes`.
+    pub fn of(bytes: &[u8]) -> ShortDigest {
+        Digest::of(bytes).short()
+    }
+}
+
+impl fmt::Display for ShortDigest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&hex::encode(self.0))
+    }
+}
+
+impl fmt::Debug for ShortDigest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ShortDigest({})", self)
+    }
+}
+
+/// Hashes the contents of the file at `path`.
+pub fn hash_file(path: &Path) -> CargoResult<Digest> {
+    let file = File::open(path).chain_err(|| format!("failed to open `{}`", path.display()))?;
+    let digest = ContentHasher::new()
+        .update_reader(file)
+        .chain_err(|| format!("failed to read `{}`", path.display()))?
+        .finish();
+    Ok(digest)
+}
+
+/// Hashes the names and contents of all files below `dir`, following
+/// symbolic links.
+///
+/// `filter` is called with the path of every file and directory relative to
+/// `dir`; returning `false` leaves the file (or the whole directory) out.
+///
+/// Files are hashed in the order of their relative paths with components
+/// joined by `/`, so the result doesn't depend on the order the filesystem
+/// lists them in nor on the platform's path separator. Empty directories
+/// don't contribute to the digest.
+pub fn hash_dir_contents(dir: &Path, filter: &mut dyn FnMut(&Path) -> bool) -> CargoResult<Digest> {
+    let mut files = Vec::new();
+    let walker = WalkDir::new(dir)
+        .follow_links(true)
+        .min_depth(1)
+        .into_iter()
+        .filter_entry(|entry| filter(entry.path().strip_prefix(dir).unwrap()));
+    for entry in walker {
+        let entry = entry.chain_err(|| format!("failed to walk `{}`", dir.display()))?;
+        if entry.file_type().is_dir() {
+            continue;
+        }
+        let rel = entry.path().strip_prefix(dir).unwrap();
+        let mut name = Vec::new();
+        for (i, component) in rel.iter().enumerate() {
+            if i > 0 {
+                name.push(b'/');
+            }
+            name.extend_from_slice(paths::path2bytes(Path::new(component))?);
+        }
+        files.push((name, entry.into_path()));
+    }
+    files.sort();
+
+    let mut hasher = ContentHasher::new();
+    for (name, path) in files {
+        hasher.update_field(&name);
+        hasher.update_field(hash_file(&path)?.as_bytes());
+    }
+    Ok(hasher.finish())
+}
+
+#[test]
+fn update_field_separates_fields() {
+    let ab_c = ContentHasher::new()
+        .update_field(b"ab")
+        .update_field(b"c")
+        .finish();
+    let a_bc = ContentHasher::new()
+        .update_field(b"a")
+        .update_field(b"bc")
+        .finish();
+    assert_ne!(ab_c, a_bc);
+    // Plain updates don't separate anything.
+    assert_eq!(
+        ContentHasher::new().update(b"ab").update(b"c").finish(),
+        ContentHasher::new().update(b"a").update(b"bc").finish()
+    );
+}
+
+#[test]
+fn known_digests() {
+    assert_eq!(
+        Digest::of(b"").to_string(),
+        "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+    );
+    assert_eq!(ShortDigest::of(b"").to_string(), "e3b0c44298fc1c14");
+}
+
+#[test]
+fn hash_dir_contents_is_order_independent() {
+    let write = |root: &Path, files: &[(&str, &str)]| {
+        for (name, contents) in files {
+            let path = root.join(name);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, contents).unwrap();
+        }
+    };
+    let files = [
+        ("a.txt", "a"),
+        ("sub/b.txt", "b"),
+        ("sub/deeper/c.txt", "c"),
+    ];
+    let mut reversed = files;
+    reversed.reverse();
+
+    let one = tempfile::tempdir().unwrap();
+    let two = tempfile::tempdir().unwrap();
+    write(one.path(), &files);
+    write(two.path(), &reversed);
+    // An empty directory in only one of them doesn't matter.
+    std::fs::create_dir(two.path().join("empty")).unwrap();
+
+    let digest = |dir: &Path| hash_dir_contents(dir, &mut |_| true).unwrap();
+    assert_eq!(digest(one.path()), digest(two.path()));
+
+    // Moving content between a name and the file contents changes the digest.
+    let three = tempfile::tempdir().unwrap();
+    write(three.path(), &[("a.txta", "")]);
+    let four = tempfile::tempdir().unwrap();
+    write(four.path(), &[("a.txt", "a")]);
+    assert_ne!(digest(three.path()), digest(four.path()));
+
+    // Filtered out files don't contribute.
+    let filtered = hash_dir_contents(two.path(), &mut |p| p != Path::new("sub")).unwrap();
+    let five = tempfile::tempdir().unwrap();
+    write(five.path(), &[("a.txt", "a")]);
+    assert_eq!(filtered, digest(five.path()));
+}
+
+#[test]
+fn hash_large_file() {
+    // Larger than the read buffer, and not a multiple of it.
+    let data: Vec<u8> = (0..5 * 1024 * 1024 + 3).map(|i| (i % 251) as u8).collect();
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("large");
+    std::fs::write(&path, &data).unwrap();
+    assert_eq!(hash_file(&path).unwrap(), Digest::of(&data));
+}
@@ -1,10 +1,13 @@
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 
 use serde::ser;
 use serde::Serialize;
 use serde_json::{self, json, value::RawValue};
 
 use crate::core::{compiler::CompileMode, PackageId, Target};
+use crate::util::errors::{self};
 
 pub trait Message: ser::Serialize {
     fn reason(&self) -> &str;
@@ -94,6 +97,12 @@ impl<'a> Message for TimingInfo<'a> {
 #[derive(Serialize)]
 pub struct BuildFinished {
     pub success: bool,
+    /// The name of the profile, such as `dev`.
+    pub profile: String,
+    /// What the profile does, such as `unoptimized + debuginfo`.
+    pub profile_description: String,
+    /// Seconds since cargo started, including resolving and downloading.
+    pub elapsed: f64,
 }
 
 impl Message for BuildFinished {
@@ -101,3 +110,71 @@ impl Message for BuildFinished {
         "build-finished"
     }
 }
+
+/// An error and its causes, along with the numeric code attached with
+/// `errors::CodedError` and the fields attached with
+/// `errors::Annotated`, for scripts consuming cargo's output.
+#[derive(Serialize)]
+pub struct ErrorReport {
+    pub message: String,
+    pub causes: Vec<String>,
+    pub code: Option<u32>,
+    /// The fields of the outermost `Annotation` in the chain, if any.
+    pub fields: BTreeMap<String, String>,
+}
+
+impl ErrorReport {
+    pub fn new(err: &anyhow::Error) -> ErrorReport {
+        let fields = match err.downcast_ref::<errors::Annotation>() {
+            Some(annotation) => annotation
+                .fields
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.clone()))
+                .collect(),
+            None => BTreeMap::new(),
+        };
+        ErrorReport {
+            message: err.to_string(),
+            causes: err.chain().skip(1).map(|e| e.to_string()).collect(),
+            code: errors::error_code(err),
+            fields,
+        }
+    }
+}
+
+impl Message for ErrorReport {
+    fn reason(&self) -> &str {
+        "cargo-error"
+    }
+}
+
+/// A warning or error, written to the `-Z diagnostics-stream` file as soon as
+/// it is emitted.
+#[derive(Serialize)]
+pub struct DiagnosticEvent {
+    /// Either `warning` or `error`.
+    pub level: String,
+    pub message: String,
+    /// Seconds since the Unix epoch.
+    pub timestamp: f64,
+}
+
+impl DiagnosticEvent {
+    pub fn new(level: &str, message: String) -> DiagnosticEvent {
+        let timestamp = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map(|d| d.as_secs_f64())
+            .unwrap_or(0.0);
+        DiagnosticEvent {
+            level: level.to_string(),
+            message,
+            timestamp,
+        }
+    }
+}
+
+impl Message for DiagnosticEvent {
+    fn reason(&self) -> &str {
+        "cargo-diagnostic"
+    }
+}
@@ -40,7 +40,7 @@ pub mod diagnostic_server;
 pub mod errors;
 mod flock;
 pub mod graph;
-mod hasher;
+pub mod hasher;
 pub mod hex;
 pub mod important_paths;
 pub mod into_url;
@@ -64,6 +64,10 @@ pub mod toml;
 mod vcs;
 mod workspace;
 
+/// Formats a duration for humans, like `0.42s` or `2m 13s`.
+///
+/// Durations under a minute are shown with hundredths of a second (rounded
+/// down), longer ones in whole minutes and seconds.
 pub fn elapsed(duration: Duration) -> String {
     let secs = duration.as_secs();
 
@@ -78,3 +82,16 @@ pub fn elapsed(duration: Duration) -> String {
 pub fn is_ci() -> bool {
     std::env::var("CI").is_ok() || std::env::var("TF_BUILD").is_ok()
 }
+
+#[test]
+fn test_elapsed() {
+    let ms = Duration::from_millis;
+    assert_eq!(elapsed(ms(0)), "0.00s");
+    assert_eq!(elapsed(ms(9)), "0.00s");
+    assert_eq!(elapsed(ms(420)), "0.42s");
+    assert_eq!(elapsed(ms(3070)), "3.07s");
+    assert_eq!(elapsed(ms(59_999)), "59.99s");
+    assert_eq!(elapsed(ms(60_000)), "1m 00s");
+    assert_eq!(elapsed(ms(133_500)), "2m 13s");
+    assert_eq!(elapsed(ms(3_600_000)), "60m 00s");
+}
@@ -1,6 +1,6 @@
 use anyhow::Error;
 
-use crate::util::errors::{CargoResult, HttpNot200};
+use crate::util::errors::{self, CargoResult, HttpNot200};
 use crate::util::Config;
 
 pub struct Retry<'a> {
@@ -34,6 +34,9 @@ impl<'a> Retry<'a> {
 }
 
 fn maybe_spurious(err: &Error) -> bool {
+    if let Some(retryable) = errors::is_retryable(err) {
+        return retryable;
+    }
     if let Some(git_err) = err.downcast_ref::<git2::Error>() {
         match git_err.class() {
             git2::ErrorClass::Net | git2::ErrorClass::Os => return true,
@@ -135,6 +138,31 @@ fn with_retry_finds_nested_spurious_errors() {
     assert!(result.is_ok())
 }
 
+#[test]
+fn with_retry_respects_explicit_retryable() {
+    use crate::core::Shell;
+    use crate::util::errors::CargoResultExt;
+
+    let config = Config::default().unwrap();
+    *config.shell() = Shell::from_write(Box::new(Vec::new()));
+
+    // A 5xx response would be retried, but the raising code knows better.
+    let error: CargoResult<()> = Err(HttpNot200 {
+        code: 503,
+        url: "Uri".to_string(),
+    }
+    .into());
+    let mut results = vec![Ok(()), error.retryable(false)];
+    let result = with_retry(&config, || results.pop().unwrap());
+    assert!(result.is_err());
+
+    // A plain message isn't considered spurious unless marked as retryable.
+    let error: CargoResult<()> = Err(anyhow::format_err!("lock contended"));
+    let mut results = vec![Ok(()), error.retryable(true)];
+    let result = with_retry(&config, || results.pop().unwrap());
+    assert!(result.is_ok());
+}
+
 #[test]
 fn curle_http2_stream_is_spurious() {
     let code = curl_sys::CURLE_HTTP2_STREAM;
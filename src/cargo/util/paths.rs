@@ -1,10 +1,11 @@
+use std::cmp;
 use std::env;
 use std::ffi::{OsStr, OsString};
 use std::fs::{self, File, OpenOptions};
 use std::io;
 use std::io::prelude::*;
 use std::iter;
-use std::path::{Component, Path, PathBuf};
+use std::path::{Component, Path, PathBuf, Prefix};
 
 use filetime::FileTime;
 
@@ -84,6 +85,62 @@ pub fn normalize_path(path: &Path) -> PathBuf {
     ret
 }
 
+/// Removes the verbatim prefix (`\\?\`) which `fs::canonicalize` adds on
+/// Windows, as many tools, including rustc and linkers, can't handle it.
+///
+/// `\\?\C:\foo` becomes `C:\foo` and `\\?\UNC\server\share` becomes
+/// `\\server\share`. Other paths are returned unchanged.
+pub fn strip_verbatim_prefix(path: &Path) -> PathBuf {
+    let mut components = path.components();
+    let prefix = match components.next() {
+        Some(Component::Prefix(prefix)) => match prefix.kind() {
+            Prefix::VerbatimDisk(disk) => format!("{}:", disk as char),
+            Prefix::VerbatimUNC(server, share) => format!(
+                r"\\{}\{}",
+                server.to_string_lossy(),
+                share.to_string_lossy()
+            ),
+            _ => return path.to_path_buf(),
+        },
+        _ => return path.to_path_buf(),
+    };
+    let mut ret = PathBuf::from(prefix);
+    ret.extend(components);
+    ret
+}
+
+/// Normalizes `path` for comparing and hashing.
+///
+/// In addition to `normalize_path`, this removes any verbatim prefix, and on
+/// Windows uses `\` as the only separator and upper-cases the drive letter,
+/// so the different ways of writing a path there are equal.
+pub fn comparable_path(path: &Path) -> PathBuf {
+    let path = normalize_path(&strip_verbatim_prefix(path));
+    let mut components = path.components();
+    match components.next() {
+        Some(Component::Prefix(prefix)) => match prefix.kind() {
+            Prefix::Disk(disk) => {
+                let mut ret = PathBuf::from(format!("{}:", disk.to_ascii_uppercase() as char));
+                ret.extend(components);
+                ret
+            }
+            _ => path,
+        },
+        _ => path,
+    }
+}
+
+/// Formats `path` for messages, without any verbatim prefix and with the
+/// platform's separator.
+pub fn display_path(path: &Path) -> String {
+    let path = strip_verbatim_prefix(path);
+    if cfg!(windows) {
+        path.components().collect::<PathBuf>().display().to_string()
+    } else {
+        path.display().to_string()
+    }
+}
+
 pub fn resolve_executable(exec: &Path) -> CargoResult<PathBuf> {
     if exec.components().count() == 1 {
         let paths = env::var_os("PATH").ok_or_else(|| anyhow::format_err!("no PATH"))?;
@@ -126,6 +183,22 @@ pub fn write<P: AsRef<Path>, C: AsRef<[u8]>>(path: P, contents: C) -> CargoResul
     fs::write(path, contents.as_ref()).chain_err(|| format!("failed to write `{}`", path.display()))
 }
 
+/// Writes a file by way of a temporary file in the same directory, so that
+/// readers never observe a partially written file.
+pub fn write_atomic<P: AsRef<Path>, C: AsRef<[u8]>>(path: P, contents: C) -> CargoResult<()> {
+    let path = path.as_ref();
+    (|| -> CargoResult<()> {
+        let dir = path.parent().unwrap_or_else(|| Path::new("."));
+        let mut tmp = tempfile::Builder::new()
+            .prefix(".cargo-write")
+            .tempfile_in(dir)?;
+        tmp.write_all(contents.as_ref())?;
+        tmp.persist(path).map_err(|e| e.error)?;
+        Ok(())
+    })()
+    .chain_err(|| format!("failed to write `{}`", path.display()))
+}
+
 pub fn write_if_changed<P: AsRef<Path>, C: AsRef<[u8]>>(path: P, contents: C) -> CargoResult<()> {
     (|| -> CargoResult<()> {
         let contents = contents.as_ref();
@@ -168,6 +241,41 @@ pub fn create<P: AsRef<Path>>(path: P) -> CargoResult<File> {
     File::create(path).chain_err(|| format!("failed to create file `{}`", path.display()))
 }
 
+/// Returns the last `lines` lines of the file at `path`, without reading the
+/// whole file when it's large, such as a log.
+pub fn tail_of(path: &Path, lines: usize) -> CargoResult<String> {
+    const CHUNK: u64 = 8 * 1024;
+    let mut file = open(path)?;
+    let len = file
+        .seek(io::SeekFrom::End(0))
+        .chain_err(|| format!("failed to read `{}`", path.display()))?;
+    let mut buf = Vec::new();
+    let mut start = len;
+    // Read chunks backwards until they hold one more newline than the
+    // number of lines wanted (not counting a trailing one), or the start of
+    // the file is reached.
+    while start > 0 {
+        let read = cmp::min(CHUNK, start);
+        start -= read;
+        let mut chunk = vec![0; read as usize];
+        file.seek(io::SeekFrom::Start(start))
+            .and_then(|_| file.read_exact(&mut chunk))
+            .chain_err(|| format!("failed to read `{}`", path.display()))?;
+        chunk.extend_from_slice(&buf);
+        buf = chunk;
+        let body = match buf.last() {
+            Some(b'\n') => &buf[..buf.len() - 1],
+            _ => &buf[..],
+        };
+        if body.iter().filter(|&&b| b == b'\n').count() >= lines {
+            break;
+        }
+    }
+    let text = String::from_utf8_lossy(&buf);
+    let all = text.lines().collect::<Vec<_>>();
+    Ok(all[all.len().saturating_sub(lines)..].join("\n"))
+}
+
 /// Opens an existing file.
 pub fn open<P: AsRef<Path>>(path: P) -> CargoResult<File> {
     let path = path.as_ref();
@@ -391,14 +499,14 @@ fn _link_or_copy(src: &Path, dst: &Path) -> CargoResult<()> {
         // rapidly creating lots of executables and running them.
         // See https://github.com/rust-lang/cargo/issues/7821 for the
         // gory details.
-        fs::copy(src, dst).map(|_| ())
+        copy_through_temp(src, dst)
     } else {
         fs::hard_link(src, dst)
     };
     link_result
         .or_else(|err| {
             log::debug!("link failed {}. falling back to fs::copy", err);
-            fs::copy(src, dst).map(|_| ())
+            copy_through_temp(src, dst)
         })
         .chain_err(|| {
             format!(
@@ -410,6 +518,19 @@ fn _link_or_copy(src: &Path, dst: &Path) -> CargoResult<()> {
     Ok(())
 }
 
+/// Copies `src` to a temporary file next to `dst` and renames it into place,
+/// so that `dst` never exists partially written, even if cargo is killed in
+/// the middle of the copy.
+fn copy_through_temp(src: &Path, dst: &Path) -> io::Result<()> {
+    let dir = dst.parent().unwrap_or_else(|| Path::new("."));
+    let tmp = tempfile::Builder::new()
+        .prefix(".cargo-copy")
+        .tempfile_in(dir)?;
+    fs::copy(src, tmp.path())?;
+    tmp.persist(dst).map_err(|e| e.error)?;
+    Ok(())
+}
+
 /// Copies a file from one location to another.
 pub fn copy<P: AsRef<Path>, Q: AsRef<Path>>(from: P, to: Q) -> CargoResult<u64> {
     let from = from.as_ref();
@@ -457,3 +578,60 @@ pub fn strip_prefix_canonical<P: AsRef<Path>>(
     let canon_base = safe_canonicalize(base.as_ref());
     canon_path.strip_prefix(canon_base).map(|p| p.to_path_buf())
 }
+
+#[cfg(windows)]
+#[test]
+fn test_comparable_path() {
+    let eq = |a: &str, b: &str| comparable_path(Path::new(a)) == comparable_path(Path::new(b));
+    assert!(eq(r"C:\foo\bar", "C:/foo/bar"));
+    assert!(eq(r"c:\foo\bar", r"C:\foo\bar"));
+    assert!(eq(r"C:\foo\.\baz\..\bar", r"C:\foo\bar"));
+    assert!(eq(r"\\?\C:\foo\bar", r"c:/foo/bar"));
+    assert!(!eq(r"C:\foo\bar", r"D:\foo\bar"));
+    assert_eq!(
+        comparable_path(Path::new("c:/foo/bar")),
+        Path::new(r"C:\foo\bar")
+    );
+}
+
+#[cfg(windows)]
+#[test]
+fn test_strip_verbatim_prefix() {
+    let strip = |p: &str| strip_verbatim_prefix(Path::new(p));
+    assert_eq!(strip(r"\\?\C:\foo\bar"), Path::new(r"C:\foo\bar"));
+    assert_eq!(
+        strip(r"\\?\UNC\server\share\foo"),
+        Path::new(r"\\server\share\foo")
+    );
+    assert_eq!(strip(r"C:\foo"), Path::new(r"C:\foo"));
+    assert_eq!(display_path(Path::new(r"\\?\C:\foo/bar")), r"C:\foo\bar");
+}
+
+#[test]
+fn test_copy_through_temp() {
+    let dir = tempfile::tempdir().unwrap();
+    let src = dir.path().join("src");
+    let dst = dir.path().join("dst");
+    fs::write(&src, "new").unwrap();
+    fs::write(&dst, "old contents").unwrap();
+    copy_through_temp(&src, &dst).unwrap();
+    assert_eq!(fs::read_to_string(&dst).unwrap(), "new");
+    // The temporary file is gone.
+    assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 2);
+}
+
+#[test]
+fn test_tail_of() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("log");
+    let long = (0..5000)
+        .map(|i| format!("line {}\n", i))
+        .collect::<String>();
+    fs::write(&path, &long).unwrap();
+    assert_eq!(tail_of(&path, 2).unwrap(), "line 4998\nline 4999");
+    assert_eq!(tail_of(&path, 0).unwrap(), "");
+    fs::write(&path, "a\nb").unwrap();
+    assert_eq!(tail_of(&path, 5).unwrap(), "a\nb");
+    fs::write(&path, "").unwrap();
+    assert_eq!(tail_of(&path, 5).unwrap(), "");
+}
@@ -1,6 +1,8 @@
-use crate::util::{process_error, read2, CargoResult, CargoResultExt};
+use crate::util::errors::OutputOverflowError;
+use crate::util::{paths, process_error, profile, read2, CargoResult, CargoResultExt};
 use anyhow::bail;
 use jobserver::Client;
+use log::debug;
 use shell_escape::escape;
 use std::collections::BTreeMap;
 use std::env;
@@ -28,6 +30,8 @@ pub struct ProcessBuilder {
     jobserver: Option<Client>,
     /// `true` to include environment variable in display.
     display_env_vars: bool,
+    /// The most `exec_with_streaming` captures of each of stdout and stderr.
+    output_limit: Option<usize>,
 }
 
 impl fmt::Display for ProcessBuilder {
@@ -47,7 +51,7 @@ impl fmt::Display for ProcessBuilder {
             }
         }
 
-        write!(f, "{}", self.program.to_string_lossy())?;
+        write!(f, "{}", paths::display_path(Path::new(&self.program)))?;
 
         for arg in &self.args {
             write!(f, " {}", escape(arg.to_string_lossy()))?;
@@ -142,14 +146,68 @@ impl ProcessBuilder {
         self
     }
 
+    /// (chainable) Limits how many bytes of each of stdout and stderr
+    /// `exec_with_streaming` captures. A process writing more to either is
+    /// killed, and fails with an `OutputOverflowError`.
+    pub fn output_limit(&mut self, limit: usize) -> &mut Self {
+        self.output_limit = Some(limit);
+        self
+    }
+
     /// Enables environment variable display.
     pub fn display_env_vars(&mut self) -> &mut Self {
         self.display_env_vars = true;
         self
     }
 
+    /// Renders the command as a line that can be pasted into a shell to run
+    /// it again, including its working directory and the environment
+    /// variables set on it.
+    ///
+    /// Long commands are split with one environment variable or argument
+    /// per line.
+    pub fn rerun_command(&self) -> String {
+        let mut parts = Vec::new();
+        for (key, val) in self.env.iter() {
+            if let Some(val) = val {
+                let val = escape(val.to_string_lossy());
+                if cfg!(windows) {
+                    parts.push(format!("set {}={}&&", key, val));
+                } else {
+                    parts.push(format!("{}={}", key, val));
+                }
+            }
+        }
+        parts.push(escape(self.program.to_string_lossy()).into_owned());
+        parts.extend(
+            self.args
+                .iter()
+                .map(|a| escape(a.to_string_lossy()).into_owned()),
+        );
+
+        let mut cmd = parts.join(" ");
+        if cmd.len() > 100 {
+            let continuation = if cfg!(windows) {
+                " ^\n    "
+            } else {
+                " \\\n    "
+            };
+            cmd = parts.join(continuation);
+        }
+        match self.get_cwd() {
+            Some(cwd) => {
+                let cwd = escape(cwd.to_string_lossy());
+                let cd = if cfg!(windows) { "cd /d" } else { "cd" };
+                format!("{} {} && {}", cd, cwd, cmd)
+            }
+            None => cmd,
+        }
+    }
+
     /// Runs the process, waiting for completion, and mapping non-success exit codes to an error.
     pub fn exec(&self) -> CargoResult<()> {
+        debug!("executing {}", self);
+        let _p = profile::start("running processes");
         let mut command = self.build_command();
         let exit = command.status().chain_err(|| {
             process_error(&format!("could not execute process {}", self), None, None)
@@ -188,6 +246,8 @@ impl ProcessBuilder {
 
     /// Executes the process, returning the stdio output, or an error if non-zero exit status.
     pub fn exec_with_output(&self) -> CargoResult<Output> {
+        debug!("executing {}", self);
+        let _p = profile::start("running processes");
         let mut command = self.build_command();
 
         let output = command.output().chain_err(|| {
@@ -221,6 +281,8 @@ impl ProcessBuilder {
         on_stderr_line: &mut dyn FnMut(&str) -> CargoResult<()>,
         capture_output: bool,
     ) -> CargoResult<Output> {
+        debug!("executing {}", self);
+        let _p = profile::start("running processes");
         let mut stdout = Vec::new();
         let mut stderr = Vec::new();
 
@@ -230,19 +292,32 @@ impl ProcessBuilder {
             .stdin(Stdio::null());
 
         let mut callback_error = None;
+        let mut overflow = None;
+        let mut seen = [0usize; 2];
         let status = (|| {
             let mut child = cmd.spawn()?;
             let out = child.stdout.take().unwrap();
             let err = child.stderr.take().unwrap();
             read2(out, err, &mut |is_out, data, eof| {
+                if let Some(limit) = self.output_limit {
+                    if seen[is_out as usize] + data.len() > limit {
+                        overflow = Some(if is_out { "stdout" } else { "stderr" });
+                        drop(child.kill());
+                        // Processes spawned by the child may still hold the
+                        // pipes open, so stop reading instead of waiting for
+                        // them to be closed.
+                        return false;
+                    }
+                }
                 let idx = if eof {
                     data.len()
                 } else {
                     match data.iter().rposition(|b| *b == b'\n') {
                         Some(i) => i + 1,
-                        None => return,
+                        None => return true,
                     }
                 };
+                seen[is_out as usize] += idx;
                 {
                     // scope for new_lines
                     let new_lines = if capture_output {
@@ -271,6 +346,7 @@ impl ProcessBuilder {
                 if !capture_output {
                     data.drain(..idx);
                 }
+                true
             })?;
             child.wait()
         })()
@@ -281,6 +357,11 @@ impl ProcessBuilder {
             status,
         };
 
+        if let Some(stream) = overflow {
+            let limit = self.output_limit.unwrap();
+            return Err(OutputOverflowError::new(&self.to_string(), stream, limit).into());
+        }
+
         {
             let to_print = if capture_output { Some(&output) } else { None };
             if let Some(e) = callback_error {
@@ -369,6 +450,7 @@ pub fn process<T: AsRef<OsStr>>(cmd: T) -> ProcessBuilder {
         env: BTreeMap::new(),
         jobserver: None,
         display_env_vars: false,
+        output_limit: None,
     }
 }
 
@@ -376,9 +458,11 @@ pub fn process<T: AsRef<OsStr>>(cmd: T) -> ProcessBuilder {
 mod imp {
     use crate::util::{process_error, ProcessBuilder};
     use crate::CargoResult;
+    use log::debug;
     use std::os::unix::process::CommandExt;
 
     pub fn exec_replace(process_builder: &ProcessBuilder) -> CargoResult<()> {
+        debug!("replacing cargo with {}", process_builder);
         let mut command = process_builder.build_command();
         let error = command.exec();
         Err(anyhow::Error::from(error).context(process_error(
@@ -412,3 +496,31 @@ mod imp {
         process_builder.exec()
     }
 }
+
+#[test]
+fn test_rerun_command() {
+    let mut cmd = process("rustc");
+    cmd.arg("--crate-name").arg("foo");
+    assert_eq!(cmd.rerun_command(), "rustc --crate-name foo");
+
+    cmd.cwd("/some dir").env("CARGO_PKG_NAME", "foo");
+    if cfg!(windows) {
+        assert_eq!(
+            cmd.rerun_command(),
+            "cd /d \"/some dir\" && set CARGO_PKG_NAME=foo&& rustc --crate-name foo"
+        );
+    } else {
+        assert_eq!(
+            cmd.rerun_command(),
+            "cd '/some dir' && CARGO_PKG_NAME=foo rustc --crate-name foo"
+        );
+    }
+
+    let mut cmd = process("rustc");
+    for _ in 0..4 {
+        cmd.arg("--cfg").arg("feature=\"a-very-long-feature-name\"");
+    }
+    let rendered = cmd.rerun_command();
+    assert_eq!(rendered.lines().count(), 9);
+    assert!(rendered.lines().skip(1).all(|l| l.starts_with("    ")));
+}
@@ -1,85 +1,129 @@
+//! A profiler for Cargo's own phases, enabled with the `CARGO_PROFILE`
+//! environment variable.
+//!
+//! Phases are timed with the guards returned by `start`, which nest by scope
+//! on each thread. Phases with the same description under the same parent are
+//! merged, and `print_summary` prints the resulting tree of total durations
+//! and call counts to stderr. The value of `CARGO_PROFILE` is the deepest
+//! level that gets printed, with `0` showing only the outermost phases.
+//!
+//! When `CARGO_PROFILE` isn't set, starting a phase only checks a flag.
+
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::env;
 use std::fmt;
-use std::io::{stdout, StdoutLock, Write};
-use std::iter::repeat;
-use std::mem;
-use std::time;
+use std::io::{self, Write};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 
-thread_local!(static PROFILE_STACK: RefCell<Vec<time::Instant>> = RefCell::new(Vec::new()));
-thread_local!(static MESSAGES: RefCell<Vec<Message>> = RefCell::new(Vec::new()));
+lazy_static::lazy_static! {
+    static ref ENABLED_LEVEL: Option<usize> =
+        env::var("CARGO_PROFILE").ok().and_then(|s| s.parse().ok());
+    static ref TREE: Mutex<Tree> = Mutex::new(Tree::default());
+}
 
-type Message = (usize, u64, String);
+thread_local!(static PROFILE_STACK: RefCell<Vec<usize>> = RefCell::new(Vec::new()));
 
-pub struct Profiler {
+/// All phases seen so far, merged by parent and description.
+#[derive(Default)]
+struct Tree {
+    nodes: Vec<Node>,
+    index: HashMap<(Option<usize>, String), usize>,
+}
+
+struct Node {
     desc: String,
+    parent: Option<usize>,
+    total: Duration,
+    count: u64,
+}
+
+impl Tree {
+    fn node(&mut self, parent: Option<usize>, desc: String) -> usize {
+        let nodes = &mut self.nodes;
+        *self.index.entry((parent, desc.clone())).or_insert_with(|| {
+            nodes.push(Node {
+                desc,
+                parent,
+                total: Duration::new(0, 0),
+                count: 0,
+            });
+            nodes.len() - 1
+        })
+    }
+
+    fn print(&self, parent: Option<usize>, depth: usize, max_depth: usize, out: &mut dyn Write) {
+        if depth > max_depth {
+            return;
+        }
+        for (i, node) in self.nodes.iter().enumerate() {
+            if node.parent != parent {
+                continue;
+            }
+            writeln!(
+                out,
+                "{:10.3}ms {:6}x  {}{}",
+                node.total.as_secs_f64() * 1000.0,
+                node.count,
+                "    ".repeat(depth),
+                node.desc
+            )
+            .expect("printing profiling info to stderr");
+            self.print(Some(i), depth + 1, max_depth, out);
+        }
+    }
+}
+
+/// Times a phase until it's dropped.
+pub struct Profiler {
+    phase: Option<(usize, Instant)>,
 }
 
 fn enabled_level() -> Option<usize> {
-    env::var("CARGO_PROFILE").ok().and_then(|s| s.parse().ok())
+    *ENABLED_LEVEL
 }
 
 pub fn start<T: fmt::Display>(desc: T) -> Profiler {
     if enabled_level().is_none() {
-        return Profiler {
-            desc: String::new(),
-        };
+        return Profiler { phase: None };
     }
 
-    PROFILE_STACK.with(|stack| stack.borrow_mut().push(time::Instant::now()));
+    let parent = PROFILE_STACK.with(|stack| stack.borrow().last().cloned());
+    let node = TREE.lock().unwrap().node(parent, desc.to_string());
+    PROFILE_STACK.with(|stack| stack.borrow_mut().push(node));
 
     Profiler {
-        desc: desc.to_string(),
+        phase: Some((node, Instant::now())),
     }
 }
 
 impl Drop for Profiler {
     fn drop(&mut self) {
-        let enabled = match enabled_level() {
-            Some(i) => i,
+        let (node, start) = match self.phase.take() {
+            Some(phase) => phase,
             None => return,
         };
-
-        let (start, stack_len) = PROFILE_STACK.with(|stack| {
-            let mut stack = stack.borrow_mut();
-            let start = stack.pop().unwrap();
-            (start, stack.len())
-        });
         let duration = start.elapsed();
-        let duration_ms = duration.as_secs() * 1000 + u64::from(duration.subsec_millis());
-
-        let msg = (stack_len, duration_ms, mem::take(&mut self.desc));
-        MESSAGES.with(|msgs| msgs.borrow_mut().push(msg));
 
-        if stack_len == 0 {
-            fn print(lvl: usize, msgs: &[Message], enabled: usize, stdout: &mut StdoutLock<'_>) {
-                if lvl > enabled {
-                    return;
-                }
-                let mut last = 0;
-                for (i, &(l, time, ref msg)) in msgs.iter().enumerate() {
-                    if l != lvl {
-                        continue;
-                    }
-                    writeln!(
-                        stdout,
-                        "{} {:6}ms - {}",
-                        repeat("    ").take(lvl + 1).collect::<String>(),
-                        time,
-                        msg
-                    )
-                    .expect("printing profiling info to stdout");
-
-                    print(lvl + 1, &msgs[last..i], enabled, stdout);
-                    last = i;
-                }
-            }
-            let stdout = stdout();
-            MESSAGES.with(|msgs| {
-                let mut msgs = msgs.borrow_mut();
-                print(0, &msgs, enabled, &mut stdout.lock());
-                msgs.clear();
-            });
-        }
+        let popped = PROFILE_STACK.with(|stack| stack.borrow_mut().pop());
+        debug_assert_eq!(popped, Some(node));
+        let mut tree = TREE.lock().unwrap();
+        let node = &mut tree.nodes[node];
+        node.total += duration;
+        node.count += 1;
     }
 }
+
+/// Prints the tree of phases recorded so far to stderr, if profiling is
+/// enabled.
+pub fn print_summary() {
+    let max_depth = match enabled_level() {
+        Some(i) => i,
+        None => return,
+    };
+    let tree = TREE.lock().unwrap();
+    let stderr = io::stderr();
+    let mut stderr = stderr.lock();
+    tree.print(None, 0, max_depth, &mut stderr);
+}
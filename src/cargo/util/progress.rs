@@ -40,12 +40,14 @@ impl<'cfg> Progress<'cfg> {
     pub fn with_style(name: &str, style: ProgressStyle, cfg: &'cfg Config) -> Progress<'cfg> {
         // report no progress when -q (for quiet) or TERM=dumb are set
         // or if running on Continuous Integration service like Travis where the
-        // output logs get mangled.
+        // output logs get mangled. The same goes for `CARGO_LOG`, whose
+        // messages would otherwise be appended to the progress bar.
         let dumb = match env::var("TERM") {
             Ok(term) => term == "dumb",
             Err(_) => false,
         };
-        if cfg.shell().verbosity() == Verbosity::Quiet || dumb || is_ci() {
+        let logging = env::var_os("CARGO_LOG").is_some();
+        if cfg.shell().verbosity() == Verbosity::Quiet || dumb || logging || is_ci() {
             return Progress { state: None };
         }
 
@@ -1,3 +1,7 @@
+/// Reads the stdout and stderr of a child process as it arrives, passing each
+/// stream's buffer to `data` along with whether the stream has ended.
+///
+/// Reading stops early, dropping the pipes, when `data` returns `false`.
 pub use self::imp::read2;
 
 #[cfg(unix)]
@@ -11,7 +15,7 @@ mod imp {
     pub fn read2(
         mut out_pipe: ChildStdout,
         mut err_pipe: ChildStderr,
-        data: &mut dyn FnMut(bool, &mut Vec<u8>, bool),
+        data: &mut dyn FnMut(bool, &mut Vec<u8>, bool) -> bool,
     ) -> io::Result<()> {
         unsafe {
             libc::fcntl(out_pipe.as_raw_fd(), libc::F_SETFL, libc::O_NONBLOCK);
@@ -61,14 +65,18 @@ mod imp {
                 err_done = true;
                 nfds -= 1;
             }
-            data(false, &mut err, err_done);
+            if !data(false, &mut err, err_done) {
+                return Ok(());
+            }
             if !out_done && fds[0].revents != 0 && handle(out_pipe.read_to_end(&mut out))? {
                 out_done = true;
                 fds[0].fd = err_pipe.as_raw_fd();
                 errfd = 0;
                 nfds -= 1;
             }
-            data(true, &mut out, out_done);
+            if !data(true, &mut out, out_done) {
+                return Ok(());
+            }
         }
         Ok(())
     }
@@ -84,7 +92,8 @@ mod imp {
     use miow::iocp::{CompletionPort, CompletionStatus};
     use miow::pipe::NamedPipe;
     use miow::Overlapped;
-    use winapi::shared::winerror::ERROR_BROKEN_PIPE;
+    use winapi::shared::winerror::{ERROR_BROKEN_PIPE, ERROR_NOT_FOUND};
+    use winapi::um::ioapiset::CancelIoEx;
 
     struct Pipe<'a> {
         dst: &'a mut Vec<u8>,
@@ -96,7 +105,7 @@ mod imp {
     pub fn read2(
         out_pipe: ChildStdout,
         err_pipe: ChildStderr,
-        data: &mut dyn FnMut(bool, &mut Vec<u8>, bool),
+        data: &mut dyn FnMut(bool, &mut Vec<u8>, bool) -> bool,
     ) -> io::Result<()> {
         let mut out = Vec::new();
         let mut err = Vec::new();
@@ -106,25 +115,33 @@ mod imp {
         port.add_handle(1, &err_pipe)?;
 
         unsafe {
-            let mut out_pipe = Pipe::new(out_pipe, &mut out);
-            let mut err_pipe = Pipe::new(err_pipe, &mut err);
+            // Indexed by completion token: stdout, then stderr.
+            let mut pipes = [Pipe::new(out_pipe, &mut out), Pipe::new(err_pipe, &mut err)];
 
-            out_pipe.read()?;
-            err_pipe.read()?;
+            pipes[0].read()?;
+            pipes[1].read()?;
 
             let mut status = [CompletionStatus::zero(), CompletionStatus::zero()];
+            let mut stopped = false;
 
-            while !out_pipe.done || !err_pipe.done {
+            while !pipes[0].done || !pipes[1].done {
                 for status in port.get_many(&mut status, None)? {
-                    if status.token() == 0 {
-                        out_pipe.complete(status);
-                        data(true, out_pipe.dst, out_pipe.done);
-                        out_pipe.read()?;
-                    } else {
-                        err_pipe.complete(status);
-                        data(false, err_pipe.dst, err_pipe.done);
-                        err_pipe.read()?;
+                    let token = status.token();
+                    pipes[token].complete(status);
+                    if stopped {
+                        // A cancelled read has finished with the buffer.
+                        pipes[token].done = true;
+                        continue;
                     }
+                    if !data(token == 0, pipes[token].dst, pipes[token].done) {
+                        // The other pipe's pending read still writes to its
+                        // buffer, so it is cancelled and waited for.
+                        stopped = true;
+                        pipes[token].done = true;
+                        pipes[1 - token].cancel()?;
+                        continue;
+                    }
+                    pipes[token].read()?;
                 }
             }
 
@@ -157,6 +174,21 @@ mod imp {
             }
         }
 
+        unsafe fn cancel(&mut self) -> io::Result<()> {
+            if self.done {
+                return Ok(());
+            }
+            if CancelIoEx(self.pipe.as_raw_handle() as _, self.overlapped.raw()) == 0 {
+                let err = io::Error::last_os_error();
+                if err.raw_os_error() != Some(ERROR_NOT_FOUND as i32) {
+                    return Err(err);
+                }
+                // Nothing is pending, so the buffer isn't written to anymore.
+                self.done = true;
+            }
+            Ok(())
+        }
+
         unsafe fn complete(&mut self, status: &CompletionStatus) {
             let prev = self.dst.len();
             self.dst.set_len(prev + status.bytes_transferred() as usize);
@@ -1,7 +1,7 @@
 //! Helpers for validating and checking names like package and crate names.
 
+use crate::util::errors::{PackageNameError, PackageNameRule};
 use crate::util::CargoResult;
-use anyhow::bail;
 use std::path::Path;
 
 /// Returns `true` if the name contains non-ASCII characters.
@@ -43,46 +43,55 @@ pub fn is_conflicting_artifact_name(name: &str) -> bool {
 /// elsewhere. `cargo new` has a few restrictions, such as checking for
 /// reserved names. crates.io has even more restrictions.
 pub fn validate_package_name(name: &str, what: &str, help: &str) -> CargoResult<()> {
+    let err = |rule| {
+        PackageNameError::new(name, what, rule)
+            .with_suggestion(suggest_package_name(name))
+            .with_help(help)
+    };
     let mut chars = name.chars();
     if let Some(ch) = chars.next() {
         if ch.is_digit(10) {
             // A specific error for a potentially common case.
-            bail!(
-                "the name `{}` cannot be used as a {}, \
-                the name cannot start with a digit{}",
-                name,
-                what,
-                help
-            );
+            return Err(err(PackageNameRule::StartsWithDigit).into());
         }
         if !(unicode_xid::UnicodeXID::is_xid_start(ch) || ch == '_') {
-            bail!(
-                "invalid character `{}` in {}: `{}`, \
-                the first character must be a Unicode XID start character \
-                (most letters or `_`){}",
-                ch,
-                what,
-                name,
-                help
-            );
+            return Err(err(PackageNameRule::InvalidStartChar(ch)).into());
         }
     }
     for ch in chars {
         if !(unicode_xid::UnicodeXID::is_xid_continue(ch) || ch == '-') {
-            bail!(
-                "invalid character `{}` in {}: `{}`, \
-                characters must be Unicode XID characters \
-                (numbers, `-`, `_`, or most letters){}",
-                ch,
-                what,
-                name,
-                help
-            );
+            return Err(err(PackageNameRule::InvalidChar(ch)).into());
         }
     }
     Ok(())
 }
 
+/// Makes a best-effort guess at a valid name close to `name` by replacing
+/// invalid characters with `_`.
+///
+/// Returns `None` if no reasonable suggestion can be made, for example for a
+/// name starting with a digit or one made up only of invalid characters.
+pub fn suggest_package_name(name: &str) -> Option<String> {
+    let suggestion: String = name
+        .trim()
+        .chars()
+        .map(|ch| {
+            if unicode_xid::UnicodeXID::is_xid_continue(ch) || ch == '-' {
+                ch
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let first = suggestion.chars().next()?;
+    if !(unicode_xid::UnicodeXID::is_xid_start(first) || first == '_')
+        || !suggestion.chars().any(|ch| ch.is_alphanumeric())
+    {
+        return None;
+    }
+    Some(suggestion)
+}
+
 // Check the entire path for names reserved in Windows.
 pub fn is_windows_reserved_path(path: &Path) -> bool {
     path.iter()
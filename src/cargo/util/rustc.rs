@@ -103,6 +103,15 @@ impl Rustc {
     pub fn cached_output(&self, cmd: &ProcessBuilder) -> CargoResult<(String, String)> {
         self.cache.lock().unwrap().cached_output(cmd)
     }
+
+    /// Writes the info cache now if it changed, rather than when cargo exits.
+    ///
+    /// The cache is in the target directory, so builds write it while they
+    /// still have the target directory locked, where `cargo clean` can't be
+    /// removing it at the same time.
+    pub fn write_cache(&self) {
+        self.cache.lock().unwrap().write();
+    }
 }
 
 /// It is a well known fact that `rustc` is not the fastest compiler in the
@@ -202,10 +211,8 @@ impl Cache {
             }
         }
     }
-}
 
-impl Drop for Cache {
-    fn drop(&mut self) {
+    fn write(&mut self) {
         if !self.dirty {
             return;
         }
@@ -216,6 +223,13 @@ impl Drop for Cache {
                 Err(e) => warn!("failed to update rustc info cache: {}", e),
             }
         }
+        self.dirty = false;
+    }
+}
+
+impl Drop for Cache {
+    fn drop(&mut self) {
+        self.write();
     }
 }
 
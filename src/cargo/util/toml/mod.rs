@@ -21,8 +21,11 @@ use crate::core::{Dependency, InternedString, Manifest, PackageId, Summary, Targ
 use crate::core::{Edition, EitherManifest, Feature, Features, VirtualManifest, Workspace};
 use crate::core::{GitReference, PackageIdSpec, SourceId, WorkspaceConfig, WorkspaceRootConfig};
 use crate::sources::{CRATES_IO_INDEX, CRATES_IO_REGISTRY};
-use crate::util::errors::{CargoResult, CargoResultExt, ManifestError};
-use crate::util::{self, paths, validate_package_name, Config, IntoUrl};
+use crate::util::errors::{
+    CargoResult, CargoResultExt, ManifestError, PackageNameError, PackageNameRule,
+    UnknownKeyWarning,
+};
+use crate::util::{self, paths, profile, validate_package_name, Config, IntoUrl};
 
 mod targets;
 use self::targets::targets;
@@ -37,11 +40,57 @@ pub fn read_manifest(
         path.display(),
         source_id
     );
+    let _p = profile::start(format!("loading manifest: {}", path.display()));
     let contents = paths::read(path).map_err(|err| ManifestError::new(err, path.into()))?;
 
     do_read_manifest(&contents, path, source_id, config)
         .chain_err(|| format!("failed to parse manifest at `{}`", path.display()))
-        .map_err(|err| ManifestError::new(err, path.into()))
+        .map_err(|err| {
+            let snippet = err
+                .chain()
+                .filter_map(|e| e.downcast_ref::<toml::de::Error>())
+                .find_map(|e| e.line_col())
+                .map(|(line, col)| source_snippet(&contents, line, col));
+            ManifestError::new(err, path.into()).with_snippet(snippet)
+        })
+}
+
+/// Renders the lines of `source` leading up to the 0-based `line` and `col`
+/// with their line numbers, and a caret under `col`:
+///
+/// ```text
+///   |
+/// 2 | name = "foo"
+/// 3 | version =
+///   |          ^
+/// ```
+fn source_snippet(source: &str, line: usize, col: usize) -> String {
+    const CONTEXT_LINES: usize = 2;
+    let first = line.saturating_sub(CONTEXT_LINES);
+    let width = (line + 1).to_string().len();
+    let mut snippet = format!("{:width$} |\n", "", width = width);
+    let mut error_line = "";
+    for (i, text) in source
+        .lines()
+        .enumerate()
+        .skip(first)
+        .take(line + 1 - first)
+    {
+        let numbered = format!("{:>width$} | {}", i + 1, text, width = width);
+        snippet.push_str(numbered.trim_end());
+        snippet.push('\n');
+        error_line = text;
+    }
+    // `col` counts bytes, while the caret has to be placed by characters.
+    let col = error_line.get(..col).map_or(col, |s| s.chars().count());
+    snippet.push_str(&format!(
+        "{:width$} | {:col$}^",
+        "",
+        "",
+        width = width,
+        col = col
+    ));
+    snippet
 }
 
 fn do_read_manifest(
@@ -65,9 +114,12 @@ fn do_read_manifest(
         stringify(&mut key, &path);
         unused.insert(key);
     })?;
+    if let Some(badges) = &manifest.badges {
+        unused.extend(unknown_badge_keys(badges));
+    }
     let add_unused = |warnings: &mut Warnings| {
         for key in unused {
-            warnings.add_warning(format!("unused manifest key: {}", key));
+            warnings.add_warning(UnknownKeyWarning::new(key.clone()).to_string());
             if key == "profiles.debug" {
                 warnings.add_warning("use `[profile.dev]` to configure debug builds".to_string());
             }
@@ -120,6 +172,34 @@ fn do_read_manifest(
     }
 }
 
+/// Returns the dotted paths of the keys in `[badges]` which are not known
+/// to apply to their badge.
+///
+/// Badges themselves are left alone, registries decide which ones they
+/// support and report the others when publishing.
+fn unknown_badge_keys(badges: &BTreeMap<String, BTreeMap<String, String>>) -> Vec<String> {
+    let mut unknown = Vec::new();
+    for (badge, attrs) in badges {
+        let known: &[&str] = match badge.as_str() {
+            "appveyor" => &["repository", "branch", "service", "id", "project_name"],
+            "circle-ci" | "cirrus-ci" | "gitlab" | "travis-ci" | "bitbucket-pipelines" => {
+                &["repository", "branch"]
+            }
+            "azure-devops" => &["project", "pipeline", "build"],
+            "codecov" | "coveralls" => &["repository", "branch", "service"],
+            "is-it-maintained-issue-resolution" | "is-it-maintained-open-issues" => &["repository"],
+            "maintenance" => &["status"],
+            _ => continue,
+        };
+        for key in attrs.keys() {
+            if !known.contains(&key.as_str()) {
+                unknown.push(format!("badges.{}.{}", badge, key));
+            }
+        }
+    }
+    unknown
+}
+
 pub fn parse(toml: &str, file: &Path, config: &Config) -> CargoResult<toml::Value> {
     let first_error = match toml.parse() {
         Ok(ret) => return Ok(ret),
@@ -803,6 +883,8 @@ pub struct TomlProject {
     namespaced_features: Option<bool>,
     #[serde(rename = "default-run")]
     default_run: Option<String>,
+    #[serde(rename = "rust-version")]
+    rust_version: Option<String>,
 
     // Package metadata.
     description: Option<String>,
@@ -1020,7 +1102,7 @@ impl TomlManifest {
 
         let package_name = project.name.trim();
         if package_name.is_empty() {
-            bail!("package name cannot be an empty string")
+            return Err(PackageNameError::new("", "package name", PackageNameRule::Empty).into());
         }
 
         validate_package_name(package_name, "package name", "")?;
@@ -1042,6 +1124,17 @@ impl TomlManifest {
             features.require(Feature::metabuild())?;
         }
 
+        if let Some(rust_version) = &project.rust_version {
+            features.require(Feature::rust_version())?;
+            if parse_rust_version(rust_version).is_none() {
+                bail!(
+                    "`rust-version` must be a version like \"1.40\" or \"1.40.0\", \
+                     but found `{}`",
+                    rust_version
+                );
+            }
+        }
+
         if project.resolver.is_some()
             || me
                 .workspace
@@ -1197,7 +1290,7 @@ impl TomlManifest {
             .as_ref()
             .map(|x| {
                 x.iter()
-                    .map(|(k, v)| (k.as_str(), v.iter().collect()))
+                    .map(|(k, v)| (k.as_str(), v.iter().collect::<Vec<_>>()))
                     .collect()
             })
             .unwrap_or_else(BTreeMap::new);
@@ -1301,6 +1394,7 @@ impl TomlManifest {
             edition,
             project.im_a_teapot,
             project.default_run.clone(),
+            project.rust_version.clone(),
             Rc::clone(me),
             project.metabuild.clone().map(|sov| sov.0),
             resolve_behavior,
@@ -1524,6 +1618,26 @@ impl TomlManifest {
     }
 }
 
+/// Parses a `rust-version` value, which is a version of two or three numbers
+/// like `1.40` or `1.40.0`, without a pre-release or build part.
+pub fn parse_rust_version(rust_version: &str) -> Option<semver::Version> {
+    let parts = rust_version.split('.').collect::<Vec<_>>();
+    if parts.len() < 2 || parts.len() > 3 {
+        return None;
+    }
+    let mut numbers = parts.iter().map(|part| {
+        if part.is_empty() || !part.bytes().all(|b| b.is_ascii_digit()) {
+            None
+        } else {
+            part.parse::<u64>().ok()
+        }
+    });
+    let major = numbers.next()??;
+    let minor = numbers.next()??;
+    let patch = numbers.next().unwrap_or(Some(0))?;
+    Some(semver::Version::new(major, minor, patch))
+}
+
 /// Returns the name of the README file for a `TomlProject`.
 fn readme_for_project(package_root: &Path, project: &TomlProject) -> Option<String> {
     match &project.readme {
@@ -1873,3 +1987,44 @@ impl fmt::Debug for PathValue {
         self.0.fmt(f)
     }
 }
+
+#[test]
+fn test_source_snippet() {
+    let source = "[package]\nname = \"foo\"\n\nversion = 1.0.0\n";
+    assert_eq!(
+        source_snippet(source, 3, 10),
+        "  |\n\
+         2 | name = \"foo\"\n\
+         3 |\n\
+         4 | version = 1.0.0\n  \
+           |           ^"
+    );
+    // The caret is placed by characters, not bytes.
+    assert_eq!(
+        source_snippet("naïve = x", 0, 9),
+        "  |\n1 | naïve = x\n  |         ^"
+    );
+}
+
+#[test]
+fn test_parse_rust_version() {
+    assert_eq!(
+        parse_rust_version("1.40"),
+        Some(semver::Version::new(1, 40, 0))
+    );
+    assert_eq!(
+        parse_rust_version("1.40.2"),
+        Some(semver::Version::new(1, 40, 2))
+    );
+    for bad in &[
+        "1",
+        "1.40.0.1",
+        "1.40.0-nightly",
+        "^1.40",
+        "1..0",
+        "1.x",
+        "",
+    ] {
+        assert_eq!(parse_rust_version(bad), None, "{}", bad);
+    }
+}
@@ -20,7 +20,10 @@ use super::{
 };
 use crate::core::compiler::CrateType;
 use crate::core::{Edition, Feature, Features, Target};
-use crate::util::errors::{CargoResult, CargoResultExt};
+use crate::util::errors::{
+    validate_all, CargoResult, CargoResultExt, CrateNameMismatchError, CrateTypeError,
+    TargetTableError,
+};
 use crate::util::restricted_names;
 
 pub fn targets(
@@ -100,6 +103,8 @@ pub fn targets(
         errors,
     )?);
 
+    validate_bench_names(manifest.bench.as_ref(), &targets)?;
+
     // processing the custom build script
     if let Some(custom_build) = manifest.maybe_custom_build(custom_build, package_root) {
         if metabuild.is_some() {
@@ -149,18 +154,10 @@ fn clean_lib(
 ) -> CargoResult<Option<Target>> {
     let inferred = inferred_lib(package_root);
     let lib = match toml_lib {
-        Some(lib) => {
-            if let Some(ref name) = lib.name {
-                // XXX: other code paths dodge this validation
-                if name.contains('-') {
-                    anyhow::bail!("library target names cannot contain hyphens: {}", name)
-                }
-            }
-            Some(TomlTarget {
-                name: lib.name.clone().or_else(|| Some(package_name.to_owned())),
-                ..lib.clone()
-            })
-        }
+        Some(lib) => Some(TomlTarget {
+            name: lib.name.clone().or_else(|| Some(package_name.to_owned())),
+            ..lib.clone()
+        }),
         None => inferred.as_ref().map(|lib| TomlTarget {
             name: Some(package_name.to_string()),
             path: Some(PathValue(lib.clone())),
@@ -174,6 +171,10 @@ fn clean_lib(
     };
 
     validate_target_name(lib, "library", "lib", warnings)?;
+    validate_all(vec![
+        validate_lib_name(toml_lib),
+        validate_proc_macro_crate_types(format!("library `{}`", lib.name()), lib, warnings),
+    ])?;
 
     let path = match (lib.path.as_ref(), inferred) {
         (Some(path), _) => package_root.join(&path.0),
@@ -198,6 +199,8 @@ fn clean_lib(
         }
     };
 
+    validate_crate_name_attr(&lib.name(), &path, warnings);
+
     // Per the Macros 1.1 RFC:
     //
     // > Initially if a crate is compiled with the `proc-macro` crate type
@@ -220,9 +223,6 @@ fn clean_lib(
                 "library `{}` should only specify `proc-macro = true` instead of setting `crate-type`",
                 lib.name()
             ));
-            if kinds.len() > 1 {
-                anyhow::bail!("cannot mix `proc-macro` crate type with others");
-            }
             vec![CrateType::ProcMacro]
         }
         (_, Some(true), Some(true)) => {
@@ -252,6 +252,8 @@ fn clean_bins(
 ) -> CargoResult<Vec<Target>> {
     let inferred = inferred_bins(package_root, package_name);
 
+    validate_target_tables(toml_bins, "bin", package_root, warnings)?;
+
     let bins = toml_targets_and_inferred(
         toml_bins,
         &inferred,
@@ -353,6 +355,8 @@ fn clean_examples(
 ) -> CargoResult<Vec<Target>> {
     let inferred = infer_from_directory(&package_root.join("examples"));
 
+    validate_target_tables(toml_examples, "example", package_root, warnings)?;
+
     let targets = clean_targets(
         "example",
         "example",
@@ -387,6 +391,36 @@ fn clean_examples(
     Ok(result)
 }
 
+/// Checks that a target with the `proc-macro` crate type has no other crate
+/// types.
+///
+/// A target with `proc-macro = true` and a `crate-type` without `proc-macro`
+/// is built with just those crate types, so it is only warned about.
+fn validate_proc_macro_crate_types(
+    target: String,
+    toml: &TomlTarget,
+    warnings: &mut Vec<String>,
+) -> CargoResult<()> {
+    let kinds = match toml.crate_types() {
+        Some(kinds) => kinds,
+        None => return Ok(()),
+    };
+    if kinds.iter().any(|k| k == "proc-macro") {
+        if kinds.len() > 1 {
+            let others = kinds.iter().filter(|k| *k != "proc-macro").cloned();
+            return Err(CrateTypeError::new(target, others.collect()).into());
+        }
+    } else if toml.proc_macro() == Some(true) {
+        let kinds: Vec<_> = kinds.iter().map(|t| format!("`{}`", t)).collect();
+        warnings.push(format!(
+            "{} sets `proc-macro = true`, which is ignored because `crate-type` is set to {}",
+            target,
+            kinds.join(", ")
+        ));
+    }
+    Ok(())
+}
+
 fn clean_tests(
     features: &Features,
     toml_tests: Option<&Vec<TomlTestTarget>>,
@@ -750,6 +784,119 @@ fn validate_target_name(
     Ok(())
 }
 
+/// Checks that an explicitly set `[lib] name` is a valid crate name.
+fn validate_lib_name(toml_lib: Option<&TomlLibTarget>) -> CargoResult<()> {
+    if let Some(name) = toml_lib.and_then(|lib| lib.name.as_ref()) {
+        // XXX: other code paths dodge this validation
+        if name.contains('-') {
+            anyhow::bail!("library target names cannot contain hyphens: {}", name)
+        }
+    }
+    Ok(())
+}
+
+/// Warns if a `#![crate_name]` attribute in the library's root source file
+/// disagrees with the name cargo will pass to rustc with `--crate-name`.
+///
+/// Only the inner attributes at the top of the file are looked at, and a
+/// mismatch is left for rustc to reject. The warning just explains the
+/// naming rule, and like other manifest warnings is only shown for packages
+/// in the workspace.
+fn validate_crate_name_attr(lib_name: &str, path: &Path, warnings: &mut Vec<String>) {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        // Missing files are reported when compiling.
+        Err(_) => return,
+    };
+    let declared = contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with("//"))
+        .take_while(|line| line.starts_with("#!"))
+        .find_map(|line| {
+            let value = line.strip_prefix("#![crate_name")?;
+            let value = value.trim_start().strip_prefix('=')?.trim_start();
+            let value = value.strip_prefix('"')?;
+            Some(value[..value.find('"')?].to_string())
+        });
+    let crate_name = lib_name.replace("-", "_");
+    if let Some(declared) = declared {
+        if declared != crate_name {
+            let err = CrateNameMismatchError {
+                manifest_name: lib_name.to_string(),
+                crate_name,
+                declared,
+                path: path.to_path_buf(),
+            };
+            warnings.push(err.to_string());
+        }
+    }
+}
+
+/// Checks the explicitly declared `[[target_kind]]` tables of a manifest.
+///
+/// A table without a `name` is a hard error. A table whose `path` does not
+/// exist is only a warning, since the target may never be selected for
+/// compilation (and rustc reports the missing file if it is).
+fn validate_target_tables(
+    toml_targets: Option<&Vec<TomlTarget>>,
+    target_kind: &str,
+    package_root: &Path,
+    warnings: &mut Vec<String>,
+) -> CargoResult<()> {
+    for (index, target) in toml_targets.into_iter().flatten().enumerate() {
+        if target.name.is_none() {
+            return Err(TargetTableError::new(target_kind, index, "`name` is required").into());
+        }
+        if let Some(ref path) = target.path {
+            let path = package_root.join(&path.0);
+            if !path.exists() {
+                let reason = format!(
+                    "`path` points to `{}`, which does not exist",
+                    path.display()
+                );
+                warnings.push(TargetTableError::new(target_kind, index, reason).to_string());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Makes sure an explicitly declared `[[bench]]` is not the same target as a
+/// test or example, that is one with the same name built from the same source
+/// file. Targets which only share a name are fine, since `--bench`, `--test`
+/// and `--example` pick between them.
+fn validate_bench_names(
+    toml_benches: Option<&Vec<TomlBenchTarget>>,
+    targets: &[Target],
+) -> CargoResult<()> {
+    for bench in toml_benches.into_iter().flatten() {
+        let name = bench.name();
+        let bench_path = targets
+            .iter()
+            .find(|t| t.is_bench() && t.name() == name)
+            .and_then(|t| t.src_path().path());
+        let conflict = targets.iter().find(|t| {
+            (t.is_test() || t.is_example()) && t.name() == name && t.src_path().path() == bench_path
+        });
+        if let Some(conflict) = conflict {
+            let kind = if conflict.is_test() {
+                "test"
+            } else {
+                "example"
+            };
+            anyhow::bail!(
+                "the benchmark target `{}` has the same name and path as the {} target `{}`, \
+                 a [[bench]] target must not be the same target as a test or example",
+                name,
+                kind,
+                conflict.name()
+            );
+        }
+    }
+    Ok(())
+}
+
 /// Will check a list of toml targets, and make sure the target names are unique within a vector.
 fn validate_unique_names(targets: &[TomlTarget], target_kind: &str) -> CargoResult<()> {
     let mut seen = HashSet::new();
@@ -626,7 +626,8 @@ fn bad_registry_name() {
 [ERROR] failed to parse manifest at `[CWD]/Cargo.toml`
 
 Caused by:
-  invalid character ` ` in registry name: `bad name`, [..]",
+  invalid character ` ` in registry name: `bad name`, [..]
+help: a valid name would be `bad_name`",
         )
         .run();
 
@@ -643,7 +644,11 @@ Caused by:
             .arg("--registry")
             .arg("bad name")
             .with_status(101)
-            .with_stderr("[ERROR] invalid character ` ` in registry name: `bad name`, [..]")
+            .with_stderr(
+                "\
+[ERROR] invalid character ` ` in registry name: `bad name`, [..]
+help: a valid name would be `bad_name`",
+            )
             .run();
     }
 }
@@ -45,13 +45,7 @@ fn bad2() {
 [ERROR] could not load Cargo configuration
 
 Caused by:
-  failed to load TOML configuration from `[..]config`
-
-Caused by:
-  failed to parse key `http`
-
-Caused by:
-  failed to parse key `proxy`
+  invalid value for key `http.proxy` in configuration file `[..]config`
 
 Caused by:
   found TOML configuration value of unknown type `float`
@@ -164,6 +158,29 @@ Caused by:
         .run();
 }
 
+#[cargo_test]
+fn bad_term_config() {
+    let p = project()
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config",
+            r#"
+            [term]
+            verbose = "yes"
+        "#,
+        )
+        .build();
+    p.cargo("build")
+        .with_stderr(
+            "\
+[WARNING] error in [..].cargo/config: `term.verbose` expected true/false, but found a string
+[COMPILING] foo v0.0.1 ([CWD])
+[FINISHED] [..]
+",
+        )
+        .run();
+}
+
 #[cargo_test]
 fn invalid_global_config() {
     let p = project()
@@ -190,7 +207,7 @@ fn invalid_global_config() {
 [ERROR] could not load Cargo configuration
 
 Caused by:
-  could not parse TOML configuration in `[..]`
+  could not load configuration file `[..]`
 
 Caused by:
   could not parse input as TOML
@@ -443,6 +460,12 @@ Caused by:
 
 Caused by:
   expected a table key, found a newline at line 8 column 23
+
+    |
+  6 |
+  7 |             [target.x86_64-apple-darwin.freetype]
+  8 |             native = {
+    |                       ^
 ",
         )
         .run();
@@ -522,6 +545,48 @@ Caused by:
         .run();
 }
 
+#[cargo_test]
+fn missing_example_path() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+           [package]
+           name = "qqq"
+           version = "0.1.0"
+           authors = ["A <a@a.a>"]
+
+           [[example]]
+           name = "ex"
+           path = "examples/ex.rs"
+
+           [[example]]
+           name = "ex2"
+           path = "examples/missing.rs"
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .file("examples/ex.rs", r#"fn main () -> () {}"#)
+        .build();
+
+    p.cargo("build")
+        .with_stderr(
+            "\
+[WARNING] invalid `[[example]]` table `example[1]`: `path` points to `[..]missing.rs`, which does not exist
+[COMPILING] qqq v0.1.0 ([CWD])
+[FINISHED] [..]
+",
+        )
+        .run();
+
+    p.cargo("build --example ex2")
+        .with_status(101)
+        .with_stderr_contains(
+            "[WARNING] invalid `[[example]]` table `example[1]`: `path` points to `[..]missing.rs`, which does not exist",
+        )
+        .run();
+}
+
 #[cargo_test]
 fn duplicate_bench_names() {
     let p = project()
@@ -559,6 +624,57 @@ Caused by:
         .run();
 }
 
+#[cargo_test]
+fn bench_name_conflicts_with_test() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+           [package]
+           name = "qqq"
+           version = "0.1.0"
+           authors = ["A <a@a.a>"]
+
+           [[bench]]
+           name = "ex"
+           path = "tests/ex.rs"
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .file("tests/ex.rs", r#"fn main () {}"#)
+        .build();
+
+    p.cargo("bench")
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] failed to parse manifest at `[..]`
+
+Caused by:
+  the benchmark target `ex` has the same name and path as the test target `ex`, \
+a [[bench]] target must not be the same target as a test or example
+",
+        )
+        .run();
+
+    // A bench in its own file may share the name of a test.
+    p.change_file(
+        "Cargo.toml",
+        r#"
+           [package]
+           name = "qqq"
+           version = "0.1.0"
+           authors = ["A <a@a.a>"]
+
+           [[bench]]
+           name = "ex"
+           path = "benches/ex.rs"
+        "#,
+    );
+    p.change_file("benches/ex.rs", r#"fn main () {}"#);
+    p.cargo("build --benches").run();
+}
+
 #[cargo_test]
 fn duplicate_deps() {
     let p = project()
@@ -775,6 +891,39 @@ fn unused_keys_in_virtual_manifest() {
         .run();
 }
 
+#[cargo_test]
+fn unused_badge_keys() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.5.0"
+            authors = []
+
+            [badges]
+            travis-ci = { repository = "foo/bar", brnach = "master" }
+            maintenance = { status = "experimental" }
+            some-future-badge = { anything = "goes" }
+
+            [package.metadata.whatever]
+            anything = "goes"
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    p.cargo("build")
+        .with_stderr(
+            "\
+warning: unused manifest key: badges.travis-ci.brnach
+[COMPILING] foo [..]
+[FINISHED] dev [unoptimized + debuginfo] target(s) in [..]
+",
+        )
+        .run();
+}
+
 #[cargo_test]
 fn empty_dependencies() {
     let p = project()
@@ -1392,10 +1541,6 @@ fn bad_target_cfg() {
 [ERROR] error in [..]/foo/.cargo/config: \
 could not load config key `target.cfg(not(target_os = \"none\")).runner`
 
-Caused by:
-  error in [..]/foo/.cargo/config: \
-  could not load config key `target.cfg(not(target_os = \"none\")).runner`
-
 Caused by:
   invalid configuration for key `target.cfg(not(target_os = \"none\")).runner`
 expected a string or array of strings, but found a boolean for \
@@ -1430,7 +1575,7 @@ fn bad_target_links_overrides() {
     p.cargo("check")
         .with_status(101)
         .with_stderr(
-            "[ERROR] Only `-l` and `-L` flags are allowed in target config \
+            "[ERROR] only `-l` and `-L` flags are allowed in target config \
              `target.[..].rustc-flags` (in [..]foo/.cargo/config): `foo`",
         )
         .run();
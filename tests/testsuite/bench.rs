@@ -1629,8 +1629,59 @@ fn json_artifact_includes_executable_for_benchmark() {
                 }
             }
 
-            {"reason": "build-finished", "success": true}
+            {"reason": "build-finished", "success": true, "profile": "bench", "profile_description": "optimized", "elapsed": "{...}"}
         "#,
         )
         .run();
 }
+
+#[cargo_test]
+fn forwards_args_after_double_dash_to_every_bench() {
+    // Cargo adds `--bench` after the user's arguments.
+    let check_args = r#"
+        fn main() {
+            let args: Vec<String> = std::env::args().skip(1).collect();
+            assert_eq!(args, ["a filter", "--verbose", "", "--bench"]);
+            println!("args ok");
+        }
+    "#;
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+
+            [lib]
+            test = false
+            doctest = false
+            bench = false
+
+            [[bench]]
+            name = "a"
+            harness = false
+
+            [[bench]]
+            name = "b"
+            harness = false
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("benches/a.rs", check_args)
+        .file("benches/b.rs", check_args)
+        .build();
+
+    let mut execs = p.cargo("bench -v --");
+    execs
+        .arg("a filter")
+        .arg("--verbose")
+        .arg("")
+        .with_stdout_contains_n("args ok", 2);
+    if cfg!(unix) {
+        execs
+            .with_stderr_contains("[RUNNING] `[..]/a-[..] 'a filter' --verbose '' --bench`")
+            .with_stderr_contains("[RUNNING] `[..]/b-[..] 'a filter' --verbose '' --bench`");
+    }
+    execs.run();
+}
@@ -33,7 +33,16 @@ fn cargo_fail_with_no_stderr() {
         .build();
     p.cargo("build --message-format=json")
         .with_status(101)
-        .with_stderr_does_not_contain("--- stderr")
+        .with_stderr(
+            "\
+[WARNING] path `[..]src/foo.rs` was erroneously implicitly accepted for binary `foo`,
+please set bin.path in Cargo.toml
+[COMPILING] foo v0.5.0 ([CWD])
+[ERROR] could not compile `foo`.
+
+To learn more, run the command again with --verbose.
+",
+        )
         .run();
 }
 
@@ -191,6 +200,12 @@ Caused by:
 
 Caused by:
   invalid number at line 3 column 19
+
+    |
+  1 |
+  2 |             [project]
+  3 |             foo = bar
+    |                   ^
 ",
         )
         .run();
@@ -211,6 +226,10 @@ Caused by:
 
 Caused by:
   invalid number at line 1 column 5
+
+    |
+  1 | a = bar
+    |     ^
 ",
         )
         .run();
@@ -301,6 +320,7 @@ fn cargo_compile_with_invalid_package_name() {
 
 Caused by:
   invalid character `:` in package name: `foo::bar`, [..]
+help: a valid name would be `foo__bar`
 ",
         )
         .run();
@@ -938,6 +958,7 @@ error: failed to parse manifest at `[..]`
 
 Caused by:
   invalid character ` ` in dependency name: `haha this isn't a valid name 🐛`, characters must be Unicode XID characters (numbers, `-`, `_`, or most letters)
+help: a valid name would be `haha_this_isn_t_a_valid_name__`
 ",
         )
         .run();
@@ -959,7 +980,13 @@ fn cargo_compile_with_filename() {
 
     p.cargo("build --bin bin.rs")
         .with_status(101)
-        .with_stderr("[ERROR] no bin target named `bin.rs`")
+        .with_stderr(
+            "\
+[ERROR] no bin target named `bin.rs`
+
+available bin targets:
+    a",
+        )
         .run();
 
     p.cargo("build --bin a.rs")
@@ -968,13 +995,22 @@ fn cargo_compile_with_filename() {
             "\
 [ERROR] no bin target named `a.rs`
 
-<tab>Did you mean `a`?",
+<tab>Did you mean `a`?
+
+available bin targets:
+    a",
         )
         .run();
 
     p.cargo("build --example example.rs")
         .with_status(101)
-        .with_stderr("[ERROR] no example target named `example.rs`")
+        .with_stderr(
+            "\
+[ERROR] no example target named `example.rs`
+
+available example targets:
+    a",
+        )
         .run();
 
     p.cargo("build --example a.rs")
@@ -983,11 +1019,32 @@ fn cargo_compile_with_filename() {
             "\
 [ERROR] no example target named `a.rs`
 
-<tab>Did you mean `a`?",
+<tab>Did you mean `a`?
+
+available example targets:
+    a",
         )
         .run();
 }
 
+#[cargo_test]
+fn build_only_selected_bin() {
+    let p = project()
+        .file("src/lib.rs", "pub fn hello() {}")
+        .file("src/bin/a.rs", "fn main() { foo::hello(); }")
+        .file("src/bin/b.rs", "fn main() { foo::hello(); }")
+        .build();
+
+    p.cargo("build -v --bin a")
+        .with_stderr_contains("[RUNNING] `rustc --crate-name foo [..]")
+        .with_stderr_contains("[RUNNING] `rustc --crate-name a [..]")
+        .with_stderr_does_not_contain("[RUNNING] `rustc --crate-name b [..]")
+        .run();
+
+    assert!(p.bin("a").is_file());
+    assert!(!p.bin("b").is_file());
+}
+
 #[cargo_test]
 fn incompatible_dependencies() {
     Package::new("bad", "0.1.0").publish();
@@ -2064,6 +2121,114 @@ fn simple_staticlib() {
     p.cargo("build").env("CARGO_LOG", "nekoneko=trace").run();
 }
 
+#[cargo_test]
+fn cargo_log_filters_by_module() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("build")
+        .env("CARGO_LOG", "cargo::util::process_builder=debug")
+        .with_stderr_contains("[..]DEBUG cargo::util::process_builder] executing `rustc [..]")
+        .with_stderr_does_not_contain("[..]cargo::core::compiler::fingerprint[..]")
+        .with_stderr_does_not_contain("[..]cargo::core::resolver[..]")
+        .run();
+
+    p.cargo("build")
+        .env("CARGO_LOG", "cargo::core::compiler::fingerprint=debug")
+        .with_stderr_contains(
+            "[..]DEBUG cargo::core::compiler::fingerprint] fingerprint fresh for [..]",
+        )
+        .with_stderr_does_not_contain("[..]cargo::util::process_builder[..]")
+        .with_stderr_contains("[FINISHED] [..]")
+        .run();
+}
+
+#[cargo_test]
+fn cargo_profile_prints_phase_tree() {
+    let p = project()
+        .file("src/lib.rs", "")
+        .file("build.rs", "fn main() {}")
+        .build();
+
+    let output = t!(p
+        .cargo("build")
+        .env("CARGO_PROFILE", "10")
+        .exec_with_output());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+
+    // Lines look like `    12.345ms      3x      description`, with the
+    // description indented by four spaces per level.
+    let phases: Vec<(usize, f64, u64, &str)> = stderr
+        .lines()
+        .filter_map(|line| {
+            let ms_end = line.find("ms ")?;
+            let ms = line[..ms_end].trim().parse().ok()?;
+            let rest = &line[ms_end + 3..];
+            let count_end = rest.find("x  ")?;
+            let count = rest[..count_end].trim().parse().ok()?;
+            let tree = &rest[count_end + 3..];
+            let desc = tree.trim_start_matches(' ');
+            Some(((tree.len() - desc.len()) / 4, ms, count, desc))
+        })
+        .collect();
+
+    let has = |f: &dyn Fn(&str) -> bool| phases.iter().any(|&(_, _, _, desc)| f(desc));
+    assert!(has(&|d| d == "loading config"), "{}", stderr);
+    assert!(
+        has(&|d| d.starts_with("loading manifest: ") && d.ends_with("Cargo.toml")),
+        "{}",
+        stderr
+    );
+    assert!(has(&|d| d == "resolving"), "{}", stderr);
+    assert!(has(&|d| d.starts_with("fingerprint: foo")), "{}", stderr);
+    assert!(has(&|d| d == "running processes"), "{}", stderr);
+
+    for (i, &(depth, ms, count, desc)) in phases.iter().enumerate() {
+        assert!(ms >= 0.0 && count > 0, "{}: {}", desc, stderr);
+        // The direct children of a phase can't take longer than it, up to
+        // rounding of the printed values.
+        let children: Vec<_> = phases[i + 1..]
+            .iter()
+            .take_while(|p| p.0 > depth)
+            .filter(|p| p.0 == depth + 1)
+            .collect();
+        let total: f64 = children.iter().map(|p| p.1).sum();
+        assert!(
+            total <= ms + 0.001 * (children.len() + 1) as f64,
+            "{}: {}",
+            desc,
+            stderr
+        );
+    }
+}
+
+#[cargo_test]
+fn finished_line_counts_diagnostics() {
+    let p = project()
+        .file("src/lib.rs", "pub fn f() { let x = 1; }")
+        .file("src/main.rs", "fn main() { let y = 1; }")
+        .build();
+
+    p.cargo("build")
+        .with_stderr_contains("[..]unused variable: `x`[..]")
+        .with_stderr_contains("[..]unused variable: `y`[..]")
+        .with_stderr_contains(
+            "[FINISHED] dev [unoptimized + debuginfo] target(s) in [..] (0 errors, 2 warnings)",
+        )
+        .run();
+
+    p.change_file("src/lib.rs", "");
+    p.change_file("src/main.rs", "fn main() {}");
+    p.cargo("build")
+        .with_stderr(
+            "\
+[COMPILING] foo [..]
+[FINISHED] dev [unoptimized + debuginfo] target(s) in [..]
+",
+        )
+        .with_stderr_does_not_contain("[..]warning[..]")
+        .run();
+}
+
 #[cargo_test]
 fn staticlib_rlib_and_bin() {
     let p = project()
@@ -2326,7 +2491,7 @@ fn bad_cargo_config() {
 [ERROR] could not load Cargo configuration
 
 Caused by:
-  could not parse TOML configuration in `[..]`
+  could not load configuration file `[..]`
 
 Caused by:
   could not parse input as TOML
@@ -2741,6 +2906,98 @@ Caused by:
         .run();
 }
 
+#[cargo_test]
+fn crate_name_attr_mismatch() {
+    let p = project()
+        .file("Cargo.toml", &basic_manifest("foo-bar", "0.0.1"))
+        .file("src/lib.rs", "#![crate_name = \"foo\"]")
+        .build();
+
+    p.cargo("build")
+        .with_status(101)
+        .with_stderr_contains(
+            "\
+[WARNING] `[..]/foo/src/lib.rs` declares `#![crate_name = \"foo\"]`, but the library is compiled as `foo_bar`
+
+The crate name is the library name `foo-bar` from `Cargo.toml` with hyphens replaced by underscores.
+Remove the attribute, or set `name = \"foo\"` in the `[lib]` table of `Cargo.toml`.
+",
+        )
+        .run();
+
+    p.change_file("src/lib.rs", "#![crate_name = \"foo_bar\"]");
+    p.cargo("build").run();
+}
+
+#[cargo_test]
+fn lib_target_errors_reported_together() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+
+            [lib]
+            name = "foo-bar"
+            crate-type = ["proc-macro", "rlib"]
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("build")
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] failed to parse manifest at `[..]/foo/Cargo.toml`
+
+Caused by:
+  2 errors occurred:
+    library target names cannot contain hyphens: foo-bar
+    library `foo-bar` has the `proc-macro` crate type, which can't be mixed with `rlib`
+      A proc-macro crate can only export procedural macros. \
+Move the rest of the code into a separate library package.
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+// The command is rendered for `cmd.exe` on Windows.
+#[cfg_attr(windows, ignore)]
+fn verbose_compile_error_shows_rerun_command() {
+    let p = project()
+        .file("src/lib.rs", "pub fn f() { missing_function() }")
+        .build();
+
+    p.cargo("build -v")
+        .with_status(101)
+        .with_stderr_contains(
+            "\
+[ERROR] could not compile `foo`.
+
+Caused by:
+  the failed command can be rerun with:
+    cd [..]/foo && [..] \\
+",
+        )
+        .with_stderr_contains("        CARGO_PKG_NAME=foo \\")
+        .with_stderr_contains(
+            "        rustc \\
+        --crate-name \\
+        foo \\",
+        )
+        .with_stderr_contains("  process didn't exit successfully: `rustc [..]` (exit [..]: 1)")
+        .run();
+
+    p.cargo("build")
+        .with_status(101)
+        .with_stderr_does_not_contain("[..]rerun[..]")
+        .run();
+}
+
 #[cargo_test]
 fn rustc_env_var() {
     let p = project().file("src/lib.rs", "").build();
@@ -3189,7 +3446,7 @@ fn compiler_json_error_format() {
         "fresh": $FRESH
     }
 
-    {"reason": "build-finished", "success": true}
+    {"reason": "build-finished", "success": true, "profile": "dev", "profile_description": "unoptimized + debuginfo", "elapsed": "{...}"}
 "#
         .replace("$FRESH", fresh)
     };
@@ -3271,7 +3528,7 @@ fn message_format_json_forward_stderr() {
         "fresh": false
     }
 
-    {"reason": "build-finished", "success": true}
+    {"reason": "build-finished", "success": true, "profile": "release", "profile_description": "optimized", "elapsed": "{...}"}
 "#,
         )
         .run();
@@ -3321,6 +3578,58 @@ fn cargo_build_empty_target() {
         .run();
 }
 
+#[cargo_test]
+fn cargo_build_unknown_target() {
+    let p = project()
+        .file("Cargo.toml", &basic_bin_manifest("foo"))
+        .file("src/main.rs", "fn main() {}")
+        .build();
+
+    p.cargo("build --target not-a-real-target")
+        .with_status(101)
+        .with_stderr_contains(
+            "error: target `not-a-real-target` is not supported by the installed rustc",
+        )
+        .with_stderr_contains("Run `rustc --print target-list` to see the supported targets.[..]")
+        .run();
+}
+
+#[cargo_test]
+fn cargo_build_target_without_std() {
+    // A target rustc knows but that's unlikely to have its standard library
+    // installed.
+    let target = "sparc64-unknown-netbsd";
+    let output = std::process::Command::new("rustc")
+        .arg("--print=sysroot")
+        .output()
+        .unwrap();
+    let sysroot = String::from_utf8(output.stdout).unwrap();
+    if std::path::Path::new(sysroot.trim())
+        .join("lib/rustlib")
+        .join(target)
+        .exists()
+    {
+        return;
+    }
+
+    let p = project()
+        .file("Cargo.toml", &basic_bin_manifest("foo"))
+        .file("src/main.rs", "fn main() {}")
+        .build();
+
+    p.cargo("build --target")
+        .arg(target)
+        .with_status(101)
+        .with_stderr(&format!(
+            "\
+[ERROR] the standard library for target `{0}` is not installed
+If the toolchain is managed by rustup, run `rustup target add {0}` to install it.
+",
+            target
+        ))
+        .run();
+}
+
 #[cargo_test]
 fn build_all_workspace() {
     let p = project()
@@ -4473,7 +4782,10 @@ fn target_filters_workspace() {
             "\
 [ERROR] no example target named `ex`
 
-<tab>Did you mean `ex1`?",
+<tab>Did you mean `ex1`?
+
+available example targets:
+    ex1",
         )
         .run();
 
@@ -4989,3 +5301,86 @@ fn reduced_reproduction_8249() {
     p.cargo("check").run();
     p.cargo("check").run();
 }
+
+#[cargo_test]
+#[cfg(unix)]
+fn build_from_read_only_source() {
+    use std::os::unix::fs::PermissionsExt;
+    use std::path::Path;
+
+    fn set_read_only(path: &Path, read_only: bool) {
+        let (dir_mode, file_mode) = if read_only {
+            (0o555, 0o444)
+        } else {
+            (0o755, 0o644)
+        };
+        for entry in walkdir::WalkDir::new(path) {
+            let entry = entry.unwrap();
+            let mode = if entry.file_type().is_dir() {
+                dir_mode
+            } else {
+                file_mode
+            };
+            fs::set_permissions(entry.path(), fs::Permissions::from_mode(mode)).unwrap();
+        }
+    }
+
+    Package::new("bar", "1.0.0").publish();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+
+            [dependencies]
+            bar = "1.0"
+            "#,
+        )
+        .file("src/main.rs", "fn main() {}")
+        .build();
+    p.cargo("generate-lockfile").run();
+
+    set_read_only(&p.root(), true);
+    if fs::write(p.root().join("probe"), "").is_ok() {
+        // Running as root, which can write anything.
+        set_read_only(&p.root(), false);
+        return;
+    }
+
+    // Dependencies are downloaded into CARGO_HOME, and everything else goes
+    // into the target directory.
+    let target = root().join("elsewhere");
+    p.cargo("build")
+        .env("CARGO_TARGET_DIR", &target)
+        .with_stderr_contains("[COMPILING] foo v0.1.0 ([CWD])")
+        .run();
+    assert!(target.join("debug/foo").is_file());
+    assert!(!p.root().join("target").exists());
+
+    // Changing the dependencies requires a lock file update, which can't be
+    // written.
+    set_read_only(&p.root(), false);
+    p.change_file(
+        "Cargo.toml",
+        r#"
+        [package]
+        name = "foo"
+        version = "0.1.0"
+        "#,
+    );
+    set_read_only(&p.root(), true);
+    p.cargo("build")
+        .env("CARGO_TARGET_DIR", &target)
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] the lock file [CWD]/Cargo.lock needs to be updated but the source directory is read-only
+Check in an up-to-date lock file, or build from a writable copy of the sources to update it.
+",
+        )
+        .run();
+
+    set_read_only(&p.root(), false);
+}
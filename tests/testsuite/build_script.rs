@@ -8,7 +8,7 @@ use std::thread;
 use cargo::util::paths::remove_dir_all;
 use cargo_test_support::paths::CargoPathExt;
 use cargo_test_support::registry::Package;
-use cargo_test_support::{basic_manifest, cross_compile, project};
+use cargo_test_support::{basic_manifest, cross_compile, project, t};
 use cargo_test_support::{rustc_host, sleep_ms, slow_cpu_multiplier};
 
 #[cargo_test]
@@ -43,6 +43,163 @@ Caused by:
         .run();
 }
 
+#[cargo_test]
+fn custom_build_scripts_failed_error_summary() {
+    // Each build script waits for the other one to start, so that both are
+    // running, and fail, together.
+    let build = |me: &str, other: &str| {
+        format!(
+            r#"
+            use std::path::Path;
+            use std::time::Duration;
+
+            fn main() {{
+                let dir = Path::new(env!("CARGO_MANIFEST_DIR")).parent().unwrap();
+                std::fs::write(dir.join("{}-started"), "").unwrap();
+                for _ in 0..600 {{
+                    if dir.join("{}-started").exists() {{
+                        break;
+                    }}
+                    std::thread::sleep(Duration::from_millis(100));
+                }}
+                std::process::exit(101);
+            }}
+            "#,
+            me, other
+        )
+    };
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.5.0"
+
+            [dependencies]
+            a = { path = "a" }
+            b = { path = "b" }
+        "#,
+        )
+        .file("src/main.rs", "fn main() {}")
+        .file("a/Cargo.toml", &basic_manifest("a", "0.5.0"))
+        .file("a/src/lib.rs", "")
+        .file("a/build.rs", &build("a", "b"))
+        .file("b/Cargo.toml", &basic_manifest("b", "0.5.0"))
+        .file("b/src/lib.rs", "")
+        .file("b/build.rs", &build("b", "a"))
+        .build();
+    p.cargo("build -j2 -Zerror-summary")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr_contains("[NOTE] 2 errors occurred, by kind:")
+        .with_stderr_contains(
+            "  compile: 2 (first: failed to run custom build command for `[..] v0.5.0 ([..])`)",
+        )
+        .with_stderr_contains("[ERROR] build failed")
+        .run();
+}
+
+#[cargo_test]
+fn custom_build_script_missing_env_var_hint() {
+    let p = project()
+        .file("src/main.rs", "fn main() {}")
+        .file(
+            "build.rs",
+            r#"fn main() { std::env::var("SDK_PATH").expect("SDK_PATH must be set"); }"#,
+        )
+        .build();
+    p.cargo("build")
+        .env_remove("SDK_PATH")
+        .with_status(101)
+        .with_stderr_contains(
+            "\
+[ERROR] failed to run custom build command for `foo v0.0.1 ([CWD])`
+help: the build script may require the environment variable `SDK_PATH` to be set
+",
+        )
+        .run();
+
+    p.change_file("build.rs", r#"fn main() { std::env::var("X").unwrap(); }"#);
+    p.cargo("build")
+        .env_remove("X")
+        .with_status(101)
+        .with_stderr_contains(
+            "\
+[ERROR] failed to run custom build command for `foo v0.0.1 ([CWD])`
+help: the build script may require an environment variable that is not set
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn custom_build_script_output_limit() {
+    let p = project()
+        .file("src/main.rs", "fn main() {}")
+        .file(
+            "build.rs",
+            r#"fn main() { loop { eprintln!("still going"); } }"#,
+        )
+        .build();
+    p.cargo("build")
+        .env("CARGO_BUILD_SCRIPT_OUTPUT_LIMIT", "1000")
+        .with_status(101)
+        .with_stderr(
+            "\
+[COMPILING] foo v0.0.1 ([CWD])
+[ERROR] failed to run custom build command for `foo v0.0.1 ([CWD])`
+
+Caused by:
+  process `[..]/build-script-build` wrote more than 1000 bytes to stderr, so it was stopped
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn custom_build_script_output_limit_grandchild() {
+    // The build script's own child keeps writing to the inherited pipe after
+    // the build script is killed.
+    let p = project()
+        .file("src/main.rs", "fn main() {}")
+        .file(
+            "build.rs",
+            r#"
+            use std::process::Command;
+
+            fn main() {
+                if std::env::var_os("GRANDCHILD").is_some() {
+                    loop {
+                        eprintln!("still going");
+                    }
+                }
+                Command::new(std::env::current_exe().unwrap())
+                    .env("GRANDCHILD", "1")
+                    .spawn()
+                    .unwrap();
+                loop {
+                    std::thread::sleep(std::time::Duration::from_secs(1));
+                }
+            }
+            "#,
+        )
+        .build();
+    p.cargo("build")
+        .env("CARGO_BUILD_SCRIPT_OUTPUT_LIMIT", "1000")
+        .with_status(101)
+        .with_stderr(
+            "\
+[COMPILING] foo v0.0.1 ([CWD])
+[ERROR] failed to run custom build command for `foo v0.0.1 ([CWD])`
+
+Caused by:
+  process `[..]/build-script-build` wrote more than 1000 bytes to stderr, so it was stopped
+",
+        )
+        .run();
+}
+
 #[cargo_test]
 fn custom_build_env_vars() {
     let p = project()
@@ -189,12 +346,62 @@ fn custom_build_script_wrong_rustc_flags() {
     p.cargo("build")
         .with_status(101)
         .with_stderr_contains(
-            "[ERROR] Only `-l` and `-L` flags are allowed in build script of `foo v0.5.0 ([CWD])`: \
-             `-aaa -bbb`",
+            "[ERROR] invalid line `cargo:rustc-flags=-aaa -bbb` in the output of the build \
+             script of `foo v0.5.0 ([CWD])`: only `-l` and `-L` flags are allowed",
         )
         .run();
 }
 
+#[cargo_test]
+fn custom_build_script_invalid_directives() {
+    let p = project()
+        .file("src/lib.rs", "")
+        .file(
+            "build.rs",
+            r#"fn main() { println!("cargo:rustc-link-lib=static="); }"#,
+        )
+        .build();
+
+    p.cargo("build")
+        .with_status(101)
+        .with_stderr_contains(
+            "[ERROR] invalid line `cargo:rustc-link-lib=static=` in the output of the build \
+             script of `foo v0.0.1 ([CWD])`: the library name is empty",
+        )
+        .run();
+
+    p.change_file(
+        "build.rs",
+        r#"fn main() { println!("cargo:rustc-env=FOO"); }"#,
+    );
+    p.cargo("build")
+        .with_status(101)
+        .with_stderr_contains(
+            "[ERROR] invalid line `cargo:rustc-env=FOO` in the output of the build script of \
+             `foo v0.0.1 ([CWD])`: the variable has no value",
+        )
+        .run();
+
+    p.change_file(
+        "build.rs",
+        r#"fn main() { println!("cargo:rerun-if-changed"); }"#,
+    );
+    p.cargo("build")
+        .with_status(101)
+        .with_stderr_contains(
+            "[ERROR] invalid line `cargo:rerun-if-changed` in the output of the build script of \
+             `foo v0.0.1 ([CWD])`: expected `cargo:KEY=VALUE`",
+        )
+        .run();
+
+    // Unknown keys are metadata for dependent packages.
+    p.change_file(
+        "build.rs",
+        r#"fn main() { println!("cargo:some-key=value"); }"#,
+    );
+    p.cargo("build").run();
+}
+
 #[cargo_test]
 fn custom_build_script_rustc_flags() {
     let p = project()
@@ -446,6 +653,126 @@ also links to native library `a`
         .run();
 }
 
+#[cargo_test]
+fn links_multiple_versions_old_registry() {
+    // Two versions of one `-sys` package, with the old links validator.
+    for version in &["0.1.0", "0.2.0"] {
+        Package::new("bar-sys", version)
+            .file(
+                "Cargo.toml",
+                &format!(
+                    r#"
+                    [package]
+                    name = "bar-sys"
+                    version = "{}"
+                    links = "bar"
+                    "#,
+                    version
+                ),
+            )
+            .file("build.rs", "fn main() {}")
+            .file("src/lib.rs", "")
+            .publish();
+    }
+    Package::new("baz", "0.1.0").dep("bar-sys", "0.2").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+
+            [dependencies]
+            bar-sys = "0.1"
+            baz = "0.1"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("build")
+        .with_status(101)
+        .with_stderr_contains(
+            "\
+[ERROR] multiple versions of package `bar-sys` were selected, \
+    but only one can be built because it links to native library `bar`
+
+package `bar-sys v0.1.0`
+    ... which is depended on by `foo v0.1.0 ([..]foo)`
+
+package `bar-sys v0.2.0`
+    ... which is depended on by `baz v0.1.0`
+    ... which is depended on by `foo v0.1.0 ([..]foo)`
+
+Update the dependencies so that they require the same version of `bar-sys`.
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn links_multiple_versions() {
+    // Two versions of one `-sys` package, caught by the resolver.
+    for version in &["0.1.0", "0.2.0"] {
+        Package::new("bar-sys", version)
+            .links("bar")
+            .file(
+                "Cargo.toml",
+                &format!(
+                    r#"
+                    [package]
+                    name = "bar-sys"
+                    version = "{}"
+                    links = "bar"
+                    "#,
+                    version
+                ),
+            )
+            .file("build.rs", "fn main() {}")
+            .file("src/lib.rs", "")
+            .publish();
+    }
+    Package::new("baz", "0.1.0").dep("bar-sys", "0.2").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+
+            [dependencies]
+            bar-sys = "0.1"
+            baz = "0.1"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("build")
+        .with_status(101)
+        .with_stderr(
+            "\
+[UPDATING] `[..]` index
+[ERROR] multiple versions of package `bar-sys` were selected, \
+    but only one can be built because it links to native library `bar`
+
+package `bar-sys v0.1.0`
+    ... which is depended on by `foo v0.1.0 ([..]foo)`
+
+package `bar-sys v0.2.0`
+    ... which is depended on by `baz v0.1.0`
+    ... which is depended on by `foo v0.1.0 ([..]foo)`
+
+Update the dependencies so that they require the same version of `bar-sys`.
+",
+        )
+        .run();
+}
+
 #[cargo_test]
 fn links_duplicates_deep_dependency() {
     // this tests that the links_duplicates are caught at resolver time
@@ -700,6 +1027,8 @@ fn only_rerun_build_script() {
     p.cargo("build -v")
         .with_stderr(
             "\
+[DIRTY] foo v0.5.0 ([..]) lib: dependency `build_script_build` was rebuilt
+[DIRTY] foo v0.5.0 ([..]) custom-build: precalculated components have changed: [..]
 [COMPILING] foo v0.5.0 ([CWD])
 [RUNNING] `[..]/build-script-build`
 [RUNNING] `rustc --crate-name foo [..]`
@@ -803,9 +1132,12 @@ fn testing_and_such() {
     p.root().move_into_the_past();
 
     println!("test");
+    // The two roots reach the build script and the library in either order.
     p.cargo("test -vj1")
-        .with_stderr(
+        .with_stderr_unordered(
             "\
+[DIRTY] foo v0.5.0 ([CWD]) custom-build: precalculated components have changed: [..]
+[DIRTY] foo v0.5.0 ([CWD]) lib: dependency `build_script_build` was rebuilt
 [COMPILING] foo v0.5.0 ([CWD])
 [RUNNING] `[..]/build-script-build`
 [RUNNING] `rustc --crate-name foo [..]`
@@ -1067,12 +1399,14 @@ fn build_deps_not_for_normal() {
         .arg(&target)
         .with_status(101)
         .with_stderr_contains("[..]can't find crate for `aaaaa`[..]")
+        .with_stderr_contains("  process didn't exit successfully: [..]")
         .with_stderr_contains(
             "\
 [ERROR] could not compile `foo`.
 
 Caused by:
-  process didn't exit successfully: [..]
+  the failed command can be rerun with:
+[..]
 ",
         )
         .run();
@@ -1203,6 +1537,9 @@ fn out_dir_is_preserved() {
     p.cargo("build -v")
         .with_stderr(
             "\
+[DIRTY] foo v0.5.0 ([..]) lib: dependency `build_script_build` was rebuilt
+[DIRTY] foo v0.5.0 ([..]) custom-build: dependency `build_script_build` was rebuilt
+[DIRTY] foo v0.5.0 ([..]) custom-build: source file `build.rs` changed
 [COMPILING] foo [..]
 [RUNNING] `rustc --crate-name build_script_build [..]
 [RUNNING] `[..]/build-script-build`
@@ -1227,6 +1564,8 @@ fn out_dir_is_preserved() {
     p.cargo("build -v")
         .with_stderr(
             "\
+[DIRTY] foo v0.5.0 ([..]) lib: dependency `build_script_build` was rebuilt
+[DIRTY] foo v0.5.0 ([..]) custom-build: [..]
 [COMPILING] foo [..]
 [RUNNING] `[..]build-script-build`
 [RUNNING] `rustc --crate-name foo [..]
@@ -2488,6 +2827,8 @@ fn changing_an_override_invalidates() {
     p.cargo("build -v")
         .with_stderr(
             "\
+[DIRTY] foo v0.5.0 ([..]) lib: dependency `build_script_build` was rebuilt
+[DIRTY] foo v0.5.0 ([..]) custom-build: precalculated components have changed: [..]
 [COMPILING] foo v0.5.0 ([..]
 [RUNNING] `rustc [..] -L native=bar`
 [FINISHED] dev [unoptimized + debuginfo] target(s) in [..]
@@ -2635,6 +2976,8 @@ fn rebuild_only_on_explicit_paths() {
     p.cargo("build -v")
         .with_stderr(
             "\
+[DIRTY] foo v0.5.0 ([..]) lib: dependency `build_script_build` was rebuilt
+[DIRTY] foo v0.5.0 ([..]) custom-build: [..]
 [COMPILING] foo v0.5.0 ([..])
 [RUNNING] `[..]/build-script-build`
 [RUNNING] `rustc [..] src/lib.rs [..]`
@@ -2653,6 +2996,8 @@ fn rebuild_only_on_explicit_paths() {
     p.cargo("build -v")
         .with_stderr(
             "\
+[DIRTY] foo v0.5.0 ([..]) lib: dependency `build_script_build` was rebuilt
+[DIRTY] foo v0.5.0 ([..]) custom-build: [..]
 [COMPILING] foo v0.5.0 ([..])
 [RUNNING] `[..]/build-script-build`
 [RUNNING] `rustc [..] src/lib.rs [..]`
@@ -2691,6 +3036,8 @@ fn rebuild_only_on_explicit_paths() {
     p.cargo("build -v")
         .with_stderr(
             "\
+[DIRTY] foo v0.5.0 ([..]) lib: dependency `build_script_build` was rebuilt
+[DIRTY] foo v0.5.0 ([..]) custom-build: [..]
 [COMPILING] foo v0.5.0 ([..])
 [RUNNING] `[..]/build-script-build`
 [RUNNING] `rustc [..] src/lib.rs [..]`
@@ -2705,6 +3052,8 @@ fn rebuild_only_on_explicit_paths() {
     p.cargo("build -v")
         .with_stderr(
             "\
+[DIRTY] foo v0.5.0 ([..]) lib: dependency `build_script_build` was rebuilt
+[DIRTY] foo v0.5.0 ([..]) custom-build: [..]
 [COMPILING] foo v0.5.0 ([..])
 [RUNNING] `[..]/build-script-build`
 [RUNNING] `rustc [..] src/lib.rs [..]`
@@ -4034,3 +4383,77 @@ Caused by:
     // Restore permissions so that the directory can be deleted.
     fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
 }
+
+#[cargo_test]
+fn build_script_scan_symlinks() {
+    // Build scripts without `rerun-if-changed` scan the whole package, which
+    // follows symlinks but shouldn't loop on cycles or fail on broken links.
+    #[cfg(unix)]
+    use std::os::unix::fs::symlink;
+    #[cfg(windows)]
+    use std::os::windows::fs::symlink_dir as symlink;
+
+    if !cargo_test_support::symlink_supported() {
+        return;
+    }
+
+    let p = project()
+        .file("src/lib.rs", "")
+        .file("build.rs", "fn main() {}")
+        .file("shared/data.txt", "some data")
+        .symlink("shared/data.txt", "data.txt")
+        .symlink_dir("shared", "shared/loop")
+        .build();
+    t!(symlink("nowhere", &p.root().join("dangling")));
+
+    p.cargo("build")
+        .with_stderr_contains(
+            "[WARNING] skipping symbolic link `[..]loop` which points to one of \
+             its parent directories and would form a cycle",
+        )
+        .with_stderr_contains(
+            "[WARNING] symbolic link `[..]dangling` points to `nowhere`, which does not exist",
+        )
+        .with_stderr_contains("[FINISHED] [..]")
+        .run();
+    p.cargo("build")
+        .with_stderr_does_not_contain("[COMPILING] [..]")
+        .run();
+}
+
+#[cargo_test]
+fn build_script_in_non_ascii_dir() {
+    // Paths with spaces and non-ASCII characters make it through to the build
+    // script, to rustc and back into the output of the build script.
+    let p = project()
+        .at("with spaces/pröject 项目")
+        .file(
+            "src/main.rs",
+            r#"
+                include!(concat!(env!("OUT_DIR"), "/hello.rs"));
+                fn main() {
+                    assert!(env!("CARGO_MANIFEST_DIR").ends_with("pröject 项目"));
+                    println!("{}", message());
+                }
+            "#,
+        )
+        .file(
+            "build.rs",
+            r#"
+                use std::path::PathBuf;
+                fn main() {
+                    let out = PathBuf::from(std::env::var_os("OUT_DIR").unwrap());
+                    std::fs::write(
+                        out.join("hello.rs"),
+                        "fn message() -> &'static str { \"héllo\" }",
+                    ).unwrap();
+                    println!("cargo:rustc-link-search=native={}", out.display());
+                    println!("cargo:rerun-if-changed=build.rs");
+                }
+            "#,
+        )
+        .build();
+
+    p.cargo("run").with_stdout("héllo").run();
+    p.cargo("build").with_stderr("[FINISHED] [..]").run();
+}
@@ -9,6 +9,18 @@ fn as_str(bytes: &[u8]) -> &str {
     std::str::from_utf8(bytes).expect("valid utf-8")
 }
 
+/// Blanks out the `elapsed` time of the `build-finished` message, which
+/// differs between runs.
+fn without_elapsed(output: &str) -> String {
+    match output.find("\"elapsed\":") {
+        Some(start) => {
+            let end = start + output[start..].find('}').unwrap();
+            format!("{}\"elapsed\":0{}", &output[..start], &output[end..])
+        }
+        None => output.to_string(),
+    }
+}
+
 #[cargo_test]
 fn simple() {
     // A simple example that generates two warnings (unused functions).
@@ -166,7 +178,7 @@ fn cached_as_json() {
         .exec_with_output()
         .expect("cargo to run");
     assert!(cargo_output.status.success());
-    let orig_cargo_out = as_str(&cargo_output.stdout);
+    let orig_cargo_out = without_elapsed(as_str(&cargo_output.stdout));
     assert!(orig_cargo_out.contains("compiler-message"));
     p.cargo("clean").run();
 
@@ -175,7 +187,10 @@ fn cached_as_json() {
         .cargo("check --message-format=json")
         .exec_with_output()
         .expect("cargo to run");
-    assert_eq!(as_str(&cargo_output1.stdout), orig_cargo_out);
+    assert_eq!(
+        without_elapsed(as_str(&cargo_output1.stdout)),
+        orig_cargo_out
+    );
 
     // Check JSON output, fresh.
     let cargo_output2 = p
@@ -183,7 +198,8 @@ fn cached_as_json() {
         .exec_with_output()
         .expect("cargo to run");
     // The only difference should be this field.
-    let fix_fresh = as_str(&cargo_output2.stdout).replace("\"fresh\":true", "\"fresh\":false");
+    let fix_fresh =
+        without_elapsed(as_str(&cargo_output2.stdout)).replace("\"fresh\":true", "\"fresh\":false");
     assert_eq!(fix_fresh, orig_cargo_out);
 }
 
@@ -7,9 +7,9 @@ use std::path::{Path, PathBuf};
 use std::process::Stdio;
 use std::str;
 
-use cargo_test_support::cargo_process;
 use cargo_test_support::paths::{self, CargoPathExt};
 use cargo_test_support::registry::Package;
+use cargo_test_support::{assert_process, cargo_process};
 use cargo_test_support::{basic_bin_manifest, basic_manifest, cargo_exe, project, Project};
 
 #[cfg_attr(windows, allow(dead_code))]
@@ -132,6 +132,31 @@ fn list_command_resolves_symlinks() {
     );
 }
 
+#[cargo_test]
+fn assert_process_list() {
+    let mut cmd = cargo_process("--list").build_command();
+    assert_process(
+        &mut cmd,
+        0,
+        &[
+            "Installed Commands:\n",
+            "\n    build                Compile a local package and all of its dependencies\n",
+        ],
+    );
+}
+
+#[cargo_test]
+fn assert_process_failure_status() {
+    let mut cmd = cargo_process("no-such-command").build_command();
+    let output = assert_process(&mut cmd, 101, &[]);
+    let stderr = str::from_utf8(&output.stderr).unwrap();
+    assert!(
+        stderr.contains("no such subcommand: `no-such-command`"),
+        "unexpected stderr: {}",
+        stderr
+    );
+}
+
 #[cargo_test]
 fn find_closest_biuld_to_build() {
     cargo_process("biuld")
@@ -234,6 +259,37 @@ fn displays_subcommand_on_error() {
         .run();
 }
 
+#[cargo_test]
+fn unexpected_flag_without_subcommand() {
+    cargo_process("--some-flag")
+        .with_status(1)
+        .with_stderr(
+            "\
+[ERROR] unexpected argument `--some-flag`, but no subcommand was given
+
+Usage:
+    cargo [OPTIONS] [SUBCOMMAND]
+
+Run `cargo --list` to see the available subcommands, or `cargo --help` for more information.
+",
+        )
+        .run();
+
+    // The value of a global option is not a subcommand.
+    cargo_process("--color never --some-flag")
+        .with_status(1)
+        .with_stderr_contains(
+            "[ERROR] unexpected argument `--some-flag`, but no subcommand was given",
+        )
+        .run();
+
+    // A bad flag after a subcommand is reported by that subcommand.
+    cargo_process("build --some-flag")
+        .with_status(1)
+        .with_stderr_does_not_contain("[..]no subcommand was given[..]")
+        .run();
+}
+
 #[cargo_test]
 fn override_cargo_home() {
     let root = paths::root();
@@ -0,0 +1,174 @@
+//! Tests for the `cargo config` command.
+
+use std::fs;
+
+use cargo_test_support::{paths, project, Project};
+
+/// A project with a config file shadowing some values of the user config.
+fn layered_project() -> Project {
+    let home_config = paths::home().join(".cargo/config");
+    fs::create_dir_all(home_config.parent().unwrap()).unwrap();
+    fs::write(
+        &home_config,
+        "\
+[build]
+jobs = 1
+rustflags = ['--user']
+
+[term]
+verbose = false
+",
+    )
+    .unwrap();
+    project()
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config",
+            "\
+[build]
+jobs = 2
+rustflags = ['--project']
+",
+        )
+        .build()
+}
+
+#[cargo_test]
+fn gated() {
+    let p = project().file("src/lib.rs", "").build();
+    p.cargo("config get build.jobs")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] the `cargo config` command is unstable, pass `-Z unstable-options` to enable it
+See https://github.com/rust-lang/cargo/issues/9301 for more information about the `cargo config` command.
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn get_shadowed_key() {
+    let p = layered_project();
+
+    p.cargo("config get build.jobs -Zunstable-options")
+        .masquerade_as_nightly_cargo()
+        .with_stdout("build.jobs = 2")
+        .with_stderr("")
+        .run();
+
+    p.cargo("config get build.jobs -v -Zunstable-options")
+        .masquerade_as_nightly_cargo()
+        .with_stdout("build.jobs = 2 # [ROOT]/foo/.cargo/config")
+        .run();
+
+    p.cargo("config get build.jobs -v -Zunstable-options")
+        .env("CARGO_BUILD_JOBS", "3")
+        .masquerade_as_nightly_cargo()
+        .with_stdout("build.jobs = 3 # environment variable `CARGO_BUILD_JOBS`")
+        .run();
+
+    // Values only set in the user config are found too.
+    p.cargo("config get term.verbose -v -Zunstable-options")
+        .masquerade_as_nightly_cargo()
+        .with_stdout("term.verbose = false # [ROOT]/home/.cargo/config")
+        .run();
+}
+
+#[cargo_test]
+fn get_merged_list() {
+    let p = layered_project();
+
+    p.cargo("config get build.rustflags -Zunstable-options")
+        .masquerade_as_nightly_cargo()
+        .with_stdout(r#"build.rustflags = ["--project", "--user"]"#)
+        .run();
+
+    p.cargo("config get build.rustflags -v -Zunstable-options")
+        .masquerade_as_nightly_cargo()
+        .with_stdout(
+            r#"build.rustflags = [
+    "--project", # [ROOT]/foo/.cargo/config
+    "--user", # [ROOT]/home/.cargo/config
+]"#,
+        )
+        .run();
+}
+
+#[cargo_test]
+fn get_table() {
+    let p = layered_project();
+
+    p.cargo("config get build -Zunstable-options")
+        .env("CARGO_BUILD_JOBS", "3")
+        .masquerade_as_nightly_cargo()
+        .with_stdout(
+            r#"build.jobs = 3
+build.rustflags = ["--project", "--user"]"#,
+        )
+        .run();
+}
+
+#[cargo_test]
+fn get_everything() {
+    let p = layered_project();
+
+    p.cargo("config get -Zunstable-options")
+        .env("CARGO_BUILD_JOBS", "3")
+        .masquerade_as_nightly_cargo()
+        .with_stdout_contains("build.jobs = 3 # environment variable `CARGO_BUILD_JOBS`")
+        .with_stdout_contains("    \"--user\", # [ROOT]/home/.cargo/config")
+        .with_stdout_contains("term.verbose = false # [ROOT]/home/.cargo/config")
+        .with_stdout_contains("# The following environment variables may affect the loaded values.")
+        .with_stdout_contains("# CARGO_BUILD_JOBS=3")
+        .run();
+}
+
+#[cargo_test]
+fn unknown_key() {
+    let p = layered_project();
+
+    p.cargo("config get build.nope -Zunstable-options")
+        .masquerade_as_nightly_cargo()
+        .with_status(1)
+        .with_stdout("")
+        .with_stderr("[ERROR] config value `build.nope` is not set")
+        .run();
+}
+
+#[cargo_test]
+fn secrets_are_redacted() {
+    let p = project().file("src/lib.rs", "").build();
+    let credentials = paths::home().join(".cargo/credentials");
+    fs::create_dir_all(credentials.parent().unwrap()).unwrap();
+    fs::write(
+        &credentials,
+        "\
+[registry]
+token = 'sekrit'
+
+[registries.alt]
+token = 'sekrit2'
+",
+    )
+    .unwrap();
+
+    p.cargo("config get registry.token -Zunstable-options")
+        .masquerade_as_nightly_cargo()
+        .with_stdout(r#"registry.token = "[REDACTED]""#)
+        .run();
+
+    p.cargo("config get registry.token --show-secrets -Zunstable-options")
+        .masquerade_as_nightly_cargo()
+        .with_stdout(r#"registry.token = "sekrit""#)
+        .run();
+
+    p.cargo("config get -Zunstable-options")
+        .env("CARGO_REGISTRY_TOKEN", "sekrit3")
+        .masquerade_as_nightly_cargo()
+        .with_stdout_contains(r#"registries.alt.token = "[REDACTED]" # [..]credentials"#)
+        .with_stdout_contains("# CARGO_REGISTRY_TOKEN=[REDACTED]")
+        .with_stdout_does_not_contain("[..]sekrit[..]")
+        .run();
+}
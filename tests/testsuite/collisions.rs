@@ -45,16 +45,14 @@ fn collision_dylib() {
         .file("b/src/lib.rs", "")
         .build();
 
-    // `j=1` is required because on Windows you'll get an error due to
-    // two processes writing to the file at the same time.
-    p.cargo("build -j=1")
-        .with_stderr_contains(&format!("\
-[WARNING] output filename collision.
+    p.cargo("build")
+        .with_status(101)
+        .with_stderr(&format!("\
+[ERROR] output filename collision.
 The lib target `a` in package `b v1.0.0 ([..]/foo/b)` has the same output filename as the lib target `a` in package `a v1.0.0 ([..]/foo/a)`.
 Colliding filename is: [..]/foo/target/debug/deps/{}a{}
 The targets should have unique names.
-Consider changing their names to be unique or compiling them separately.
-This may become a hard error in the future; see <https://github.com/rust-lang/cargo/issues/6313>.
+Consider renaming one of them, for example with the `name` key of its target table in `Cargo.toml`.
 ", env::consts::DLL_PREFIX, env::consts::DLL_SUFFIX))
         .run();
 }
@@ -76,20 +74,79 @@ fn collision_example() {
         .file("b/examples/ex1.rs", "fn main() {}")
         .build();
 
-    // `j=1` is required because on Windows you'll get an error due to
-    // two processes writing to the file at the same time.
-    p.cargo("build --examples -j=1")
-        .with_stderr_contains("\
-[WARNING] output filename collision.
+    p.cargo("build --examples")
+        .with_status(101)
+        .with_stderr("\
+[ERROR] output filename collision.
 The example target `ex1` in package `b v1.0.0 ([..]/foo/b)` has the same output filename as the example target `ex1` in package `a v1.0.0 ([..]/foo/a)`.
 Colliding filename is: [..]/foo/target/debug/examples/ex1[EXE]
 The targets should have unique names.
-Consider changing their names to be unique or compiling them separately.
-This may become a hard error in the future; see <https://github.com/rust-lang/cargo/issues/6313>.
+Consider renaming one of them, for example with the `name` key of its target table in `Cargo.toml`.
 ")
         .run();
 }
 
+#[cargo_test]
+fn no_collision_diamond() {
+    // A dylib reached through two paths is a single unit, and must not be
+    // reported as colliding with itself.
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+
+            [dependencies]
+            b = { path = "b" }
+            c = { path = "c" }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            "b/Cargo.toml",
+            r#"
+            [package]
+            name = "b"
+            version = "0.1.0"
+
+            [dependencies]
+            utils = { path = "../utils" }
+            "#,
+        )
+        .file("b/src/lib.rs", "")
+        .file(
+            "c/Cargo.toml",
+            r#"
+            [package]
+            name = "c"
+            version = "0.1.0"
+
+            [dependencies]
+            utils = { path = "../utils" }
+            "#,
+        )
+        .file("c/src/lib.rs", "")
+        .file(
+            "utils/Cargo.toml",
+            r#"
+            [package]
+            name = "utils"
+            version = "0.1.0"
+
+            [lib]
+            crate-type = ["dylib", "rlib"]
+            "#,
+        )
+        .file("utils/src/lib.rs", "")
+        .build();
+
+    p.cargo("build")
+        .with_stderr_does_not_contain("[..]collision[..]")
+        .run();
+}
+
 #[cargo_test]
 // --out-dir and examples are currently broken on MSVC and apple.
 // See https://github.com/rust-lang/cargo/issues/7493
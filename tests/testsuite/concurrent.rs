@@ -5,6 +5,7 @@ use std::net::TcpListener;
 use std::process::Stdio;
 use std::sync::mpsc::channel;
 use std::thread;
+use std::time::Duration;
 use std::{env, str};
 
 use cargo_test_support::cargo_process;
@@ -349,6 +350,80 @@ fn same_project() {
     execs().run_output(&b);
 }
 
+#[cargo_test]
+fn build_and_test_same_project() {
+    let p = project()
+        .file("src/main.rs", "fn main() { println!(\"hello\"); }")
+        .file("src/lib.rs", "#[test] fn t() {}")
+        .build();
+
+    let mut a = p.cargo("build").build_command();
+    let mut b = p.cargo("test").build_command();
+
+    a.stdout(Stdio::piped()).stderr(Stdio::piped());
+    b.stdout(Stdio::piped()).stderr(Stdio::piped());
+
+    let a = a.spawn().unwrap();
+    let b = b.spawn().unwrap();
+    let a = thread::spawn(move || a.wait_with_output().unwrap());
+    let b = b.wait_with_output().unwrap();
+    let a = a.join().unwrap();
+
+    execs().run_output(&a);
+    execs().with_stdout_contains("test t ... ok").run_output(&b);
+
+    // Neither build left behind broken artifacts or fingerprints.
+    p.process(&p.bin("foo")).with_stdout("hello").run();
+    p.cargo("build").with_stderr("[FINISHED] [..]").run();
+    p.cargo("test")
+        .with_stderr_does_not_contain("[COMPILING] [..]")
+        .run();
+}
+
+#[cargo_test]
+fn clean_waits_for_build() {
+    let p = project()
+        .file("src/main.rs", "fn main() {}")
+        .file(
+            "build.rs",
+            r#"
+            use std::io::Read;
+            use std::net::TcpStream;
+
+            fn main() {
+                let addr = std::env::var("ADDR").unwrap();
+                let mut stream = TcpStream::connect(&addr[..]).unwrap();
+                // Hold the build directory until the test hangs up.
+                let _ = stream.read(&mut [0]);
+            }
+        "#,
+        )
+        .build();
+
+    let l = TcpListener::bind("127.0.0.1:0").unwrap();
+    let mut build = p.cargo("build").build_command();
+    build
+        .env("ADDR", l.local_addr().unwrap().to_string())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    let build = build.spawn().unwrap();
+    let (conn, _) = l.accept().unwrap();
+
+    let mut clean = p.cargo("clean").build_command();
+    clean.stdout(Stdio::piped()).stderr(Stdio::piped());
+    let clean = clean.spawn().unwrap();
+    thread::sleep(Duration::from_secs(1));
+    drop(conn);
+
+    let build = build.wait_with_output().unwrap();
+    let clean = clean.wait_with_output().unwrap();
+    execs().run_output(&build);
+    execs()
+        .with_stderr_contains("[..]Blocking waiting for file lock on build directory")
+        .run_output(&clean);
+    assert!(!p.root().join("target").exists());
+}
+
 // Make sure that if Cargo dies while holding a lock that it's released and the
 // next Cargo to come in will take over cleanly.
 // older win versions don't support job objects, so skip test there
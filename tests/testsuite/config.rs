@@ -2,9 +2,10 @@
 
 use cargo::core::{enable_nightly_features, InternedString, Shell};
 use cargo::util::config::{self, Config, SslVersionConfig, StringList};
+use cargo::util::errors::{ConfigFileError, EnvConfigError, ManifestError};
 use cargo::util::toml::{self, VecStringOrBool as VSOB};
 use cargo::CargoResult;
-use cargo_test_support::{normalized_lines_match, paths, project, t};
+use cargo_test_support::{basic_manifest, normalized_lines_match, paths, project, t};
 use serde::Deserialize;
 use std::borrow::Borrow;
 use std::collections::{BTreeMap, HashMap};
@@ -667,8 +668,14 @@ Caused by:
     // Environment variable type errors.
     assert_error(
         config.get::<i64>("e.s").unwrap_err(),
-        "error in environment variable `CARGO_E_S`: invalid digit found in string",
-    );
+        "error in environment variable `CARGO_E_S`: `e.s` expected an integer, but found `asdf`",
+    );
+    let err = config.get::<i64>("e.s").unwrap_err();
+    let err = err.downcast_ref::<EnvConfigError>().unwrap();
+    assert_eq!(err.var, "CARGO_E_S");
+    assert_eq!(err.key, "e.s");
+    assert_eq!(err.value, "asdf");
+    assert_eq!(err.expected, "an integer");
     assert_error(
         config.get::<i8>("e.big").unwrap_err(),
         "\
@@ -719,7 +726,7 @@ fn config_bad_toml() {
 could not load Cargo configuration
 
 Caused by:
-  could not parse TOML configuration in `[..]/.cargo/config`
+  could not load configuration file `[..]/.cargo/config`
 
 Caused by:
   could not parse input as TOML
@@ -872,6 +879,311 @@ ns2 = 456
     );
 }
 
+#[cargo_test]
+fn config_typed_getters() {
+    write_config(
+        "\
+[t]
+s = 'hello'
+b = true
+i = -42
+l = ['a', 'b']
+
+[t.table]
+x = 1
+",
+    );
+
+    let config = ConfigBuilder::new()
+        .env("CARGO_T_ENV_B", "false")
+        .env("CARGO_T_ENV_I", "7")
+        .build();
+
+    // `Definition` equality ignores the path, so compare them as strings.
+    let def = paths::root().join(".cargo/config").display().to_string();
+    let s = config.get_string("t.s").unwrap().unwrap();
+    assert_eq!(s.val, "hello");
+    assert_eq!(s.definition.to_string(), def);
+    assert!(config.get_bool("t.b").unwrap().unwrap().val);
+    assert!(!config.get_bool("t.env-b").unwrap().unwrap().val);
+    assert_eq!(config.get_i64("t.i").unwrap().unwrap().val, -42);
+    assert_eq!(config.get_i64("t.env-i").unwrap().unwrap().val, 7);
+    let l = config.get_list("t.l").unwrap().unwrap();
+    let l: Vec<_> = l
+        .val
+        .iter()
+        .map(|(s, d)| (s.as_str(), d.to_string()))
+        .collect();
+    assert_eq!(l, vec![("a", def.clone()), ("b", def.clone())]);
+    let table = config.get_table("t.table").unwrap().unwrap();
+    assert_eq!(table.definition.to_string(), def);
+    assert_eq!(table.val.len(), 1);
+    assert_eq!(table.val["x"].definition().to_string(), def);
+
+    assert!(config.get_string("t.missing").unwrap().is_none());
+    assert!(config.get_bool("t.missing").unwrap().is_none());
+    assert!(config.get_i64("t.missing").unwrap().is_none());
+    assert!(config.get_list("t.missing").unwrap().is_none());
+    assert!(config.get_table("t.missing").unwrap().is_none());
+}
+
+#[cargo_test]
+fn config_typed_getter_errors() {
+    write_config(
+        "\
+[t]
+s = 'hello'
+b = true
+i = 1
+l = ['a']
+",
+    );
+
+    let config = new_config();
+    assert_error(
+        config.get_string("t.i").unwrap_err(),
+        "error in [..]/.cargo/config: `t.i` expected a string, but found a integer",
+    );
+    assert_error(
+        config.get_bool("t.s").unwrap_err(),
+        "error in [..]/.cargo/config: `t.s` expected true/false, but found a string",
+    );
+    assert_error(
+        config.get_i64("t.b").unwrap_err(),
+        "error in [..]/.cargo/config: `t.b` expected an integer, but found a boolean",
+    );
+    assert_error(
+        config.get_list("t.s").unwrap_err(),
+        "\
+invalid configuration for key `t.s`
+expected a list, but found a string for `t.s` in [..]/.cargo/config",
+    );
+    assert_error(
+        config.get_table("t.l").unwrap_err(),
+        "\
+invalid configuration for key `t.l`
+expected a table, but found a array for `t.l` in [..]/.cargo/config",
+    );
+}
+
+#[cargo_test]
+fn config_get_list_across_files() {
+    write_config_at(".cargo/config", "l = ['root']");
+    write_config_at("a/.cargo/config", "l = ['a1', 'a2']");
+    write_config_at("a/b/.cargo/config", "l = ['b']");
+
+    let config = ConfigBuilder::new().cwd("a/b").build();
+    let l = config.get_list("l").unwrap().unwrap();
+    let l: Vec<_> = l
+        .val
+        .iter()
+        .map(|(s, d)| (s.as_str(), d.to_string()))
+        .collect();
+    let def = |p: &str| paths::root().join(p).display().to_string();
+    assert_eq!(
+        l,
+        vec![
+            ("b", def("a/b/.cargo/config")),
+            ("a1", def("a/.cargo/config")),
+            ("a2", def("a/.cargo/config")),
+            ("root", def(".cargo/config")),
+        ]
+    );
+}
+
+#[cargo_test]
+fn env_config_precedence() {
+    // For one key, from highest to lowest precedence: command-line flag,
+    // environment variable, project config, user config.
+    let p = project().file("src/lib.rs", "").build();
+    let home_config = paths::home().join(".cargo/config");
+    fs::create_dir_all(home_config.parent().unwrap()).unwrap();
+    fs::write(&home_config, "[build]\ntarget-dir = 'user-target'").unwrap();
+
+    p.cargo("build").run();
+    assert!(paths::home()
+        .join("user-target/debug/libfoo.rlib")
+        .is_file());
+
+    p.change_file(".cargo/config", "[build]\ntarget-dir = 'project-target'");
+    p.cargo("build").run();
+    assert!(p.root().join("project-target/debug/libfoo.rlib").is_file());
+
+    p.cargo("build")
+        .env("CARGO_BUILD_TARGET_DIR", "env-target")
+        .run();
+    assert!(p.root().join("env-target/debug/libfoo.rlib").is_file());
+
+    p.cargo("build --target-dir flag-target")
+        .env("CARGO_BUILD_TARGET_DIR", "env-target")
+        .run();
+    assert!(p.root().join("flag-target/debug/libfoo.rlib").is_file());
+}
+
+#[cargo_test]
+fn env_config_conversion_error() {
+    // A bad value in the environment is reported like a bad value in a file,
+    // naming the variable instead of the file.
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("build")
+        .env("CARGO_BUILD_JOBS", "lots")
+        .with_status(101)
+        .with_stderr_contains(
+            "[..]error in environment variable `CARGO_BUILD_JOBS`: \
+             `build.jobs` expected an integer, but found `lots`",
+        )
+        .run();
+
+    p.change_file(".cargo/config", "[build]\njobs = 'lots'");
+    p.cargo("build")
+        .with_status(101)
+        .with_stderr_contains(
+            "[..]error in [..]/.cargo/config: `build.jobs` expected an integer, but found a string",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn build_jobs_config() {
+    let p = project()
+        .file("src/lib.rs", "")
+        .file(
+            "build.rs",
+            r#"
+            fn main() {
+                println!("cargo:rerun-if-env-changed=EXPECTED_JOBS");
+                assert_eq!(
+                    std::env::var("NUM_JOBS").unwrap(),
+                    std::env::var("EXPECTED_JOBS").unwrap(),
+                );
+            }
+            "#,
+        )
+        .file(".cargo/config", "[build]\njobs = 3")
+        .build();
+
+    p.cargo("build").env("EXPECTED_JOBS", "3").run();
+    p.cargo("build")
+        .env("EXPECTED_JOBS", "5")
+        .env("CARGO_BUILD_JOBS", "5")
+        .run();
+    p.cargo("build -j 2")
+        .env("EXPECTED_JOBS", "2")
+        .env("CARGO_BUILD_JOBS", "5")
+        .run();
+}
+
+#[cargo_test]
+fn build_jobs_config_zero() {
+    let p = project()
+        .file("src/lib.rs", "")
+        .file(".cargo/config", "[build]\njobs = 0")
+        .build();
+
+    p.cargo("build")
+        .with_status(101)
+        .with_stderr(
+            "[ERROR] `build.jobs` must be at least 1, but found 0 in [..]/foo/.cargo/config",
+        )
+        .run();
+    p.cargo("build")
+        .env("CARGO_BUILD_JOBS", "0")
+        .with_status(101)
+        .with_stderr(
+            "[ERROR] `build.jobs` must be at least 1, \
+             but found 0 in environment variable `CARGO_BUILD_JOBS`",
+        )
+        .run();
+    p.cargo("build -j 1").run();
+}
+
+#[cargo_test]
+fn build_target_config() {
+    // `build.target` behaves exactly like `--target`, including selecting
+    // target-specific dependencies and the output directory.
+    let target = cargo_test_support::rustc_host();
+    let p = project()
+        .file(
+            "Cargo.toml",
+            &format!(
+                r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [target.{}.dependencies]
+                bar = {{ path = "bar" }}
+                "#,
+                target
+            ),
+        )
+        .file("src/lib.rs", "extern crate bar;")
+        .file("bar/Cargo.toml", &basic_manifest("bar", "0.1.0"))
+        .file("bar/src/lib.rs", "")
+        .file(".cargo/config", &format!("[build]\ntarget = '{}'", target))
+        .build();
+
+    p.cargo("build")
+        .with_stderr(
+            "\
+[COMPILING] bar v0.1.0 ([..])
+[COMPILING] foo v0.1.0 ([..])
+[FINISHED] [..]
+",
+        )
+        .run();
+    assert!(p
+        .build_dir()
+        .join(&target)
+        .join("debug/libfoo.rlib")
+        .is_file());
+    assert!(!p.build_dir().join("debug/libfoo.rlib").exists());
+
+    p.cargo("build --target")
+        .arg(&target)
+        .with_stderr("[FINISHED] [..]")
+        .run();
+
+    p.change_file(".cargo/config", "[build]\ntarget = 'no-such-target'");
+    p.cargo("build")
+        .env("CARGO_BUILD_TARGET", &target)
+        .with_stderr("[FINISHED] [..]")
+        .run();
+    p.cargo("build --target")
+        .arg(&target)
+        .env("CARGO_BUILD_TARGET", "no-such-target")
+        .with_stderr("[FINISHED] [..]")
+        .run();
+}
+
+#[cargo_test]
+fn build_rustflags_config() {
+    let p = project()
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config",
+            "[build]\nrustflags = ['--cfg', 'from_config']",
+        )
+        .build();
+
+    p.cargo("build -v")
+        .with_stderr_contains("[RUNNING] `rustc [..]--cfg from_config[..]`")
+        .run();
+    p.cargo("build -v")
+        .env("CARGO_BUILD_RUSTFLAGS", "--cfg from_cargo_env")
+        .with_stderr_contains("[RUNNING] `rustc [..]--cfg from_cargo_env[..]`")
+        .with_stderr_does_not_contain("[..]from_config[..]")
+        .run();
+    p.cargo("build -v")
+        .env("CARGO_BUILD_RUSTFLAGS", "--cfg from_cargo_env")
+        .env("RUSTFLAGS", "--cfg from_env")
+        .with_stderr_contains("[RUNNING] `rustc [..]--cfg from_env[..]`")
+        .with_stderr_does_not_contain("[..]from_config[..]")
+        .with_stderr_does_not_contain("[..]from_cargo_env[..]")
+        .run();
+}
+
 #[cargo_test]
 fn config_relative_path() {
     write_config(&format!(
@@ -1078,7 +1390,7 @@ ssl-version.max = 'tlsv1.3'
 could not load Cargo configuration
 
 Caused by:
-  could not parse TOML configuration in `[..]/.cargo/config`
+  could not load configuration file `[..]/.cargo/config`
 
 Caused by:
   could not parse input as TOML
@@ -1135,6 +1447,169 @@ Caused by:
     );
 }
 
+#[cargo_test]
+fn layered_config_files() {
+    // Closer files override farther ones key-by-key, and tables merge.
+    write_config_at(
+        ".cargo/config",
+        "
+        [layer]
+        far = 'root'
+        shared = 'root'
+        ",
+    );
+    write_config_at(
+        "a/.cargo/config",
+        "
+        [layer]
+        middle = 'a'
+        shared = 'a'
+        ",
+    );
+    write_config_at(
+        "a/b/.cargo/config",
+        "
+        [layer]
+        near = 'b'
+        shared = 'b'
+        ",
+    );
+
+    let config = ConfigBuilder::new().cwd("a/b").build();
+    let layer: BTreeMap<String, String> = config.get("layer").unwrap();
+    let expected: BTreeMap<String, String> = [
+        ("far", "root"),
+        ("middle", "a"),
+        ("near", "b"),
+        ("shared", "b"),
+    ]
+    .iter()
+    .map(|(k, v)| (k.to_string(), v.to_string()))
+    .collect();
+    assert_eq!(layer, expected);
+
+    // Only the files above the working directory are read.
+    let config = ConfigBuilder::new().cwd("a").build();
+    assert_eq!(config.get::<Option<String>>("layer.near").unwrap(), None);
+    assert_eq!(config.get::<String>("layer.shared").unwrap(), "a");
+
+    // Errors name the file that is broken.
+    write_config_at("a/.cargo/config", "[layer\nmiddle = 'a'");
+    let config = ConfigBuilder::new().cwd("a/b").build();
+    assert_error(
+        config.get::<String>("layer.near").unwrap_err(),
+        "\
+could not load Cargo configuration
+
+Caused by:
+  could not load configuration file `[..]/a/.cargo/config`
+
+Caused by:
+  could not parse input as TOML
+
+Caused by:
+  expected a right bracket, found a newline at line 1 column [..]",
+    );
+
+    // Type errors name the key and the file that defined it.
+    write_config_at("a/.cargo/config", "[layer]\nmiddle = 1");
+    let config = ConfigBuilder::new().cwd("a/b").build();
+    assert_error(
+        config.get::<String>("layer.middle").unwrap_err(),
+        "error in [..]/a/.cargo/config: `layer.middle` expected a string, but found a integer",
+    );
+}
+
+#[cargo_test]
+fn layered_config_errors_name_file() {
+    // Each of the project, parent and user config files is broken in turn,
+    // and the error must blame that file rather than another layer.
+    let files = ["a/b/.cargo/config", "a/.cargo/config", "home/config"];
+    let write_all = || {
+        for file in files.iter() {
+            write_config_at(file, "[layer]\nok = true");
+        }
+    };
+
+    for broken in files.iter() {
+        write_all();
+        write_config_at(broken, "[layer]\nok = true\nbad = ");
+        let config = ConfigBuilder::new().cwd("a/b").build();
+        assert_error(
+            config.get::<bool>("layer.ok").unwrap_err(),
+            &format!(
+                "\
+could not load Cargo configuration
+
+Caused by:
+  could not load configuration file `[ROOT]/{}`
+
+Caused by:
+  could not parse input as TOML
+
+Caused by:
+  [..] at line 3 column [..]",
+                broken
+            ),
+        );
+    }
+
+    // A wrong-typed value which is only defined in the outermost file.
+    write_all();
+    write_config_at("home/config", "[layer]\nok = true\nbad = 1");
+    let config = ConfigBuilder::new().cwd("a/b").build();
+    assert_error(
+        config.get::<String>("layer.bad").unwrap_err(),
+        "error in [ROOT]/home/config: `layer.bad` expected a string, but found a integer",
+    );
+}
+
+#[cargo_test]
+fn config_file_is_directory() {
+    fs::create_dir_all(paths::root().join(".cargo/config")).unwrap();
+    let config = new_config();
+    assert_error(
+        config.get::<i32>("foo").unwrap_err(),
+        "\
+could not load Cargo configuration
+
+Caused by:
+  could not load configuration file `[ROOT]/.cargo/config`
+
+Caused by:
+  expected a file, but found a directory",
+    );
+}
+
+#[cfg(unix)]
+#[cargo_test]
+fn config_file_not_readable() {
+    use std::os::unix::fs::PermissionsExt;
+
+    write_config("foo = 1");
+    let path = paths::root().join(".cargo/config");
+    fs::set_permissions(&path, fs::Permissions::from_mode(0)).unwrap();
+    if fs::read(&path).is_ok() {
+        // Running as root, which can read anything.
+        return;
+    }
+    let config = new_config();
+    assert_error(
+        config.get::<i32>("foo").unwrap_err(),
+        "\
+could not load Cargo configuration
+
+Caused by:
+  could not load configuration file `[ROOT]/.cargo/config`
+
+Caused by:
+  the file is not readable
+
+Caused by:
+  Permission denied (os error 13)",
+    );
+}
+
 #[cargo_test]
 fn non_string_in_array() {
     // Currently only strings are supported.
@@ -1146,16 +1621,42 @@ fn non_string_in_array() {
 could not load Cargo configuration
 
 Caused by:
-  failed to load TOML configuration from `[..]/.cargo/config`
-
-Caused by:
-  failed to parse key `foo`
+  invalid value for key `foo` in configuration file `[..]/.cargo/config`
 
 Caused by:
   expected string but found integer in list",
     );
 }
 
+#[cargo_test]
+fn config_file_error_names_file_and_key() {
+    write_config("[build]\njobs = 1.5");
+    let config = new_config();
+    let err = config.get::<u32>("build.jobs").unwrap_err();
+    let file_err = err
+        .chain()
+        .find_map(|e| e.downcast_ref::<ConfigFileError>())
+        .expect("expected a ConfigFileError");
+    assert_eq!(
+        file_err.config_path(),
+        paths::root().join(".cargo/config").as_path()
+    );
+    assert_eq!(file_err.key(), Some("build.jobs"));
+    assert!(err
+        .chain()
+        .all(|e| e.downcast_ref::<ManifestError>().is_none()));
+
+    // Syntax errors name the file but not a key.
+    write_config("[build");
+    let config = new_config();
+    let err = config.get::<u32>("build.jobs").unwrap_err();
+    let file_err = err
+        .chain()
+        .find_map(|e| e.downcast_ref::<ConfigFileError>())
+        .expect("expected a ConfigFileError");
+    assert_eq!(file_err.key(), None);
+}
+
 #[cargo_test]
 fn struct_with_opt_inner_struct() {
     // Struct with a key that is Option of another struct.
@@ -88,7 +88,7 @@ Caused by:
   failed to load config include `missing` from `[..]/.cargo/config`
 
 Caused by:
-  failed to read configuration file `[..]/.cargo/missing`
+  could not load configuration file `[..]/.cargo/missing`
 
 Caused by:
   {}",
@@ -175,7 +175,7 @@ Caused by:
   failed to load config include `foobar` from `--config cli option`
 
 Caused by:
-  failed to read configuration file `[..]/foobar`
+  could not load configuration file `[..]/foobar`
 
 Caused by:
   {}",
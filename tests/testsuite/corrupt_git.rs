@@ -141,6 +141,71 @@ fn deleting_checkout_files() {
     }
 }
 
+#[cargo_test]
+fn modified_checkout_files() {
+    let project = project();
+    let git_project = git::new("bar", |project| {
+        project
+            .file("Cargo.toml", &basic_manifest("bar", "0.5.0"))
+            .file("src/lib.rs", "")
+    });
+
+    let project = project
+        .file(
+            "Cargo.toml",
+            &format!(
+                r#"
+            [project]
+            name = "foo"
+            version = "0.5.0"
+            authors = []
+
+            [dependencies]
+            bar = {{ git = '{}' }}
+        "#,
+                git_project.url()
+            ),
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    project.cargo("build").run();
+
+    let dir = paths::home()
+        .join(".cargo/git/checkouts")
+        .read_dir()
+        .unwrap()
+        .next()
+        .unwrap()
+        .unwrap()
+        .path()
+        .read_dir()
+        .unwrap()
+        .next()
+        .unwrap()
+        .unwrap()
+        .path();
+    fs::write(dir.join("src/lib.rs"), "pub fn patched() {}").unwrap();
+    cargopaths::remove_file(&dir.join(".cargo-ok")).unwrap();
+
+    project
+        .cargo("build")
+        .with_status(101)
+        .with_stderr_contains(
+            "  the git checkout `[..]` has local modifications, which checking it out \
+             again would overwrite:
+    src/lib.rs
+Move away any changes to keep, then remove the directory so that Cargo checks it out again.",
+        )
+        .run();
+
+    // The modifications are left alone.
+    assert_eq!(
+        fs::read_to_string(dir.join("src/lib.rs")).unwrap(),
+        "pub fn patched() {}"
+    );
+}
+
 fn make_writable(path: &Path) {
     let mut p = path.metadata().unwrap().permissions();
     p.set_readonly(false);
@@ -170,6 +170,61 @@ fn no_rewrite_if_no_change() {
     );
 }
 
+#[cargo_test]
+fn dep_info_includes_manifests_and_path_dep_sources() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+
+                [dependencies]
+                bar = { path = "bar" }
+            "#,
+        )
+        .file(
+            "src/main.rs",
+            "mod util; fn main() { util::f(); bar::f(); }",
+        )
+        .file("src/util.rs", "pub fn f() {}")
+        .file("bar/Cargo.toml", &basic_manifest("bar", "0.1.0"))
+        .file("bar/src/lib.rs", "pub fn f() {}")
+        .build();
+
+    p.cargo("build").run();
+
+    let depinfo = p.read_file("target/debug/foo.d");
+    let target = format!("{}:", p.bin("foo").display());
+    let line = depinfo
+        .lines()
+        .find(|line| line.starts_with(&target))
+        .unwrap_or_else(|| panic!("no rule for {} in:\n{}", target, depinfo));
+    let prereqs: Vec<String> = line[target.len()..]
+        .replace("\\ ", "\0")
+        .split_whitespace()
+        .map(|s| s.replace("\0", " "))
+        .collect();
+
+    for path in &[
+        "Cargo.toml",
+        "Cargo.lock",
+        "src/main.rs",
+        "src/util.rs",
+        "bar/Cargo.toml",
+        "bar/src/lib.rs",
+    ] {
+        let path = p.root().join(path);
+        assert!(
+            prereqs.iter().any(|f| Path::new(f) == path),
+            "expected {} in {:?}",
+            path.display(),
+            prereqs
+        );
+    }
+}
+
 #[cargo_test]
 fn relative_depinfo_paths_ws() {
     if !is_nightly() {
@@ -0,0 +1,78 @@
+//! Tests for -Zdiagnostics-stream.
+
+use cargo_test_support::project;
+
+#[cargo_test]
+fn streams_cargo_and_compiler_diagnostics() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            unused-key = true
+            "#,
+        )
+        .file("src/lib.rs", "pub fn f() { let x = 1; }")
+        .build();
+
+    p.cargo("build -Zdiagnostics-stream=diagnostics.jsonl")
+        .masquerade_as_nightly_cargo()
+        .run();
+
+    let contents = p.read_file("diagnostics.jsonl");
+    let events = contents
+        .lines()
+        .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+        .collect::<Vec<_>>();
+    let summary = events
+        .iter()
+        .map(|e| {
+            assert_eq!(e["reason"], "cargo-diagnostic");
+            assert!(e["timestamp"].as_f64().unwrap() > 0.0);
+            (e["level"].as_str().unwrap(), e["message"].as_str().unwrap())
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        summary,
+        [
+            ("warning", "unused manifest key: package.unused-key"),
+            ("warning", "unused variable: `x`"),
+        ]
+    );
+}
+
+#[cargo_test]
+fn streams_errors() {
+    let p = project().file("src/lib.rs", "pub fn f() { x }").build();
+
+    p.cargo("build -Zdiagnostics-stream=diagnostics.jsonl")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr_contains("[ERROR] could not compile `foo`.")
+        .run();
+
+    let contents = p.read_file("diagnostics.jsonl");
+    let levels = contents
+        .lines()
+        .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+        .map(|e| {
+            (
+                e["level"].as_str().unwrap().to_string(),
+                e["message"].clone(),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(levels[0].0, "error");
+    assert_eq!(levels[0].1, "cannot find value `x` in this scope");
+    // The error cargo exits with is streamed last.
+    assert_eq!(levels.last().unwrap().0, "error");
+    assert!(levels
+        .last()
+        .unwrap()
+        .1
+        .as_str()
+        .unwrap()
+        .starts_with("could not compile `foo`"));
+}
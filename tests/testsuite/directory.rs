@@ -329,6 +329,54 @@ fn multiple() {
         .run();
 }
 
+#[cargo_test]
+fn locked_version_not_vendored() {
+    setup();
+
+    for version in &["0.1.0", "0.1.1"] {
+        VendorPackage::new(&format!("bar-{}", version))
+            .file("Cargo.toml", &basic_manifest("bar", version))
+            .file("src/lib.rs", "pub fn bar() {}")
+            .build();
+    }
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            authors = []
+
+            [dependencies]
+            bar = "0.1.0"
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("generate-lockfile").run();
+    fs::remove_dir_all(paths::root().join("index/bar-0.1.1")).unwrap();
+
+    p.cargo("build")
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] failed to get `bar` as a dependency of package `foo v0.1.0 ([CWD])`
+
+Caused by:
+  failed to query replaced source registry `https://github.com/rust-lang/crates.io-index`
+
+Caused by:
+  package `bar v0.1.1` is missing from the vendor directory `[ROOT]/index`
+The directory may be out of date with the lock file. Run `cargo vendor` again \
+to re-vendor the dependencies.
+",
+        )
+        .run();
+}
+
 #[cargo_test]
 fn crates_io_then_directory() {
     let p = project()
@@ -0,0 +1,154 @@
+//! Tests for `--error-format` and `term.error-format`.
+
+use cargo_test_support::{cargo_process, project};
+
+#[cargo_test]
+fn short_shows_only_primary_error() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+
+            [dependencies]
+            bar = { path = "bar" }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("build")
+        .with_status(101)
+        .with_stderr_contains("Caused by:")
+        .run();
+
+    p.cargo("build --error-format=short -Zunstable-options")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr("[ERROR] failed to get `bar` as a dependency of package `foo v0.1.0 ([CWD])`")
+        .run();
+
+    // The flag is global, so it may come before the subcommand.
+    p.cargo("--error-format short -Zunstable-options build")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr("[ERROR] failed to get `bar` as a dependency of package `foo v0.1.0 ([CWD])`")
+        .run();
+}
+
+#[cargo_test]
+fn short_keeps_exit_code() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("no-such-command --error-format=short -Zunstable-options")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr("[ERROR] no such subcommand: `no-such-command`")
+        .run();
+}
+
+#[cargo_test]
+fn short_internal_error() {
+    // Internal errors have no human-readable link, so the error itself is
+    // shown, without the notes asking for a bug report.
+    cargo_process("init --error-format=short -Zunstable-options")
+        .masquerade_as_nightly_cargo()
+        .env("__CARGO_TEST_INTERNAL_ERROR", "1")
+        .with_status(101)
+        .with_stderr("[ERROR] internal error test")
+        .run();
+}
+
+#[cargo_test]
+fn gated() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("build --error-format=short")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] the `--error-format` flag is unstable, pass `-Z unstable-options` to enable it
+See https://github.com/rust-lang/cargo/issues/8680 for more information about the `--error-format` flag.
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn from_config() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+
+            [dependencies]
+            bar = { path = "bar" }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            ".cargo/config",
+            r#"
+            [term]
+            error-format = "short"
+            "#,
+        )
+        .build();
+
+    p.cargo("build -Zunstable-options")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr("[ERROR] failed to get `bar` as a dependency of package `foo v0.1.0 ([CWD])`")
+        .run();
+
+    // The environment overrides the config, and the flag overrides both.
+    p.cargo("build -Zunstable-options")
+        .masquerade_as_nightly_cargo()
+        .env("CARGO_TERM_ERROR_FORMAT", "bogus")
+        .with_status(101)
+        .with_stderr(
+            "[ERROR] the `term.error-format` config value must be `full` or `short`, \
+             but found `bogus`",
+        )
+        .run();
+
+    p.cargo("build --error-format=full -Zunstable-options")
+        .masquerade_as_nightly_cargo()
+        .env("CARGO_TERM_ERROR_FORMAT", "bogus")
+        .with_status(101)
+        .with_stderr_contains("Caused by:")
+        .run();
+
+    // Without `-Z unstable-options` the config value is ignored.
+    p.cargo("build")
+        .with_status(101)
+        .with_stderr_contains(
+            "[WARNING] config `term.error-format` ignored, \
+             the -Zunstable-options command-line flag is required",
+        )
+        .with_stderr_contains("Caused by:")
+        .run();
+}
+
+#[cargo_test]
+fn from_env_on_stable() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("build")
+        .env("CARGO_TERM_ERROR_FORMAT", "bogus")
+        .with_stderr(
+            "\
+[WARNING] config `term.error-format` ignored, \
+the -Zunstable-options command-line flag is required
+[COMPILING] foo v0.0.1 ([CWD])
+[FINISHED] [..]
+",
+        )
+        .run();
+}
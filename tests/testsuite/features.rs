@@ -2113,3 +2113,64 @@ fn slash_optional_enables() {
 
     p.cargo("check --features dep/feat").run();
 }
+
+#[cargo_test]
+fn no_default_features_required_by_dependent() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "a"
+            version = "0.1.0"
+
+            [workspace]
+            members = ["b"]
+
+            [features]
+            default = ["f1"]
+            f1 = []
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            "b/Cargo.toml",
+            r#"
+            [package]
+            name = "b"
+            version = "0.1.0"
+
+            [dependencies]
+            a = { path = ".." }
+            "#,
+        )
+        .file("b/src/lib.rs", "")
+        .build();
+
+    p.cargo("check --workspace --no-default-features")
+        .with_stderr_contains(
+            "\
+[WARNING] `--no-default-features` has no effect on package `a`, its `default` feature \
+is required by:
+  b v0.1.0 ([CWD]/b)
+Add `default-features = false` to their dependency on `a` to build it without its \
+default features.
+",
+        )
+        .run();
+
+    p.change_file(
+        "b/Cargo.toml",
+        r#"
+        [package]
+        name = "b"
+        version = "0.1.0"
+
+        [dependencies]
+        a = { path = "..", default-features = false }
+        "#,
+    );
+    p.cargo("check --workspace --no-default-features")
+        .with_stderr_does_not_contain("[WARNING] [..]")
+        .run();
+}
@@ -2,7 +2,8 @@
 
 use cargo_test_support::registry::Package;
 use cargo_test_support::rustc_host;
-use cargo_test_support::{basic_manifest, cross_compile, project};
+use cargo_test_support::{basic_manifest, cross_compile, git, paths, project};
+use std::fs;
 
 #[cargo_test]
 fn no_deps() {
@@ -133,3 +134,53 @@ fn fetch_warning() {
         .with_stderr("[WARNING] unused manifest key: package.misspelled")
         .run();
 }
+
+#[cargo_test]
+fn fetch_respects_cargo_home() {
+    // Everything downloaded goes under `CARGO_HOME`, which is created if
+    // missing, and separate homes don't share caches.
+    Package::new("d1", "1.2.3").publish();
+    let git_dep = git::new("d2", |project| {
+        project
+            .file("Cargo.toml", &basic_manifest("d2", "0.5.0"))
+            .file("src/lib.rs", "")
+    });
+    let p = project()
+        .file(
+            "Cargo.toml",
+            &format!(
+                r#"
+                [package]
+                name = "foo"
+                version = "0.0.1"
+
+                [dependencies]
+                d1 = "1.2.3"
+                d2 = {{ git = '{}' }}
+                "#,
+                git_dep.url()
+            ),
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    // The test registry is configured in the default home's config, so
+    // carry it over into the project for the other homes.
+    let default_home = paths::home().join(".cargo");
+    let registry_config = fs::read_to_string(default_home.join("config")).unwrap();
+    fs::create_dir_all(p.root().join(".cargo")).unwrap();
+    fs::write(p.root().join(".cargo/config"), registry_config).unwrap();
+
+    for name in &["home-a", "home-b"] {
+        let home = paths::root().join(name);
+        assert!(!home.exists());
+        p.cargo("fetch")
+            .env("CARGO_HOME", &home)
+            .with_stderr_contains("[DOWNLOADED] d1 v1.2.3 [..]")
+            .run();
+        assert!(home.join("registry").is_dir());
+        assert!(home.join("git/db").is_dir());
+    }
+    assert!(!default_home.join("registry").exists());
+    assert!(!default_home.join("git").exists());
+}
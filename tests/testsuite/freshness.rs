@@ -52,6 +52,221 @@ fn modifying_and_moving() {
         .run();
 }
 
+#[cargo_test]
+fn dirty_reasons_verbose() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.0.1"
+
+            [lib]
+            crate-type = ["cdylib"]
+
+            [dependencies]
+            bar = { path = "bar" }
+
+            [features]
+            f = []
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("bar/Cargo.toml", &basic_manifest("bar", "0.0.1"))
+        .file("bar/src/lib.rs", "")
+        .build();
+
+    p.cargo("build -v")
+        .with_stderr_does_not_contain("[DIRTY][..]")
+        .run();
+
+    p.root().move_into_the_past();
+    p.root().join("target").move_into_the_past();
+    p.change_file("src/lib.rs", "// modified");
+    p.cargo("build -v")
+        .with_stderr_contains("[DIRTY] foo v0.0.1 ([CWD]) lib: source file `src/lib.rs` changed")
+        .run();
+
+    // A cdylib of a path package has no metadata hash, so enabling a
+    // feature rebuilds the same unit.
+    p.cargo("build -v --features f")
+        .with_stderr_contains("[DIRTY] foo v0.0.1 ([CWD]) lib: features changed from [] to [\"f\"]")
+        .run();
+
+    p.cargo("build -v --features f")
+        .env("RUSTFLAGS", "-C debug-assertions")
+        .with_stderr_contains("[DIRTY] foo v0.0.1 ([CWD]) lib: profile flags changed")
+        .run();
+
+    p.root().move_into_the_past();
+    p.root().join("target").move_into_the_past();
+    p.change_file("bar/src/lib.rs", "// modified");
+    p.cargo("build -v --features f")
+        .env("RUSTFLAGS", "-C debug-assertions")
+        .with_stderr_contains(
+            "[DIRTY] bar v0.0.1 ([CWD]/bar) lib: source file `src/lib.rs` changed",
+        )
+        .with_stderr_contains("[DIRTY] foo v0.0.1 ([CWD]) lib: dependency `bar` was rebuilt")
+        .run();
+}
+
+#[cargo_test]
+fn dirty_reason_no_previous_fingerprint() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("build").run();
+
+    // An empty fingerprint is what a build which was interrupted leaves.
+    let dir = p.target_debug_dir().join(".fingerprint");
+    let fingerprint = fs::read_dir(&dir)
+        .unwrap()
+        .map(|e| e.unwrap().path())
+        .find(|path| path.join("lib-foo").is_file())
+        .unwrap();
+    fs::write(fingerprint.join("lib-foo"), b"").unwrap();
+
+    p.cargo("build -v")
+        .with_stderr_contains("[DIRTY] foo v0.0.1 ([CWD]) lib: no previous fingerprint")
+        .with_stderr_contains("[COMPILING] foo v0.0.1 ([CWD])")
+        .run();
+}
+
+#[cargo_test]
+fn dirty_reason_tracked_env_var() {
+    let p = project()
+        .file("src/lib.rs", "")
+        .file(
+            "build.rs",
+            r#"
+                fn main() {
+                    println!("cargo:rerun-if-env-changed=FOO");
+                }
+            "#,
+        )
+        .build();
+
+    p.cargo("build -v").env("FOO", "1").run();
+
+    p.cargo("build -v")
+        .env("FOO", "2")
+        .with_stderr_contains(
+            "[DIRTY] foo v0.0.1 ([CWD]) custom-build: environment variable `FOO` changed",
+        )
+        .with_stderr_contains("[RUNNING] `[..]/build/foo-[..]/build-script-build`")
+        .run();
+
+    p.cargo("build -v")
+        .env("FOO", "2")
+        .env("BAR", "1")
+        .with_stderr_does_not_contain("[DIRTY][..]")
+        .with_stderr_contains("[FRESH] foo v0.0.1 ([CWD])")
+        .run();
+}
+
+#[cargo_test]
+fn only_files_read_by_rustc_are_tracked() {
+    let p = project()
+        .file("src/lib.rs", "mod a;")
+        .file("src/a.rs", "")
+        .file("src/unused.rs", "")
+        .file("README.md", "")
+        .build();
+
+    p.cargo("build").run();
+
+    p.root().move_into_the_past();
+    p.root().join("target").move_into_the_past();
+    p.change_file("README.md", "# foo");
+    p.change_file("src/unused.rs", "// modified");
+    p.cargo("build -v")
+        .with_stderr(
+            "\
+[FRESH] foo v0.0.1 ([CWD])
+[FINISHED] dev [unoptimized + debuginfo] target(s) in [..]
+",
+        )
+        .run();
+
+    p.change_file("src/a.rs", "// modified");
+    p.cargo("build -v")
+        .with_stderr_contains("[DIRTY] foo v0.0.1 ([CWD]) lib: source file `src/a.rs` changed")
+        .with_stderr_contains("[COMPILING] foo v0.0.1 ([CWD])")
+        .run();
+}
+
+#[cargo_test]
+fn malformed_dep_info_rebuilds() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("build").run();
+
+    let dir = p.target_debug_dir().join(".fingerprint");
+    let fingerprint = fs::read_dir(&dir)
+        .unwrap()
+        .map(|e| e.unwrap().path())
+        .find(|path| path.join("dep-lib-foo").is_file())
+        .unwrap();
+    fs::write(fingerprint.join("dep-lib-foo"), b"\xffnot dep-info").unwrap();
+
+    p.cargo("build -v")
+        .with_stderr_contains(
+            "[DIRTY] foo v0.0.1 ([CWD]) lib: dep-info file \
+             `debug/.fingerprint/foo-[..]/dep-lib-foo` is malformed",
+        )
+        .with_stderr_contains("[COMPILING] foo v0.0.1 ([CWD])")
+        .run();
+
+    p.cargo("build -v")
+        .with_stderr_contains("[FRESH] foo v0.0.1 ([CWD])")
+        .run();
+}
+
+#[cargo_test]
+fn corrupt_fingerprint_warns() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("build").run();
+    // The old fingerprint is only read when something changed.
+    p.change_file("src/lib.rs", "// modified");
+
+    let dir = p.target_debug_dir().join(".fingerprint");
+    let fingerprint = fs::read_dir(&dir)
+        .unwrap()
+        .map(|e| e.unwrap().path())
+        .find(|path| path.join("lib-foo.json").is_file())
+        .unwrap();
+    fs::write(fingerprint.join("lib-foo.json"), b"{\"rustc\":").unwrap();
+
+    p.cargo("build")
+        .with_stderr(
+            "\
+[WARNING] failed to read the fingerprint of foo v0.0.1 ([CWD]) lib from \
+`[..]/lib-foo.json`: EOF while parsing [..]
+It will be rebuilt. If this keeps happening, the target directory may be corrupted, \
+and `cargo clean` will reset it.
+[COMPILING] foo v0.0.1 ([CWD])
+[FINISHED] [..]
+",
+        )
+        .run();
+
+    p.cargo("build").with_stderr("[FINISHED] [..]").run();
+
+    // Valid JSON in another format, like from another version of cargo, is
+    // rebuilt without a warning.
+    p.change_file("src/lib.rs", "// modified again");
+    fs::write(fingerprint.join("lib-foo.json"), b"{\"rustc\":\"x\"}").unwrap();
+    p.cargo("build")
+        .with_stderr(
+            "\
+[COMPILING] foo v0.0.1 ([CWD])
+[FINISHED] [..]
+",
+        )
+        .run();
+}
+
 #[cargo_test]
 fn modify_only_some_files() {
     let p = project()
@@ -145,6 +360,7 @@ fn rebuild_sub_package_then_while_package() {
     p.cargo("build -pb -v")
         .with_stderr(
             "\
+[DIRTY] b [..] lib: source file `src/lib.rs` changed
 [COMPILING] b [..]
 [RUNNING] `rustc --crate-name b [..]
 [FINISHED] dev [..]
@@ -160,6 +376,8 @@ fn rebuild_sub_package_then_while_package() {
     p.cargo("build -v")
         .with_stderr(
             "\
+[DIRTY] foo [..] lib: dependency `a` was rebuilt
+[DIRTY] a [..] lib: current filesystem status shows we're outdated
 [FRESH] b [..]
 [COMPILING] a [..]
 [RUNNING] `rustc --crate-name a [..]
@@ -365,6 +583,10 @@ fn changing_bin_paths_common_target_features_caches_targets() {
         .with_stdout("ftest off")
         .with_stderr(
             "\
+[WARNING] path dependency `dep_crate` of package `a` resolves to `[ROOT]/foo/dep_crate`, \
+which is outside of the workspace root `[ROOT]/foo/a`
+It may not exist on other machines. Move it into the workspace, or set \
+`build.path-dependency-escape` to `allow`.
 [..]Compiling dep_crate v0.0.1 ([..])
 [..]Compiling a v0.0.1 ([..])
 [FINISHED] dev [unoptimized + debuginfo] target(s) in [..]
@@ -378,6 +600,10 @@ fn changing_bin_paths_common_target_features_caches_targets() {
         .with_stdout("ftest off")
         .with_stderr(
             "\
+[WARNING] path dependency `dep_crate` of package `a` resolves to `[ROOT]/foo/dep_crate`, \
+which is outside of the workspace root `[ROOT]/foo/a`
+It may not exist on other machines. Move it into the workspace, or set \
+`build.path-dependency-escape` to `allow`.
 [..]Compiling a v0.0.1 ([..])
 [FINISHED] dev [unoptimized + debuginfo] target(s) in [..]
 [RUNNING] `[..]target/debug/a[EXE]`
@@ -391,6 +617,10 @@ fn changing_bin_paths_common_target_features_caches_targets() {
         .with_stdout("ftest on")
         .with_stderr(
             "\
+[WARNING] path dependency `dep_crate` of package `b` resolves to `[ROOT]/foo/dep_crate`, \
+which is outside of the workspace root `[ROOT]/foo/b`
+It may not exist on other machines. Move it into the workspace, or set \
+`build.path-dependency-escape` to `allow`.
 [..]Compiling dep_crate v0.0.1 ([..])
 [..]Compiling b v0.0.1 ([..])
 [FINISHED] dev [unoptimized + debuginfo] target(s) in [..]
@@ -404,6 +634,10 @@ fn changing_bin_paths_common_target_features_caches_targets() {
         .with_stdout("ftest on")
         .with_stderr(
             "\
+[WARNING] path dependency `dep_crate` of package `b` resolves to `[ROOT]/foo/dep_crate`, \
+which is outside of the workspace root `[ROOT]/foo/b`
+It may not exist on other machines. Move it into the workspace, or set \
+`build.path-dependency-escape` to `allow`.
 [..]Compiling b v0.0.1 ([..])
 [FINISHED] dev [unoptimized + debuginfo] target(s) in [..]
 [RUNNING] `[..]target/debug/b[EXE]`
@@ -419,6 +653,10 @@ fn changing_bin_paths_common_target_features_caches_targets() {
         .with_stdout("ftest off")
         .with_stderr(
             "\
+[WARNING] path dependency `dep_crate` of package `a` resolves to `[ROOT]/foo/dep_crate`, \
+which is outside of the workspace root `[ROOT]/foo/a`
+It may not exist on other machines. Move it into the workspace, or set \
+`build.path-dependency-escape` to `allow`.
 [..]Compiling a v0.0.1 ([..])
 [FINISHED] dev [unoptimized + debuginfo] target(s) in [..]
 [RUNNING] `[..]target/debug/a[EXE]`
@@ -434,6 +672,10 @@ fn changing_bin_paths_common_target_features_caches_targets() {
         .with_stdout("ftest on")
         .with_stderr(
             "\
+[WARNING] path dependency `dep_crate` of package `b` resolves to `[ROOT]/foo/dep_crate`, \
+which is outside of the workspace root `[ROOT]/foo/b`
+It may not exist on other machines. Move it into the workspace, or set \
+`build.path-dependency-escape` to `allow`.
 [..]Compiling b v0.0.1 ([..])
 [FINISHED] dev [unoptimized + debuginfo] target(s) in [..]
 [RUNNING] `[..]target/debug/b[EXE]`
@@ -707,6 +949,10 @@ fn same_build_dir_cached_packages() {
         .cwd("a1")
         .with_stderr(&format!(
             "\
+[WARNING] path dependency `b` of package `a1` resolves to `[ROOT]/foo/b`, \
+which is outside of the workspace root `[ROOT]/foo/a1`
+It may not exist on other machines. Move it into the workspace, or set \
+`build.path-dependency-escape` to `allow`.
 [COMPILING] d v0.0.1 ({dir}/d)
 [COMPILING] c v0.0.1 ({dir}/c)
 [COMPILING] b v0.0.1 ({dir}/b)
@@ -720,6 +966,10 @@ fn same_build_dir_cached_packages() {
         .cwd("a2")
         .with_stderr(
             "\
+[WARNING] path dependency `b` of package `a2` resolves to `[ROOT]/foo/b`, \
+which is outside of the workspace root `[ROOT]/foo/a2`
+It may not exist on other machines. Move it into the workspace, or set \
+`build.path-dependency-escape` to `allow`.
 [COMPILING] a2 v0.0.1 ([CWD])
 [FINISHED] dev [unoptimized + debuginfo] target(s) in [..]
 ",
@@ -1861,6 +2111,8 @@ fn simulated_docker_deps_stay_cached() {
 [FRESH] regdep_env [..]
 [FRESH] regdep_old_style [..]
 [FRESH] regdep_rerun [..]
+[DIRTY] foo [..] lib: dependency `build_script_build` was rebuilt
+[DIRTY] foo [..] custom-build: precalculated components have changed: [..]
 [COMPILING] foo [..]
 [RUNNING] [..]/foo-[..]/build-script-build[..]
 [RUNNING] `rustc --crate-name foo[..]
@@ -2092,6 +2344,8 @@ fn rerun_if_changes() {
         .env("FOO", "1")
         .with_stderr(
             "\
+[DIRTY] foo [..] lib: dependency `build_script_build` was rebuilt
+[DIRTY] foo [..] custom-build: environment variable `[..]` changed
 [COMPILING] foo [..]
 [RUNNING] `[..]build-script-build`
 [RUNNING] `rustc [..]
@@ -2109,6 +2363,8 @@ fn rerun_if_changes() {
         .env("BAR", "1")
         .with_stderr(
             "\
+[DIRTY] foo [..] lib: dependency `build_script_build` was rebuilt
+[DIRTY] foo [..] custom-build: environment variable `[..]` changed
 [COMPILING] foo [..]
 [RUNNING] `[..]build-script-build`
 [RUNNING] `rustc [..]
@@ -2126,6 +2382,8 @@ fn rerun_if_changes() {
         .env("BAR", "2")
         .with_stderr(
             "\
+[DIRTY] foo [..] lib: dependency `build_script_build` was rebuilt
+[DIRTY] foo [..] custom-build: environment variable `[..]` changed
 [COMPILING] foo [..]
 [RUNNING] `[..]build-script-build`
 [RUNNING] `rustc [..]
@@ -2437,6 +2695,49 @@ fn linking_interrupted() {
         .run();
 }
 
+#[cargo_test]
+fn interrupted_before_fingerprint() {
+    // Outputs produced by a build that was killed before writing the
+    // fingerprint are never treated as fresh, and are removed by the next
+    // build even if it fails.
+    let p = project().file("src/lib.rs", "").build();
+    p.cargo("build").run();
+
+    let rlibs = || {
+        fs::read_dir(p.target_debug_dir().join("deps"))
+            .unwrap()
+            .map(|e| e.unwrap().path())
+            .filter(|p| p.extension().map_or(false, |ext| ext == "rlib"))
+            .collect::<Vec<_>>()
+    };
+
+    // A build killed after rustc started writing the rlib leaves an empty
+    // fingerprint behind, and possibly a partially written rlib.
+    let dir = p.target_debug_dir().join(".fingerprint");
+    let fingerprint = fs::read_dir(&dir)
+        .unwrap()
+        .map(|e| e.unwrap().path().join("lib-foo"))
+        .find(|path| path.is_file())
+        .unwrap();
+    fs::write(&fingerprint, "").unwrap();
+    let rlib = rlibs().pop().unwrap();
+    fs::write(&rlib, "").unwrap();
+
+    p.change_file("src/lib.rs", "pub fn f() {");
+    p.cargo("build")
+        .with_status(101)
+        .with_stderr_contains("[COMPILING] foo [..]")
+        .run();
+    assert!(rlibs().is_empty());
+
+    p.change_file("src/lib.rs", "pub fn f() {}");
+    p.cargo("build")
+        .with_stderr("[COMPILING] foo [..]\n[FINISHED] [..]")
+        .run();
+    assert!(fs::metadata(&rlib).unwrap().len() > 0);
+    p.cargo("build").with_stderr("[FINISHED] [..]").run();
+}
+
 #[cargo_test]
 #[cfg_attr(
     not(all(target_arch = "x86_64", target_os = "windows", target_env = "msvc")),
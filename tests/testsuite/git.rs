@@ -779,6 +779,10 @@ fn update_with_shared_deps() {
 [UPDATING] git repository [..]
 [ERROR] Unable to update [..]
 
+Caused by:
+  revision `0.1.2` does not exist in git repository `[..]`
+It may have been deleted from the repository, or be mistyped.
+
 Caused by:
   revspec '0.1.2' not found; [..]
 ",
@@ -2276,6 +2280,8 @@ fn include_overrides_gitignore() {
     p.cargo("build -v")
         .with_stderr(
             "\
+[DIRTY] foo v0.5.0 ([..]) lib: dependency `build_script_build` was rebuilt
+[DIRTY] foo v0.5.0 ([..]) custom-build: precalculated components have changed: [..]
 [COMPILING] foo v0.5.0 ([..])
 [RUNNING] `[..]build-script-build[..]`
 [RUNNING] `rustc --crate-name foo src/lib.rs [..]`
@@ -2373,7 +2379,13 @@ Caused by:
   could not parse input as TOML
 
 Caused by:
-  duplicate key: `categories` for key `project` at line 10 column 17",
+  duplicate key: `categories` for key `project` at line 10 column 17
+
+     |
+   8 |                 categories = [\"algorithms\"]
+   9 |
+  10 |                 [lib]
+     |                 ^",
             path2url(&git_root),
             path2url(&git_root),
         ))
@@ -2782,3 +2794,38 @@ to proceed despite [..]
     git::commit(&repo);
     git_project.cargo("package --no-verify").run();
 }
+
+#[cargo_test]
+fn missing_branch() {
+    let git_project = git::new("bar", |p| {
+        p.file("Cargo.toml", &basic_manifest("bar", "0.5.0"))
+            .file("src/lib.rs", "")
+    });
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            &format!(
+                r#"
+                [project]
+                name = "foo"
+                version = "0.5.0"
+
+                [dependencies]
+                bar = {{ git = '{}', branch = "does-not-exist" }}
+            "#,
+                git_project.url()
+            ),
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("build")
+        .with_status(101)
+        .with_stderr_contains(
+            "  branch `does-not-exist` does not exist in git repository `[..]bar`",
+        )
+        .with_stderr_contains("It may have been deleted from the repository, or be mistyped.")
+        .with_stderr_contains("  failed to find branch `does-not-exist`")
+        .run();
+}
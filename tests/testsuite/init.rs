@@ -305,6 +305,7 @@ fn invalid_dir_name() {
         .with_stderr(
             "\
 [ERROR] invalid character `.` in crate name: `foo.bar`, [..]
+help: a valid name would be `foo_bar`
 use --name to override crate name",
         )
         .run();
@@ -322,7 +323,8 @@ fn reserved_name() {
         .with_status(101)
         .with_stderr(
             "\
-[ERROR] the name `test` cannot be used as a crate name, it conflicts [..]\n\
+[ERROR] the name `test` cannot be used as a crate name, it conflicts [..]
+help: a valid name would be `test-rs`
 use --name to override crate name
 ",
         )
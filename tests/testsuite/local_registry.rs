@@ -488,3 +488,49 @@ fn crates_io_registry_url_is_optional() {
     p.cargo("build").with_stderr("[FINISHED] [..]").run();
     p.cargo("test").run();
 }
+
+#[cargo_test]
+fn malformed_index_entry() {
+    setup();
+    Package::new("bar", "0.0.1")
+        .local(true)
+        .file("src/lib.rs", "pub fn bar() {}")
+        .publish();
+
+    let index_file = registry_path().join("index/3/b/bar");
+    let mut contents = t!(fs::read(&index_file));
+    contents.extend_from_slice(b"{\"name\":\"bar\",\"vers\":\xff\n");
+    t!(fs::write(&index_file, contents));
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [project]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [dependencies]
+            bar = "0.0.1"
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("build")
+        .with_status(101)
+        .with_stderr_contains(
+            "[..]malformed entry for package `bar` in registry index file `3/b/bar` on line 2: [..]",
+        )
+        .run();
+
+    p.cargo("build")
+        .env("CARGO_REGISTRY_LENIENT_INDEX", "true")
+        .with_stderr_contains(
+            "[WARNING] skipping malformed entry for package `bar` in registry index file \
+             `3/b/bar` on line 2: [..]",
+        )
+        .with_stderr_contains("[COMPILING] bar v0.0.1")
+        .run();
+}
@@ -513,13 +513,142 @@ fn locked_correct_error() {
         .with_stderr(
             "\
 [UPDATING] `[..]` index
-error: the lock file [CWD]/Cargo.lock needs to be updated but --locked was passed to prevent this
-If you want to try to generate the lock file without accessing the network, use the --offline flag.
+error: the lock file [CWD]/Cargo.lock is missing but --locked was passed, which requires it to exist
+Run `cargo generate-lockfile` to create it, and commit it with the sources.
 ",
         )
         .run();
 }
 
+#[cargo_test]
+fn frozen_missing_lockfile() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("build --frozen")
+        .with_status(101)
+        .with_stderr(
+            "\
+error: the lock file [CWD]/Cargo.lock is missing but --frozen was passed, which requires it to exist
+Run `cargo generate-lockfile` to create it, and commit it with the sources.
+",
+        )
+        .run();
+
+    p.cargo("generate-lockfile").run();
+    p.cargo("build --frozen").run();
+}
+
+#[cargo_test]
+fn locked_source_mismatch() {
+    // A dependency moved from git to a registry is called out when the lock
+    // file can't be updated.
+    Package::new("bar", "0.1.0").publish();
+    let git_project = git::new("bar", |p| {
+        p.file("Cargo.toml", &basic_manifest("bar", "0.1.0"))
+            .file("src/lib.rs", "")
+    });
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            &format!(
+                r#"
+                [project]
+                name = "foo"
+                version = "0.0.1"
+
+                [dependencies]
+                bar = {{ git = '{}' }}
+            "#,
+                git_project.url()
+            ),
+        )
+        .file("src/lib.rs", "")
+        .build();
+    p.cargo("generate-lockfile").run();
+
+    p.change_file(
+        "Cargo.toml",
+        r#"
+            [project]
+            name = "foo"
+            version = "0.0.1"
+
+            [dependencies]
+            bar = "0.1.0"
+        "#,
+    );
+    p.cargo("build --locked")
+        .with_status(101)
+        .with_stderr_contains(
+            "error: the lock file [CWD]/Cargo.lock needs to be updated but --locked was passed \
+             to prevent this",
+        )
+        .with_stderr_contains(
+            "  the lock file has package `bar` from the git source `[..]bar`, but the manifest \
+             now requires it from the registry source `[..]`",
+        )
+        .with_stderr_contains("Run `cargo update -p bar` to update the lock file.")
+        .run();
+}
+
+#[cargo_test]
+fn locked_names_changed_packages() {
+    Package::new("bar", "0.1.0").publish();
+    Package::new("bar", "0.2.0").publish();
+    Package::new("baz", "1.0.0").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [project]
+            name = "foo"
+            version = "0.0.1"
+
+            [dependencies]
+            bar = "0.1"
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+    p.cargo("generate-lockfile").run();
+
+    p.change_file(
+        "Cargo.toml",
+        r#"
+            [project]
+            name = "foo"
+            version = "0.0.1"
+
+            [dependencies]
+            bar = "0.2"
+            baz = "1.0"
+        "#,
+    );
+    p.cargo("build --locked")
+        .with_status(101)
+        .with_stderr_contains(
+            "error: the lock file [CWD]/Cargo.lock needs to be updated but --locked was passed \
+             to prevent this",
+        )
+        .with_stderr_contains(
+            "\
+The following packages would change in the lock file:
+  bar: v0.1.0 -> v0.2.0
+  baz: added v1.0.0
+Run `cargo update -p bar` locally and commit the lock file.
+",
+        )
+        .run();
+
+    p.cargo("build").run();
+    let lock = p.read_lockfile();
+    assert!(lock.contains("name = \"bar\"\nversion = \"0.2.0\""));
+    assert!(lock.contains("name = \"baz\"\nversion = \"1.0.0\""));
+    p.cargo("build --locked").run();
+}
+
 #[cargo_test]
 fn v2_format_preserved() {
     let cksum = Package::new("bar", "0.1.0").publish();
@@ -23,6 +23,7 @@ mod build_script_env;
 mod cache_messages;
 mod cargo_alias_config;
 mod cargo_command;
+mod cargo_config;
 mod cargo_features;
 mod cargo_targets;
 mod cfg;
@@ -39,10 +40,12 @@ mod cross_publish;
 mod custom_target;
 mod death;
 mod dep_info;
+mod diagnostics_stream;
 mod directory;
 mod doc;
 mod edition;
 mod error;
+mod error_format;
 mod features;
 mod features2;
 mod fetch;
@@ -86,6 +89,7 @@ mod profile_custom;
 mod profile_overrides;
 mod profile_targets;
 mod profiles;
+mod proxy;
 mod pub_priv;
 mod publish;
 mod publish_lockfile;
@@ -95,6 +99,7 @@ mod rename_deps;
 mod replace;
 mod required_features;
 mod run;
+mod rust_version;
 mod rustc;
 mod rustc_info_cache;
 mod rustdoc;
@@ -102,6 +107,7 @@ mod rustdoc_extern_html;
 mod rustdocflags;
 mod rustflags;
 mod search;
+mod shared_cache;
 mod shell_quoting;
 mod standard_lib;
 mod test;
@@ -115,6 +121,7 @@ mod vendor;
 mod verify_project;
 mod version;
 mod warn_on_failure;
+mod warnings_exit_code;
 mod workspaces;
 mod yank;
 
@@ -66,7 +66,7 @@ fn cargo_renders() {
         .with_status(101)
         .with_stdout(
             "{\"reason\":\"compiler-artifact\",[..]\n\
-             {\"reason\":\"build-finished\",\"success\":false}",
+             {\"reason\":\"build-finished\",\"success\":false,[..]}",
         )
         .with_stderr_contains(
             "\
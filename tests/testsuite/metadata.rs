@@ -1,7 +1,9 @@
 //! Tests for the `cargo metadata` command.
 
 use cargo_test_support::registry::Package;
-use cargo_test_support::{basic_bin_manifest, basic_lib_manifest, main_file, project, rustc_host};
+use cargo_test_support::{
+    basic_bin_manifest, basic_lib_manifest, basic_manifest, git, main_file, project, rustc_host,
+};
 
 #[cargo_test]
 fn cargo_metadata_simple() {
@@ -2737,3 +2739,134 @@ fn dep_kinds_workspace() {
         )
         .run();
 }
+
+#[cargo_test]
+fn lib_bin_dev_dep_and_git_dep() {
+    Package::new("dev", "0.1.0").publish();
+    let git_project = git::new("gitdep", |p| {
+        p.file("Cargo.toml", &basic_manifest("gitdep", "0.2.0"))
+            .file("src/lib.rs", "")
+    });
+    let p = project()
+        .file(
+            "Cargo.toml",
+            &format!(
+                r#"
+                [package]
+                name = "foo"
+                version = "0.5.0"
+
+                [dependencies]
+                gitdep = {{ git = '{}' }}
+
+                [dev-dependencies]
+                dev = "0.1"
+            "#,
+                git_project.url()
+            ),
+        )
+        .file("src/lib.rs", "")
+        .file("src/main.rs", "fn main() {}")
+        .build();
+
+    let metadata = |p: &cargo_test_support::Project| {
+        let output = p
+            .cargo("metadata --format-version 1")
+            .exec_with_output()
+            .unwrap();
+        String::from_utf8(output.stdout).unwrap()
+    };
+    let stdout = metadata(&p);
+    // The output is the same every time.
+    assert_eq!(stdout, metadata(&p));
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+
+    assert_eq!(json["version"], 1);
+    let packages = json["packages"].as_array().unwrap();
+    let names: Vec<_> = packages
+        .iter()
+        .map(|p| p["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, ["dev", "foo", "gitdep"]);
+    let package = |name: &str| packages.iter().find(|p| p["name"] == name).unwrap();
+
+    let foo = package("foo");
+    let foo_id = foo["id"].as_str().unwrap();
+    assert!(foo_id.starts_with("foo 0.5.0 (path+file://"));
+    assert_eq!(foo["source"], serde_json::Value::Null);
+    assert!(foo["manifest_path"]
+        .as_str()
+        .unwrap()
+        .ends_with("Cargo.toml"));
+    let targets: Vec<_> = foo["targets"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|t| {
+            (
+                t["kind"][0].as_str().unwrap(),
+                t["name"].as_str().unwrap(),
+                t["src_path"].as_str().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(targets.len(), 2);
+    assert!(targets
+        .iter()
+        .any(|&(kind, name, src)| kind == "lib" && name == "foo" && src.ends_with("lib.rs")));
+    assert!(targets
+        .iter()
+        .any(|&(kind, name, src)| kind == "bin" && name == "foo" && src.ends_with("main.rs")));
+    assert_eq!(foo["features"], serde_json::json!({}));
+    let deps: Vec<_> = foo["dependencies"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|d| {
+            (
+                d["name"].as_str().unwrap(),
+                d["req"].as_str().unwrap(),
+                d["kind"].as_str(),
+            )
+        })
+        .collect();
+    assert!(deps.contains(&("gitdep", "*", None)));
+    assert!(deps.contains(&("dev", "^0.1", Some("dev"))));
+
+    let gitdep = package("gitdep");
+    assert!(gitdep["source"]
+        .as_str()
+        .unwrap()
+        .starts_with("git+file://"));
+    assert!(package("dev")["source"]
+        .as_str()
+        .unwrap()
+        .starts_with("registry+"));
+
+    let resolve = &json["resolve"];
+    assert_eq!(resolve["root"], foo_id);
+    let nodes = resolve["nodes"].as_array().unwrap();
+    assert_eq!(nodes.len(), 3);
+    let foo_node = nodes.iter().find(|n| n["id"] == foo_id).unwrap();
+    let mut dep_ids: Vec<_> = foo_node["dependencies"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|d| d.as_str().unwrap())
+        .collect();
+    dep_ids.sort();
+    assert_eq!(
+        dep_ids,
+        [
+            package("dev")["id"].as_str().unwrap(),
+            gitdep["id"].as_str().unwrap()
+        ]
+    );
+    assert!(nodes
+        .iter()
+        .filter(|n| n["id"] != foo_id)
+        .all(|n| n["dependencies"].as_array().unwrap().is_empty()));
+
+    assert_eq!(json["workspace_members"], serde_json::json!([foo_id]));
+    assert!(json["workspace_root"].as_str().unwrap().ends_with("foo"));
+}
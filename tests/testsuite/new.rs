@@ -116,7 +116,11 @@ fn existing() {
 fn invalid_characters() {
     cargo_process("new foo.rs")
         .with_status(101)
-        .with_stderr("[ERROR] invalid character `.` in crate name: `foo.rs`, [..]")
+        .with_stderr(
+            "\
+[ERROR] invalid character `.` in crate name: `foo.rs`, [..]
+help: a valid name would be `foo_rs`",
+        )
         .run();
 }
 
@@ -124,7 +128,11 @@ fn invalid_characters() {
 fn reserved_name() {
     cargo_process("new test")
         .with_status(101)
-        .with_stderr("[ERROR] the name `test` cannot be used as a crate name, it conflicts [..]")
+        .with_stderr(
+            "\
+[ERROR] the name `test` cannot be used as a crate name, it conflicts [..]
+help: a valid name would be `test-rs`",
+        )
         .run();
 }
 
@@ -133,7 +141,9 @@ fn reserved_binary_name() {
     cargo_process("new --bin incremental")
         .with_status(101)
         .with_stderr(
-            "[ERROR] the name `incremental` cannot be used as a crate name, it conflicts [..]",
+            "\
+[ERROR] the name `incremental` cannot be used as a crate name, it conflicts [..]
+help: a valid name would be `incremental-rs`",
         )
         .run();
 
@@ -153,7 +163,11 @@ it conflicts with cargo's build directory names
 fn keyword_name() {
     cargo_process("new pub")
         .with_status(101)
-        .with_stderr("[ERROR] the name `pub` cannot be used as a crate name, it is a Rust keyword")
+        .with_stderr(
+            "\
+[ERROR] the name `pub` cannot be used as a crate name, it is a Rust keyword
+help: a valid name would be `pub-rs`",
+        )
         .run();
 }
 
@@ -522,7 +536,11 @@ fn restricted_windows_name() {
         cargo_process("new nul")
             .env("USER", "foo")
             .with_status(101)
-            .with_stderr("[ERROR] cannot use name `nul`, it is a reserved Windows filename")
+            .with_stderr(
+                "\
+[ERROR] cannot use name `nul`, it is a reserved Windows filename
+help: a valid name would be `nul-rs`",
+            )
             .run();
     } else {
         cargo_process("new nul")
@@ -569,7 +587,8 @@ fn non_ascii_name_invalid() {
         .with_status(101)
         .with_stderr(
             "[ERROR] invalid character `¼` in crate name: `a¼`, \
-            characters must be Unicode XID characters (numbers, `-`, `_`, or most letters)",
+            characters must be Unicode XID characters (numbers, `-`, `_`, or most letters)\n\
+            help: a valid name would be `a_`",
         )
         .run();
 }
@@ -269,17 +269,17 @@ fn cargo_compile_forbird_git_httpsrepo_offline() {
         .file("src/main.rs", "")
         .build();
 
-    p.cargo("build --offline").with_status(101).with_stderr("\
+    p.cargo("build --offline")
+        .with_status(101)
+        .with_stderr(
+            "\
 [ERROR] failed to get `dep1` as a dependency of package `foo v0.5.0 [..]`
 
 Caused by:
-  failed to load source for dependency `dep1`
-
-Caused by:
-  Unable to update https://github.com/some_user/dep1.git
-
-Caused by:
-  can't checkout from 'https://github.com/some_user/dep1.git': you are in the offline mode (--offline)").run();
+  dependency `dep1` requires the git source `https://github.com/some_user/dep1.git`, \
+which has not been downloaded before and cannot be fetched in the offline mode (--offline)",
+        )
+        .run();
 }
 
 #[cargo_test]
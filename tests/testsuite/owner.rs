@@ -2,9 +2,9 @@
 
 use std::fs;
 
-use cargo_test_support::paths::CargoPathExt;
-use cargo_test_support::project;
+use cargo_test_support::paths::{self, CargoPathExt};
 use cargo_test_support::registry::{self, api_path};
+use cargo_test_support::{cargo_process, project};
 
 fn setup(name: &str, content: Option<&str>) {
     let dir = api_path().join(format!("api/v1/crates/{}", name));
@@ -118,3 +118,55 @@ Caused by:
         )
         .run();
 }
+
+#[cargo_test]
+fn add_outside_project() {
+    registry::init();
+    setup("foo", None);
+
+    // The file registry echoes nothing back, but keeps the request body.
+    cargo_process("owner -a username foo --token sekrit")
+        .with_status(101)
+        .with_stderr(
+            "    Updating `[..]` index
+error: failed to invite owners to crate foo: EOF while parsing a value at line 1 column 0",
+        )
+        .run();
+
+    let body = fs::read_to_string(api_path().join("api/v1/crates/foo/owners")).unwrap();
+    assert_eq!(body, r#"{"users":["username"]}"#);
+}
+
+#[cargo_test]
+fn not_an_owner() {
+    registry::init();
+    setup(
+        "foo",
+        Some(r#"{"errors": [{"detail": "you are not an owner of crate `foo`"}]}"#),
+    );
+
+    cargo_process("owner -l foo --token sekrit")
+        .with_status(101)
+        .with_stderr(
+            "    Updating `[..]` index
+error: failed to list owners of crate foo
+
+Caused by:
+  api errors (status [..]): you are not an owner of crate `foo`",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn no_token() {
+    registry::init();
+    setup("foo", None);
+    fs::remove_file(paths::home().join(".cargo/credentials")).unwrap();
+
+    cargo_process("owner -l foo")
+        .with_status(101)
+        .with_stderr_contains(
+            "error: no upload token found, please run `cargo login` or pass `--token`",
+        )
+        .run();
+}
@@ -156,6 +156,7 @@ See https://doc.rust-lang.org/cargo/reference/manifest.html#package-metadata for
 [ARCHIVING] Cargo.toml
 [ARCHIVING] Cargo.toml.orig
 [ARCHIVING] src/main.rs
+[CHECKSUM] [..] foo-0.0.1.crate
 ",
         )
         .run();
@@ -195,6 +196,7 @@ See https://doc.rust-lang.org/cargo/reference/manifest.html#package-metadata for
 [ARCHIVING] Cargo.toml
 [ARCHIVING] Cargo.toml.orig
 [ARCHIVING] src/lib.rs
+[CHECKSUM] [..] a-0.0.1.crate
 ",
         )
         .run();
@@ -384,6 +386,7 @@ See https://doc.rust-lang.org/cargo/reference/manifest.html#package-metadata for
 [ARCHIVING] some_dir/file_deep_4
 [ARCHIVING] some_dir/file_deep_5
 [ARCHIVING] src/main.rs
+[CHECKSUM] [..] foo-0.0.1.crate
 ",
         )
         .run();
@@ -451,6 +454,7 @@ See https://doc.rust-lang.org/cargo/reference/manifest.html#package-metadata for
 [ARCHIVING] Cargo.toml.orig
 [ARCHIVING] foo.txt
 [ARCHIVING] src/main.rs
+[CHECKSUM] [..] foo-0.0.1.crate
 ",
         )
         .run();
@@ -728,16 +732,77 @@ fn broken_symlink() {
     p.cargo("package -v")
         .with_status(101)
         .with_stderr_contains(
-            "\
-error: failed to prepare local package for uploading
+            "[ERROR] cannot package symbolic link `src[..]foo.rs`, \
+             its target `nowhere` does not exist",
+        )
+        .run();
+}
 
-Caused by:
-  failed to open for archiving: `[..]foo.rs`
+#[cargo_test]
+/// Tests that a symlink to a file is packaged with the contents of its target.
+///
+/// This test requires you to be able to make symlinks.
+/// For windows, this may require you to enable developer mode.
+fn package_symlink_to_file() {
+    if !symlink_supported() {
+        return;
+    }
 
-Caused by:
-  [..]
+    let p = project()
+        .file("src/main.rs", r#"fn main() { println!("hello"); }"#)
+        .file("shared/data.txt", "some data")
+        .symlink("shared/data.txt", "data.txt")
+        .build();
+
+    p.cargo("package --no-verify")
+        .with_stderr_contains("[WARNING] manifest has no description[..]")
+        .run();
+
+    let f = File::open(&p.root().join("target/package/foo-0.0.1.crate")).unwrap();
+    validate_crate_contents(
+        f,
+        "foo-0.0.1.crate",
+        &[
+            "Cargo.lock",
+            "Cargo.toml",
+            "Cargo.toml.orig",
+            "data.txt",
+            "shared/data.txt",
+            "src/main.rs",
+        ],
+        &[("data.txt", "some data")],
+    );
+}
+
+#[cargo_test]
+/// Tests that a symlink to a parent directory is skipped instead of being
+/// followed forever.
+///
+/// This test requires you to be able to make symlinks.
+/// For windows, this may require you to enable developer mode.
+fn package_symlink_cycle() {
+    if !symlink_supported() {
+        return;
+    }
+
+    let p = project()
+        .file("src/main.rs", r#"fn main() { println!("hello"); }"#)
+        .symlink_dir("src", "src/loop")
+        .build();
+
+    p.cargo("package --no-verify --list")
+        .with_stdout(
+            "\
+Cargo.lock
+Cargo.toml
+Cargo.toml.orig
+src/main.rs
 ",
         )
+        .with_stderr_contains(
+            "[WARNING] skipping symbolic link `[..]loop` which points to one of \
+             its parent directories and would form a cycle",
+        )
         .run();
 }
 
@@ -1528,6 +1593,7 @@ subdir/LICENSE
 [ARCHIVING] Cargo.toml.orig
 [ARCHIVING] src/lib.rs
 [ARCHIVING] subdir/LICENSE
+[CHECKSUM] [..] foo-1.0.0.crate
 ",
         )
         .run();
@@ -1845,3 +1911,151 @@ dependency `bar` does not specify a version.
         )
         .run();
 }
+
+#[cargo_test]
+fn list_reasons_git() {
+    // Ignored files stay out, and so do tracked files which are excluded.
+    let p = git::new("foo", |p| {
+        p.file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            exclude = ["data.txt"]
+            "#,
+        )
+        .file(".gitignore", "*.swp\n")
+        .file("data.txt", "")
+        .file("src/lib.rs", "")
+    });
+    p.change_file("src/lib.rs.swp", "");
+    p.change_file("notes.txt", "");
+
+    p.cargo("package --list --allow-dirty")
+        .with_stdout(
+            "\
+.gitignore
+Cargo.toml
+Cargo.toml.orig
+notes.txt
+src/lib.rs
+",
+        )
+        .run();
+
+    p.cargo("package --list --allow-dirty -v")
+        .with_stdout(
+            "\
+.gitignore (tracked by git)
+Cargo.toml (generated by cargo)
+Cargo.toml.orig (tracked by git)
+notes.txt (untracked, not ignored by git)
+src/lib.rs (tracked by git)
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn list_reasons_no_git() {
+    let p = project()
+        .file("Cargo.toml", &basic_manifest("foo", "0.1.0"))
+        .file(".DS_Store", "")
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("package --list -v")
+        .with_stdout(
+            "\
+Cargo.toml (generated by cargo)
+Cargo.toml.orig (found in package directory)
+src/lib.rs (found in package directory)
+",
+        )
+        .run();
+
+    p.change_file(
+        "Cargo.toml",
+        r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            include = ["src/*.rs"]
+        "#,
+    );
+    p.cargo("package --list -v")
+        .with_stdout(
+            "\
+Cargo.toml (generated by cargo)
+Cargo.toml.orig (always included)
+src/lib.rs (matches `package.include`)
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn reproducible_archive() {
+    // Packaging the same sources gives the same bytes, whatever order the
+    // files were created in and whatever their timestamps are.
+    let a = project()
+        .at("a")
+        .file("Cargo.toml", &basic_manifest("foo", "0.1.0"))
+        .file("build.sh", "#!/bin/sh\n")
+        .file("src/lib.rs", "pub mod util;")
+        .file("src/util.rs", "")
+        .build();
+    let b = project()
+        .at("b")
+        .file("src/util.rs", "")
+        .file("src/lib.rs", "pub mod util;")
+        .file("build.sh", "#!/bin/sh\n")
+        .file("Cargo.toml", &basic_manifest("foo", "0.1.0"))
+        .build();
+    for path in &["build.sh", "src/lib.rs", "src/util.rs", "Cargo.toml"] {
+        let time = filetime::FileTime::from_unix_time(1_000_000_000, 0);
+        t!(filetime::set_file_times(b.root().join(path), time, time));
+    }
+    #[cfg(unix)]
+    for p in &[&a, &b] {
+        use std::os::unix::fs::PermissionsExt;
+        let script = p.root().join("build.sh");
+        t!(fs::set_permissions(
+            &script,
+            fs::Permissions::from_mode(0o775)
+        ));
+    }
+
+    a.cargo("package --no-verify").run();
+    let a_crate = t!(fs::read(a.root().join("target/package/foo-0.1.0.crate")));
+    let checksum = cargo::util::Sha256::new().update(&a_crate).finish_hex();
+    b.cargo("package --no-verify -v")
+        .with_stderr_contains(&format!("[CHECKSUM] {} foo-0.1.0.crate", checksum))
+        .run();
+    let b_crate = t!(fs::read(b.root().join("target/package/foo-0.1.0.crate")));
+    assert!(a_crate == b_crate, "archives differ");
+
+    let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(&a_crate[..]));
+    let mut entries = Vec::new();
+    for entry in t!(archive.entries()) {
+        let entry = t!(entry);
+        let header = entry.header();
+        assert_eq!(t!(header.mtime()), 1153704088);
+        assert_eq!(t!(header.uid()), 0);
+        assert_eq!(t!(header.gid()), 0);
+        let path = t!(entry.path()).to_str().unwrap().to_string();
+        entries.push((path, t!(header.mode())));
+    }
+    let script_mode = if cfg!(unix) { 0o755 } else { 0o644 };
+    assert_eq!(
+        entries,
+        [
+            ("foo-0.1.0/Cargo.toml".to_string(), 0o644),
+            ("foo-0.1.0/Cargo.toml.orig".to_string(), 0o644),
+            ("foo-0.1.0/build.sh".to_string(), script_mode),
+            ("foo-0.1.0/src/lib.rs".to_string(), 0o644),
+            ("foo-0.1.0/src/util.rs".to_string(), 0o644),
+        ]
+    );
+}
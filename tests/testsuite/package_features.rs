@@ -305,6 +305,64 @@ fn other_member_from_current() {
         .run();
 }
 
+#[cargo_test]
+fn features_of_other_members() {
+    // member/feat for members which the current package doesn't depend on.
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [workspace]
+            members = ["a", "b"]
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            "a/Cargo.toml",
+            r#"
+            [package]
+            name = "a"
+            version = "0.1.0"
+
+            [features]
+            x = []
+            "#,
+        )
+        .file("a/src/lib.rs", "")
+        .file(
+            "b/Cargo.toml",
+            r#"
+            [package]
+            name = "b"
+            version = "0.1.0"
+
+            [features]
+            y = []
+            "#,
+        )
+        .file("b/src/lib.rs", "")
+        .build();
+
+    p.cargo("check --workspace --features a/x,b/y")
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] `--features` only applies to the current package `foo`, \
+but `a/x`, `b/y` select features of other workspace members
+Run the command from the directory of each member, or pass \
+`-Z package-features` to select features of several members at once.
+",
+        )
+        .run();
+
+    p.cargo("check --workspace --features a/x,b/y -Zpackage-features")
+        .masquerade_as_nightly_cargo()
+        .run();
+}
+
 #[cargo_test]
 fn virtual_member_slash() {
     // member slash feature syntax
@@ -184,6 +184,10 @@ fn cargo_compile_with_root_dev_deps_with_testing() {
     p.cargo("test")
         .with_stderr(
             "\
+[WARNING] path dependency `bar` of package `foo` resolves to `[ROOT]/bar`, \
+which is outside of the workspace root `[ROOT]/foo`
+It may not exist on other machines. Move it into the workspace, or set \
+`build.path-dependency-escape` to `allow`.
 [COMPILING] [..] v0.5.0 ([..])
 [COMPILING] [..] v0.5.0 ([..])
 [FINISHED] test [unoptimized + debuginfo] target(s) in [..]
@@ -870,6 +874,14 @@ fn override_and_depend() {
         .cwd("b")
         .with_stderr(
             "\
+[WARNING] path dependency `a1` of package `b` resolves to `[ROOT]/foo/a/a1`, \
+which is outside of the workspace root `[ROOT]/foo/b`
+It may not exist on other machines. Move it into the workspace, or set \
+`build.path-dependency-escape` to `allow`.
+[WARNING] path dependency `a2` of package `b` resolves to `[ROOT]/foo/a/a2`, \
+which is outside of the workspace root `[ROOT]/foo/b`
+It may not exist on other machines. Move it into the workspace, or set \
+`build.path-dependency-escape` to `allow`.
 [COMPILING] a2 v0.5.0 ([..])
 [COMPILING] a1 v0.5.0 ([..])
 [COMPILING] b v0.5.0 ([..])
@@ -1063,3 +1075,76 @@ Caused by:
         )
         .run();
 }
+
+#[cargo_test]
+fn path_dependency_escape() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+
+            [dependencies]
+            bar = { path = "../bar" }
+            baz = { path = "baz" }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("baz/Cargo.toml", &basic_manifest("baz", "0.1.0"))
+        .file("baz/src/lib.rs", "")
+        .build();
+    let _bar = project()
+        .at("bar")
+        .file("Cargo.toml", &basic_manifest("bar", "0.1.0"))
+        .file("src/lib.rs", "")
+        .build();
+
+    // Warned about by default.
+    p.cargo("check")
+        .with_stderr(
+            "\
+[WARNING] path dependency `bar` of package `foo` resolves to `[ROOT]/bar`, \
+which is outside of the workspace root `[ROOT]/foo`
+It may not exist on other machines. Move it into the workspace, or set \
+`build.path-dependency-escape` to `allow`.
+[CHECKING] ba[..] v0.1.0 ([..])
+[CHECKING] ba[..] v0.1.0 ([..])
+[CHECKING] foo v0.1.0 ([ROOT]/foo)
+[FINISHED] [..]
+",
+        )
+        .run();
+
+    p.cargo("check")
+        .env("CARGO_BUILD_PATH_DEPENDENCY_ESCAPE", "warn")
+        .with_stderr(
+            "\
+[WARNING] path dependency `bar` of package `foo` resolves to `[ROOT]/bar`, \
+which is outside of the workspace root `[ROOT]/foo`
+It may not exist on other machines. Move it into the workspace, or set \
+`build.path-dependency-escape` to `allow`.
+[FINISHED] [..]
+",
+        )
+        .run();
+
+    p.cargo("check")
+        .env("CARGO_BUILD_PATH_DEPENDENCY_ESCAPE", "allow")
+        .with_stderr("[FINISHED] [..]")
+        .run();
+
+    p.cargo("check")
+        .env("CARGO_BUILD_PATH_DEPENDENCY_ESCAPE", "deny")
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] path dependency `bar` of package `foo` resolves to `[ROOT]/bar`, \
+which is outside of the workspace root `[ROOT]/foo`
+It may not exist on other machines. Move it into the workspace, or set \
+`build.path-dependency-escape` to `allow`.
+",
+        )
+        .run();
+}
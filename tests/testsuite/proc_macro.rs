@@ -434,11 +434,35 @@ fn proc_macro_crate_type_multiple() {
 [ERROR] failed to parse manifest at `[..]/foo/Cargo.toml`
 
 Caused by:
-  cannot mix `proc-macro` crate type with others
+  library `foo` has the `proc-macro` crate type, which can't be mixed with `rlib`
+A proc-macro crate can only export procedural macros. Move the rest of the code into a \
+separate library package.
 ",
         )
         .with_status(101)
         .run();
+
+    foo.change_file(
+        "Cargo.toml",
+        r#"
+        [package]
+        name = "foo"
+        version = "0.1.0"
+        [lib]
+        proc-macro = true
+        crate-type = ["cdylib", "rlib"]
+        "#,
+    );
+    foo.cargo("build")
+        .with_stderr(
+            "\
+[WARNING] library `foo` sets `proc-macro = true`, which is ignored because `crate-type` is \
+set to `cdylib`, `rlib`
+[COMPILING] foo v0.1.0 ([CWD])
+[FINISHED] [..]
+",
+        )
+        .run();
 }
 
 #[cargo_test]
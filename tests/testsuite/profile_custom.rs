@@ -200,6 +200,40 @@ fn non_existent_inherits() {
         .run();
 }
 
+#[cargo_test]
+fn misspelled_inherits() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            cargo-features = ["named-profiles"]
+
+            [package]
+            name = "foo"
+            version = "0.0.1"
+            authors = []
+
+            [profile.release-lto]
+            codegen-units = 7
+            inherits = "releas"
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("build")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] profile `release-lto` inherits from `releas`, but that profile is not defined
+
+	Did you mean `release`?
+",
+        )
+        .run();
+}
+
 #[cargo_test]
 fn self_inherits() {
     let p = project()
@@ -213,6 +213,14 @@ fn profile_override_hierarchy() {
     // m1: 1 (as [profile.dev])
 
     p.cargo("build -v").with_stderr_unordered("\
+[WARNING] path dependency `dep` of package `m1` resolves to `[ROOT]/dep`, \
+which is outside of the workspace root `[ROOT]/foo`
+It may not exist on other machines. Move it into the workspace, or set \
+`build.path-dependency-escape` to `allow`.
+[WARNING] path dependency `dep` of package `m2` resolves to `[ROOT]/dep`, \
+which is outside of the workspace root `[ROOT]/foo`
+It may not exist on other machines. Move it into the workspace, or set \
+`build.path-dependency-escape` to `allow`.
 [COMPILING] m3 [..]
 [COMPILING] dep [..]
 [RUNNING] `rustc --crate-name m3 m3/src/lib.rs [..] --crate-type lib --emit=[..]link[..]-C codegen-units=4 [..]
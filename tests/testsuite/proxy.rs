@@ -0,0 +1,252 @@
+//! Tests for HTTP proxy configuration.
+
+use std::io::prelude::*;
+use std::io::BufReader;
+use std::net::{SocketAddr, TcpListener};
+use std::thread::{self, JoinHandle};
+
+use cargo_test_support::{paths, project, Project};
+
+/// Starts a proxy which records the request line of the first request made
+/// through it and answers it with a 404.
+fn recording_proxy() -> (SocketAddr, JoinHandle<String>) {
+    let server = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = server.local_addr().unwrap();
+    let t = thread::spawn(move || {
+        let mut conn = BufReader::new(server.accept().unwrap().0);
+        let mut request = String::new();
+        conn.read_line(&mut request).unwrap();
+        // Drain the headers before responding.
+        let mut line = String::new();
+        while conn.read_line(&mut line).unwrap() > 2 {
+            line.clear();
+        }
+        conn.get_mut()
+            .write_all(b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n")
+            .unwrap();
+        request.trim().to_string()
+    });
+    (addr, t)
+}
+
+/// A project with a git dependency on `url`, with `config` appended to its
+/// `.cargo/config`.
+fn git_dep_project(url: &str, config: &str) -> Project {
+    project()
+        .file(
+            "Cargo.toml",
+            &format!(
+                r#"
+                [project]
+                name = "foo"
+                version = "0.0.1"
+                authors = []
+
+                [dependencies.bar]
+                git = "{}"
+            "#,
+                url
+            ),
+        )
+        .file("src/main.rs", "")
+        .file(
+            ".cargo/config",
+            &format!(
+                "
+                [net]
+                retry = 0
+                {}
+                ",
+                config
+            ),
+        )
+        .build()
+}
+
+#[cargo_test]
+fn config_proxy() {
+    let (addr, t) = recording_proxy();
+    let p = git_dep_project(
+        "http://needs-proxy.invalid/foo/bar",
+        &format!("[http]\nproxy = \"{}\"", addr),
+    );
+
+    p.cargo("build -v")
+        .with_status(101)
+        .with_stderr_contains(&format!(
+            "[NOTE] using proxy `{}` for `http://needs-proxy.invalid/foo/bar` \
+             (from `http.proxy` in [..]config)",
+            addr
+        ))
+        .run();
+
+    assert_eq!(
+        t.join().unwrap(),
+        "GET http://needs-proxy.invalid/foo/bar/info/refs?service=git-upload-pack HTTP/1.1"
+    );
+}
+
+#[cargo_test]
+fn env_proxy() {
+    let (addr, t) = recording_proxy();
+    let p = git_dep_project("http://needs-proxy.invalid/foo/bar", "");
+
+    p.cargo("build -v")
+        .env("http_proxy", addr.to_string())
+        .with_status(101)
+        .with_stderr_contains(&format!(
+            "[NOTE] using proxy `{}` for `http://needs-proxy.invalid/foo/bar` \
+             (from environment variable `http_proxy`)",
+            addr
+        ))
+        .run();
+
+    assert_eq!(
+        t.join().unwrap(),
+        "GET http://needs-proxy.invalid/foo/bar/info/refs?service=git-upload-pack HTTP/1.1"
+    );
+}
+
+#[cargo_test]
+fn uppercase_env_proxy() {
+    let (addr, t) = recording_proxy();
+    let p = git_dep_project("http://needs-proxy.invalid/foo/bar", "");
+
+    p.cargo("build -v")
+        .env("HTTP_PROXY", addr.to_string())
+        .with_status(101)
+        .with_stderr_contains(&format!(
+            "[NOTE] using proxy `{}` for `http://needs-proxy.invalid/foo/bar` \
+             (from environment variable `HTTP_PROXY`)",
+            addr
+        ))
+        .run();
+
+    assert_eq!(
+        t.join().unwrap(),
+        "GET http://needs-proxy.invalid/foo/bar/info/refs?service=git-upload-pack HTTP/1.1"
+    );
+}
+
+#[cargo_test]
+fn git_config_proxy() {
+    let (addr, t) = recording_proxy();
+    let config = paths::home().join(".gitconfig");
+    let mut config = git2::Config::open(&config).unwrap();
+    config.set_str("http.proxy", &addr.to_string()).unwrap();
+    let p = git_dep_project("http://needs-proxy.invalid/foo/bar", "");
+
+    p.cargo("build -v")
+        .with_status(101)
+        .with_stderr_contains(&format!(
+            "[NOTE] using proxy `{}` for `http://needs-proxy.invalid/foo/bar` \
+             (from git's `http.proxy` configuration)",
+            addr
+        ))
+        .run();
+
+    assert_eq!(
+        t.join().unwrap(),
+        "GET http://needs-proxy.invalid/foo/bar/info/refs?service=git-upload-pack HTTP/1.1"
+    );
+}
+
+#[cargo_test]
+fn env_proxy_overrides_git_config() {
+    let (addr, t) = recording_proxy();
+    let config = paths::home().join(".gitconfig");
+    let mut config = git2::Config::open(&config).unwrap();
+    config.set_str("http.proxy", "127.0.0.1:1").unwrap();
+    let p = git_dep_project("http://needs-proxy.invalid/foo/bar", "");
+
+    p.cargo("build -v")
+        .env("http_proxy", addr.to_string())
+        .with_status(101)
+        .with_stderr_contains("[NOTE] using proxy [..] (from environment variable `http_proxy`)")
+        .run();
+
+    assert_eq!(
+        t.join().unwrap(),
+        "GET http://needs-proxy.invalid/foo/bar/info/refs?service=git-upload-pack HTTP/1.1"
+    );
+}
+
+#[cargo_test]
+fn no_proxy_bypasses_proxy() {
+    let proxy = TcpListener::bind("127.0.0.1:0").unwrap();
+    proxy.set_nonblocking(true).unwrap();
+    let proxy_addr = proxy.local_addr().unwrap();
+    // The "origin" server records requests the same way as the proxy.
+    let (origin_addr, t) = recording_proxy();
+    let p = git_dep_project(
+        &format!("http://{}/foo/bar", origin_addr),
+        &format!("[http]\nproxy = \"{}\"", proxy_addr),
+    );
+
+    p.cargo("build -v")
+        .env("no_proxy", "example.com, 127.0.0.1")
+        .with_status(101)
+        .with_stderr_does_not_contain("[..]using proxy[..]")
+        .run();
+
+    assert_eq!(
+        t.join().unwrap(),
+        "GET /foo/bar/info/refs?service=git-upload-pack HTTP/1.1"
+    );
+    assert_eq!(
+        proxy.accept().unwrap_err().kind(),
+        std::io::ErrorKind::WouldBlock
+    );
+}
+
+#[cargo_test]
+fn fetch_with_cli_uses_proxy() {
+    let (addr, t) = recording_proxy();
+    let p = git_dep_project(
+        "http://needs-proxy.invalid/foo/bar",
+        &format!("git-fetch-with-cli = true\n[http]\nproxy = \"{}\"", addr),
+    );
+
+    p.cargo("build -v")
+        .with_status(101)
+        .with_stderr_contains(&format!(
+            "[RUNNING] `git -c 'http.proxy={}' fetch [..]`",
+            addr
+        ))
+        .run();
+
+    assert!(t
+        .join()
+        .unwrap()
+        .starts_with("GET http://needs-proxy.invalid/foo/bar/info/refs?service=git-upload-pack"));
+}
+
+#[cargo_test]
+fn invalid_proxy_url() {
+    let p = git_dep_project(
+        "http://needs-proxy.invalid/foo/bar",
+        "[http]\nproxy = \"ftp://127.0.0.1:8080\"",
+    );
+
+    p.cargo("build")
+        .with_status(101)
+        .with_stderr_contains(
+            "  invalid proxy URL `ftp://127.0.0.1:8080` from `http.proxy` in [..]config: \
+             the `ftp` scheme is not supported for proxies",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn invalid_env_proxy_warns() {
+    let p = git_dep_project("http://needs-proxy.invalid/foo/bar", "");
+
+    p.cargo("build")
+        .env("http_proxy", "ftp://127.0.0.1:1")
+        .with_status(101)
+        .with_stderr_contains(
+            "[WARNING] invalid proxy URL `ftp://127.0.0.1:1` from environment variable \
+             `http_proxy`: the `ftp` scheme is not supported for proxies",
+        )
+        .run();
+}
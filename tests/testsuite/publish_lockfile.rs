@@ -133,6 +133,7 @@ src/main.rs
 [COMPILING] foo v0.0.1 ([..])
 [RUNNING] `rustc --crate-name foo src/main.rs [..]
 [FINISHED] dev [unoptimized + debuginfo] target(s) in [..]
+[CHECKSUM] [..] foo-0.0.1.crate
 ",
         )
         .run();
@@ -227,6 +228,7 @@ fn note_resolve_changes() {
 [ARCHIVING] Cargo.toml
 [ARCHIVING] Cargo.toml.orig
 [ARCHIVING] src/main.rs
+[CHECKSUM] [..] foo-0.0.1.crate
 [UPDATING] `[..]` index
 [NOTE] package `mutli v0.1.0` added to the packaged Cargo.lock file, was originally sourced from `[..]/foo/mutli`
 [NOTE] package `patched v1.0.0` added to the packaged Cargo.lock file, was originally sourced from `[..]/foo/patched`
@@ -448,6 +450,7 @@ src/main.rs
 [COMPILING] foo v0.0.1 ([..])
 [RUNNING] `rustc --crate-name foo src/main.rs [..]
 [FINISHED] dev [unoptimized + debuginfo] target(s) in [..]
+[CHECKSUM] [..] foo-0.0.1.crate
 ",
         )
         .run();
@@ -474,6 +477,7 @@ fn ignore_lockfile_inner() {
 [ARCHIVING] Cargo.toml
 [ARCHIVING] Cargo.toml.orig
 [ARCHIVING] src/main.rs
+[CHECKSUM] [..] bar-0.0.1.crate
 ",
         )
         .run();
@@ -1,6 +1,6 @@
 //! Tests for the `cargo read-manifest` command.
 
-use cargo_test_support::{basic_bin_manifest, main_file, project};
+use cargo_test_support::{basic_bin_manifest, basic_manifest, main_file, project};
 
 fn manifest_output(readme_value: &str) -> String {
     format!(
@@ -20,6 +20,8 @@ fn manifest_output(readme_value: &str) -> String {
     "license_file": null,
     "links": null,
     "description": null,
+    "documentation": null,
+    "homepage": null,
     "edition": "2015",
     "source":null,
     "dependencies":[],
@@ -198,3 +200,89 @@ fn cargo_read_manifest_defaults_readme_if_true() {
         .with_json(&manifest_output(r#""README.md""#))
         .run();
 }
+
+#[cargo_test]
+fn read_manifest_is_normalized() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+                [package]
+                name = "foo"
+                version = "0.1.0"
+                homepage = "https://example.com"
+
+                [dependencies]
+                bar = "1.0"
+                baz = { path = "baz" }
+                qux = { git = "https://example.com/qux", branch = "dev", optional = true }
+
+                [dev-dependencies]
+                quux = { version = "0.2", features = ["a"] }
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("src/main.rs", "fn main() {}")
+        .file("baz/Cargo.toml", &basic_manifest("baz", "0.1.0"))
+        .file("baz/src/lib.rs", "")
+        .build();
+
+    let first = p.cargo("read-manifest").exec_with_output().unwrap();
+
+    p.change_file(
+        "Cargo.toml",
+        r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            homepage = "https://example.com"
+
+            [dependencies.bar]
+            version = "1.0"
+
+            [dependencies.baz]
+            path = "baz"
+
+            [dependencies.qux]
+            git = "https://example.com/qux"
+            branch = "dev"
+            optional = true
+
+            [dev-dependencies.quux]
+            version = "0.2"
+            features = ["a"]
+        "#,
+    );
+    let second = p.cargo("read-manifest").exec_with_output().unwrap();
+    assert_eq!(
+        String::from_utf8_lossy(&first.stdout),
+        String::from_utf8_lossy(&second.stdout)
+    );
+
+    let json: serde_json::Value = serde_json::from_slice(&first.stdout).unwrap();
+    assert_eq!(json["homepage"], "https://example.com");
+    assert!(json["manifest_path"]
+        .as_str()
+        .unwrap()
+        .ends_with("Cargo.toml"));
+
+    let targets = json["targets"].as_array().unwrap();
+    let lib = targets.iter().find(|t| t["kind"][0] == "lib").unwrap();
+    assert!(lib["src_path"].as_str().unwrap().ends_with("lib.rs"));
+    let bin = targets.iter().find(|t| t["kind"][0] == "bin").unwrap();
+    assert_eq!(bin["name"], "foo");
+    assert!(bin["src_path"].as_str().unwrap().ends_with("main.rs"));
+
+    let deps = json["dependencies"].as_array().unwrap();
+    let dep = |name: &str| deps.iter().find(|d| d["name"] == name).unwrap();
+    assert_eq!(dep("bar")["req"], "^1.0");
+    assert_eq!(dep("bar")["kind"], serde_json::Value::Null);
+    assert!(dep("baz")["path"].as_str().unwrap().ends_with("baz"));
+    assert_eq!(
+        dep("qux")["source"],
+        "git+https://example.com/qux?branch=dev"
+    );
+    assert_eq!(dep("qux")["optional"], true);
+    assert_eq!(dep("quux")["kind"], "dev");
+    assert_eq!(dep("quux")["features"][0], "a");
+}
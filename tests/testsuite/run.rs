@@ -415,6 +415,9 @@ automatically infer them to be a target, such as in subfolders.
 For more information on this warning you can consult
 https://github.com/rust-lang/cargo/issues/5330
 error: no example target named `a`
+
+available example targets:
+    do_magic
 ",
         )
         .run();
@@ -439,7 +442,14 @@ fn run_example_autodiscover_2015_with_autoexamples_disabled() {
     let p = autodiscover_examples_project("2015", Some(false));
     p.cargo("run --example a")
         .with_status(101)
-        .with_stderr("error: no example target named `a`\n")
+        .with_stderr(
+            "\
+error: no example target named `a`
+
+available example targets:
+    do_magic
+",
+        )
         .run();
 }
 
@@ -511,7 +521,13 @@ fn run_with_filename() {
 
     p.cargo("run --bin bin.rs")
         .with_status(101)
-        .with_stderr("[ERROR] no bin target named `bin.rs`")
+        .with_stderr(
+            "\
+[ERROR] no bin target named `bin.rs`
+
+available bin targets:
+    a",
+        )
         .run();
 
     p.cargo("run --bin a.rs")
@@ -520,13 +536,22 @@ fn run_with_filename() {
             "\
 [ERROR] no bin target named `a.rs`
 
-<tab>Did you mean `a`?",
+<tab>Did you mean `a`?
+
+available bin targets:
+    a",
         )
         .run();
 
     p.cargo("run --example example.rs")
         .with_status(101)
-        .with_stderr("[ERROR] no example target named `example.rs`")
+        .with_stderr(
+            "\
+[ERROR] no example target named `example.rs`
+
+available example targets:
+    a",
+        )
         .run();
 
     p.cargo("run --example a.rs")
@@ -535,7 +560,10 @@ fn run_with_filename() {
             "\
 [ERROR] no example target named `a.rs`
 
-<tab>Did you mean `a`?",
+<tab>Did you mean `a`?
+
+available example targets:
+    a",
         )
         .run();
 }
@@ -1162,3 +1190,25 @@ fn run_link_system_path_macos() {
     p2.cargo("run").env(VAR, &libdir).run();
     p2.cargo("test").env(VAR, &libdir).run();
 }
+
+#[cargo_test]
+fn forwards_args_after_double_dash_verbatim() {
+    let p = project()
+        .file(
+            "src/main.rs",
+            r#"
+            fn main() {
+                let args: Vec<String> = std::env::args().skip(1).collect();
+                assert_eq!(args, ["a filter", "--verbose", ""]);
+            }
+        "#,
+        )
+        .build();
+
+    let mut execs = p.cargo("run -v --");
+    execs.arg("a filter").arg("--verbose").arg("");
+    if cfg!(unix) {
+        execs.with_stderr_contains("[RUNNING] `target/debug/foo 'a filter' --verbose ''`");
+    }
+    execs.run();
+}
@@ -0,0 +1,98 @@
+//! Tests for the `rust-version` manifest key.
+
+use cargo_test_support::project;
+
+fn manifest(name: &str, rust_version: &str, deps: &str) -> String {
+    format!(
+        r#"
+        cargo-features = ["rust-version"]
+
+        [package]
+        name = "{}"
+        version = "0.1.0"
+        rust-version = "{}"
+
+        [dependencies]
+        {}
+        "#,
+        name, rust_version, deps
+    )
+}
+
+#[cargo_test]
+fn gated() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            rust-version = "1.40"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("build")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr_contains("  feature `rust-version` is required")
+        .run();
+}
+
+#[cargo_test]
+fn invalid() {
+    let p = project()
+        .file("Cargo.toml", &manifest("foo", "^1.40", ""))
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("build")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] failed to parse manifest at `[..]`
+
+Caused by:
+  `rust-version` must be a version like \"1.40\" or \"1.40.0\", but found `^1.40`
+",
+        )
+        .run();
+}
+
+#[cargo_test]
+fn supported() {
+    let p = project()
+        .file("Cargo.toml", &manifest("foo", "1.0", ""))
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("build").masquerade_as_nightly_cargo().run();
+}
+
+#[cargo_test]
+fn too_old() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            &manifest("foo", "1.0", "bar = { path = \"bar\" }"),
+        )
+        .file("src/lib.rs", "")
+        .file("bar/Cargo.toml", &manifest("bar", "99.0", ""))
+        .file("bar/src/lib.rs", "")
+        .build();
+
+    p.cargo("build")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] package `bar v0.1.0` cannot be built because it requires rustc 99.0 or newer, \
+while the currently active rustc version is [..]
+Update the toolchain, or use an older version of the package which supports rustc [..]
+",
+        )
+        .run();
+}
@@ -184,6 +184,10 @@ fn build_foo_with_bar_dependency() {
     foo.cargo("rustc -v -- -C debug-assertions")
         .with_stderr(
             "\
+[WARNING] path dependency `bar` of package `foo` resolves to `[ROOT]/bar`, \
+which is outside of the workspace root `[ROOT]/foo`
+It may not exist on other machines. Move it into the workspace, or set \
+`build.path-dependency-escape` to `allow`.
 [COMPILING] bar v0.1.0 ([..])
 [RUNNING] `[..] -C debuginfo=2 [..]`
 [COMPILING] foo v0.0.1 ([CWD])
@@ -220,6 +224,10 @@ fn build_only_bar_dependency() {
     foo.cargo("rustc -v -p bar -- -C debug-assertions")
         .with_stderr(
             "\
+[WARNING] path dependency `bar` of package `foo` resolves to `[ROOT]/bar`, \
+which is outside of the workspace root `[ROOT]/foo`
+It may not exist on other machines. Move it into the workspace, or set \
+`build.path-dependency-escape` to `allow`.
 [COMPILING] bar v0.1.0 ([..])
 [RUNNING] `rustc --crate-name bar [..]--crate-type lib [..] -C debug-assertions [..]`
 [FINISHED] dev [unoptimized + debuginfo] target(s) in [..]
@@ -387,6 +395,7 @@ fn rustc_fingerprint() {
         .with_stderr_does_not_contain("-C debug-assertions")
         .with_stderr(
             "\
+[DIRTY] foo [..] lib: profile flags changed
 [COMPILING] foo [..]
 [RUNNING] `rustc [..]
 [FINISHED] [..]
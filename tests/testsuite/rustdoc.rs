@@ -65,6 +65,10 @@ fn rustdoc_foo_with_bar_dependency() {
     foo.cargo("rustdoc -v -- --cfg=foo")
         .with_stderr(
             "\
+[WARNING] path dependency `bar` of package `foo` resolves to `[ROOT]/bar`, \
+which is outside of the workspace root `[ROOT]/foo`
+It may not exist on other machines. Move it into the workspace, or set \
+`build.path-dependency-escape` to `allow`.
 [CHECKING] bar v0.0.1 ([..])
 [RUNNING] `rustc [..]bar/src/lib.rs [..]`
 [DOCUMENTING] foo v0.0.1 ([CWD])
@@ -106,6 +110,10 @@ fn rustdoc_only_bar_dependency() {
     foo.cargo("rustdoc -v -p bar -- --cfg=foo")
         .with_stderr(
             "\
+[WARNING] path dependency `bar` of package `foo` resolves to `[ROOT]/bar`, \
+which is outside of the workspace root `[ROOT]/foo`
+It may not exist on other machines. Move it into the workspace, or set \
+`build.path-dependency-escape` to `allow`.
 [DOCUMENTING] bar v0.0.1 ([..])
 [RUNNING] `rustdoc [..]--crate-name bar [..]bar/src/lib.rs [..]\
         -o [CWD]/target/doc \
@@ -0,0 +1,89 @@
+//! Tests for the -Zshared-cache feature.
+
+use cargo_test_support::paths;
+use cargo_test_support::registry::Package;
+use cargo_test_support::{project, Project};
+use std::fs;
+
+fn project_with_dep(name: &str) -> Project {
+    project()
+        .at(name)
+        .file(
+            "Cargo.toml",
+            &format!(
+                r#"
+                [package]
+                name = "{}"
+                version = "0.1.0"
+
+                [dependencies]
+                dep = "1.0"
+                "#,
+                name
+            ),
+        )
+        .file("src/lib.rs", "")
+        .build()
+}
+
+#[cargo_test]
+fn shares_dependency_between_projects() {
+    Package::new("dep", "1.0.0").publish();
+    let a = project_with_dep("a");
+    let b = project_with_dep("b");
+
+    a.cargo("build -v -Zshared-cache")
+        .masquerade_as_nightly_cargo()
+        .with_stderr_contains("[RUNNING] `rustc --crate-name dep [..]")
+        .run();
+
+    // Restoring from the cache is still reported as compiling the dependency.
+    b.cargo("build -v -Zshared-cache")
+        .masquerade_as_nightly_cargo()
+        .with_stderr_contains("[COMPILING] dep v1.0.0")
+        .with_stderr_does_not_contain("[RUNNING] `rustc --crate-name dep [..]")
+        .with_stderr_contains("[RUNNING] `rustc --crate-name b [..]")
+        .run();
+
+    // Without the flag, the cache is not consulted.
+    b.cargo("clean").run();
+    b.cargo("build -v")
+        .with_stderr_contains("[RUNNING] `rustc --crate-name dep [..]")
+        .run();
+}
+
+#[cargo_test]
+fn evicts_corrupt_entry() {
+    Package::new("dep", "1.0.0").publish();
+    let a = project_with_dep("a");
+    let b = project_with_dep("b");
+
+    a.cargo("build -Zshared-cache")
+        .masquerade_as_nightly_cargo()
+        .run();
+
+    let cache = paths::home().join(".cargo/artifact-cache");
+    for entry in fs::read_dir(&cache).unwrap() {
+        let entry = entry.unwrap().path();
+        if entry.is_dir() {
+            for file in fs::read_dir(&entry).unwrap() {
+                let file = file.unwrap().path();
+                if file.extension().map_or(false, |e| e == "rlib") {
+                    fs::write(&file, "truncated").unwrap();
+                }
+            }
+        }
+    }
+
+    b.cargo("build -v -Zshared-cache")
+        .masquerade_as_nightly_cargo()
+        .with_stderr_contains("[RUNNING] `rustc --crate-name dep [..]")
+        .run();
+
+    // The entry was replaced with a good one.
+    b.cargo("clean").run();
+    b.cargo("build -v -Zshared-cache")
+        .masquerade_as_nightly_cargo()
+        .with_stderr_does_not_contain("[RUNNING] `rustc --crate-name dep [..]")
+        .run();
+}
@@ -518,6 +518,10 @@ fn test_with_deep_lib_dep() {
     p.cargo("test")
         .with_stderr(
             "\
+[WARNING] path dependency `bar` of package `foo` resolves to `[ROOT]/bar`, \
+which is outside of the workspace root `[ROOT]/foo`
+It may not exist on other machines. Move it into the workspace, or set \
+`build.path-dependency-escape` to `allow`.
 [COMPILING] bar v0.0.1 ([..])
 [COMPILING] foo v0.0.1 ([CWD])
 [FINISHED] test [unoptimized + debuginfo] target(s) in [..]
@@ -640,6 +644,22 @@ fn external_test_implicit() {
         .run();
 }
 
+#[cargo_test]
+fn external_test_compile_error_names_file() {
+    let p = project()
+        .file("src/lib.rs", "")
+        .file("tests/good.rs", "#[test] fn good() {}")
+        .file("tests/bad.rs", "#[test] fn bad() { missing_function() }")
+        .build();
+
+    p.cargo("test --test bad")
+        .with_status(101)
+        .with_stderr_contains(
+            "[ERROR] could not compile `foo` (integration test `bad` at `tests/bad.rs`).",
+        )
+        .run();
+}
+
 #[cargo_test]
 fn dont_run_examples() {
     let p = project()
@@ -921,7 +941,7 @@ fn bin_without_name() {
 [ERROR] failed to parse manifest at `[..]`
 
 Caused by:
-  binary target bin.name is required",
+  invalid `[[bin]]` table `bin[0]`: `name` is required",
         )
         .run();
 }
@@ -1092,7 +1112,7 @@ fn example_without_name() {
 [ERROR] failed to parse manifest at `[..]`
 
 Caused by:
-  example target example.name is required",
+  invalid `[[example]]` table `example[0]`: `name` is required",
         )
         .run();
 }
@@ -1996,11 +2016,21 @@ fn bad_example() {
 
     p.cargo("run --example foo")
         .with_status(101)
-        .with_stderr("[ERROR] no example target named `foo`")
+        .with_stderr(
+            "\
+[ERROR] no example target named `foo`
+
+there are no example targets available",
+        )
         .run();
     p.cargo("run --bin foo")
         .with_status(101)
-        .with_stderr("[ERROR] no bin target named `foo`")
+        .with_stderr(
+            "\
+[ERROR] no bin target named `foo`
+
+there are no bin targets available",
+        )
         .run();
 }
 
@@ -2424,6 +2454,8 @@ fn bin_does_not_rebuild_tests() {
     p.cargo("test -v --no-run")
         .with_stderr(
             "\
+[DIRTY] foo v0.0.1 ([..]) bin \"foo\": source file `src/main.rs` changed
+[DIRTY] foo v0.0.1 ([..]) bin \"foo\": source file `src/main.rs` changed
 [COMPILING] foo v0.0.1 ([..])
 [RUNNING] `rustc [..] src/main.rs [..]`
 [RUNNING] `rustc [..] src/main.rs [..]`
@@ -3364,7 +3396,7 @@ fn json_artifact_includes_test_flag() {
         "fresh": false
     }
 
-    {"reason": "build-finished", "success": true}
+    {"reason": "build-finished", "success": true, "profile": "test", "profile_description": "unoptimized + debuginfo", "elapsed": "{...}"}
 "#,
         )
         .run();
@@ -3398,7 +3430,7 @@ fn json_artifact_includes_executable_for_library_tests() {
                 }
             }
 
-            {"reason": "build-finished", "success": true}
+            {"reason": "build-finished", "success": true, "profile": "test", "profile_description": "unoptimized + debuginfo", "elapsed": "{...}"}
         "#,
         )
         .run();
@@ -3434,7 +3466,7 @@ fn json_artifact_includes_executable_for_integration_tests() {
                 }
             }
 
-            {"reason": "build-finished", "success": true}
+            {"reason": "build-finished", "success": true, "profile": "test", "profile_description": "unoptimized + debuginfo", "elapsed": "{...}"}
         "#,
         )
         .run();
@@ -4055,3 +4087,53 @@ fn bin_env_for_test() {
     p.cargo("test --test check_env").run();
     p.cargo("check --test check_env").run();
 }
+
+#[cargo_test]
+fn forwards_args_after_double_dash_to_every_test() {
+    let check_args = r#"
+        fn main() {
+            let args: Vec<String> = std::env::args().skip(1).collect();
+            assert_eq!(args, ["a filter", "--verbose", ""]);
+            println!("args ok");
+        }
+    "#;
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+
+            [lib]
+            test = false
+            doctest = false
+            bench = false
+
+            [[test]]
+            name = "a"
+            harness = false
+
+            [[test]]
+            name = "b"
+            harness = false
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("tests/a.rs", check_args)
+        .file("tests/b.rs", check_args)
+        .build();
+
+    let mut execs = p.cargo("test -v --");
+    execs
+        .arg("a filter")
+        .arg("--verbose")
+        .arg("")
+        .with_stdout_contains_n("args ok", 2);
+    if cfg!(unix) {
+        execs
+            .with_stderr_contains("[RUNNING] `[..]/a-[..] 'a filter' --verbose ''`")
+            .with_stderr_contains("[RUNNING] `[..]/b-[..] 'a filter' --verbose ''`");
+    }
+    execs.run();
+}
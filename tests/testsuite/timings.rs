@@ -63,3 +63,73 @@ fn timings_works() {
 
     p.cargo("doc -Ztimings").masquerade_as_nightly_cargo().run();
 }
+
+#[cargo_test]
+fn timings_tag_errors_with_phase() {
+    let p = project()
+        .file("src/lib.rs", "pub fn f() { missing_function() }")
+        .build();
+
+    p.cargo("build -Ztimings")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr_contains("[ERROR] could not compile `foo`. (during compile, +[..]s)")
+        .run();
+
+    p.cargo("build")
+        .with_status(101)
+        .with_stderr_does_not_contain("[..](during compile[..]")
+        .run();
+}
+
+#[cargo_test]
+fn timings_summary() {
+    Package::new("dep", "0.1.0").publish();
+
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+
+            [dependencies]
+            dep = "0.1"
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .file("src/main.rs", "fn main() {}")
+        .build();
+
+    p.cargo("build -Ztimings=summary")
+        .masquerade_as_nightly_cargo()
+        .with_stderr_unordered(
+            "\
+[UPDATING] [..]
+[DOWNLOADING] crates ...
+[DOWNLOADED] dep v0.1.0 [..]
+[COMPILING] dep v0.1.0
+[COMPILING] foo v0.1.0 [..]
+[FINISHED] [..]
+      Timing 3 units compiled in [..]s total, [..]s on the critical path
+[..]s dep v0.1.0
+[..]s foo v0.1.0
+[..]s foo v0.1.0 bin \"foo\"
+",
+        )
+        .run();
+
+    p.change_file("src/main.rs", "fn main() { }");
+    p.cargo("build -Ztimings=summary")
+        .masquerade_as_nightly_cargo()
+        .with_stderr(
+            "\
+[COMPILING] foo v0.1.0 [..]
+      Timing 1 unit compiled in [..]s total, [..]s on the critical path
+[..]s foo v0.1.0 bin \"foo\"
+[FINISHED] [..]
+",
+        )
+        .run();
+}
@@ -0,0 +1,89 @@
+//! Tests for -Zwarnings-exit-code.
+
+use cargo_test_support::{basic_manifest, project};
+
+#[cargo_test]
+fn clean_build_exits_zero() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("build -Zwarnings-exit-code=2")
+        .masquerade_as_nightly_cargo()
+        .with_status(0)
+        .run();
+}
+
+#[cargo_test]
+fn compiler_warnings() {
+    let p = project()
+        .file("src/lib.rs", "pub fn f() { let x = 1; }")
+        .build();
+
+    p.cargo("build -Zwarnings-exit-code=2")
+        .masquerade_as_nightly_cargo()
+        .with_status(2)
+        .with_stderr_contains("[..]unused variable: `x`[..]")
+        .run();
+
+    // Warnings replayed from the cache of a fresh build count too.
+    p.cargo("build -Zwarnings-exit-code=2")
+        .masquerade_as_nightly_cargo()
+        .with_status(2)
+        .with_stderr_contains("[..]unused variable: `x`[..]")
+        .run();
+
+    // Without the flag warnings do not affect the exit code.
+    p.cargo("build").with_status(0).run();
+}
+
+#[cargo_test]
+fn cargo_warnings() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [package]
+            name = "foo"
+            version = "0.1.0"
+            unused-key = true
+            "#,
+        )
+        .file("src/lib.rs", "")
+        .build();
+
+    p.cargo("build -Zwarnings-exit-code=3")
+        .masquerade_as_nightly_cargo()
+        .with_status(3)
+        .with_stderr_contains("[WARNING] unused manifest key: package.unused-key")
+        .run();
+}
+
+#[cargo_test]
+fn failure_keeps_its_exit_code() {
+    let p = project()
+        .file("Cargo.toml", &basic_manifest("foo", "0.1.0"))
+        .file("src/lib.rs", "pub fn f() { let x = 1; } bad")
+        .build();
+
+    p.cargo("build -Zwarnings-exit-code=2")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr_contains("[ERROR] could not compile `foo`.")
+        .run();
+}
+
+#[cargo_test]
+fn invalid_exit_code() {
+    let p = project().file("src/lib.rs", "").build();
+
+    p.cargo("build -Zwarnings-exit-code=0")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr("[ERROR] flag -Zwarnings-exit-code expected a non-zero exit code, found: `0`")
+        .run();
+
+    p.cargo("build -Zwarnings-exit-code")
+        .masquerade_as_nightly_cargo()
+        .with_status(101)
+        .with_stderr("[ERROR] flag -Zwarnings-exit-code requires an exit code")
+        .run();
+}
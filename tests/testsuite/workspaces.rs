@@ -1065,6 +1065,10 @@ Caused by:
 
 Caused by:
   expected an equals, found eof at line 1 column 5
+
+    |
+  1 | asdf
+    |     ^
      Created binary (application) `bar` package
 ",
         )
@@ -1564,6 +1568,49 @@ fn excluded_simple() {
     assert!(p.root().join("foo/target").is_dir());
 }
 
+#[cargo_test]
+fn excluded_with_workspace_pointer() {
+    let p = project()
+        .file(
+            "Cargo.toml",
+            r#"
+            [project]
+            name = "ws"
+            version = "0.1.0"
+            authors = []
+
+            [workspace]
+            exclude = ["foo"]
+        "#,
+        )
+        .file("src/lib.rs", "")
+        .file(
+            "foo/Cargo.toml",
+            r#"
+            [project]
+            name = "foo"
+            version = "0.1.0"
+            authors = []
+            workspace = ".."
+        "#,
+        )
+        .file("foo/src/lib.rs", "");
+    let p = p.build();
+
+    p.cargo("build")
+        .cwd("foo")
+        .with_status(101)
+        .with_stderr(
+            "\
+[ERROR] package `[CWD]/Cargo.toml` has a `package.workspace` key pointing to the workspace \
+`[ROOT]/foo/Cargo.toml`, but that workspace excludes it with the `workspace.exclude` entry `foo`
+Remove the entry to make the package a member of the workspace, or remove the \
+`package.workspace` key to keep it out of the workspace.
+",
+        )
+        .run();
+}
+
 #[cargo_test]
 fn exclude_members_preferred() {
     let p = project()
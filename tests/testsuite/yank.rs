@@ -3,8 +3,8 @@
 use std::fs;
 
 use cargo_test_support::paths::CargoPathExt;
-use cargo_test_support::project;
 use cargo_test_support::registry;
+use cargo_test_support::{cargo_process, project};
 
 fn setup(name: &str, version: &str) {
     let dir = registry::api_path().join(format!("api/v1/crates/{}/{}", name, version));
@@ -46,3 +46,26 @@ Caused by:
         )
         .run();
 }
+
+#[cargo_test]
+fn refused_outside_project() {
+    registry::init();
+    setup("foo", "0.0.1");
+    fs::write(
+        registry::api_path().join("api/v1/crates/foo/0.0.1/yank"),
+        r#"{"ok": false}"#,
+    )
+    .unwrap();
+
+    cargo_process("yank --vers 0.0.1 foo --token sekrit")
+        .with_status(101)
+        .with_stderr(
+            "    Updating `[..]` index
+        Yank foo:0.0.1
+error: failed to yank
+
+Caused by:
+  the registry refused to yank foo 0.0.1",
+        )
+        .run();
+}